@@ -0,0 +1,152 @@
+pub mod dto {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{common::service::Paginated, home::dto::Image};
+
+    #[derive(Deserialize)]
+    pub struct RepositoriesQuery {
+        /// Walks the catalog with the registry cursor from the repository after this one instead
+        /// of paginating by page number, empty for the catalog start
+        pub last: Option<String>,
+    }
+
+    /// Body of the API errors that have something to say beyond their status
+    #[derive(Serialize)]
+    pub struct ErrorResponse {
+        pub error: String,
+    }
+
+    #[derive(Serialize)]
+    pub struct TagDeletionResponse {
+        pub digest: String,
+        /// Tags that pointed to the deleted manifest, the requested one included
+        pub deleted_tags: Vec<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct RepositoriesResponse {
+        #[serde(flatten)]
+        pub repositories: Paginated<Image>,
+        /// Pass as `last` to get the following page in cursor mode, `null` on the last page and
+        /// when paginating by page number
+        pub next: Option<String>,
+    }
+}
+
+pub mod handler {
+    use axum::{
+        Json,
+        extract::{Path, Query, State},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    };
+
+    use crate::{
+        AppState,
+        api::dto::{ErrorResponse, RepositoriesQuery, RepositoriesResponse, TagDeletionResponse},
+        auth::middleware::{Authenticated, RequireAdmin},
+        common::{
+            handler::PaginationQuery,
+            service::{Paginated, env},
+        },
+        error::service::Error,
+        home::{self, dto::ImageSort},
+        image::{self, dto::TagDeletion},
+    };
+
+    pub async fn repositories(
+        Query(pagination): Query<PaginationQuery>,
+        Query(RepositoriesQuery { last }): Query<RepositoriesQuery>,
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        _: Authenticated,
+    ) -> Result<Json<RepositoriesResponse>, Response> {
+        if pagination.size == Some(0) {
+            return Err(bad_request("size must be at least 1".to_owned()));
+        }
+        let requested_page = pagination
+            .goto
+            .map_or(pagination.page, |goto| Some(goto.saturating_sub(1)));
+        let cursor_mode = last.is_some();
+        let (repositories, next) = match last {
+            Some(last) => home::service::get_images_after(
+                &registry_api_client,
+                &last,
+                pagination.size.unwrap_or_else(env::default_page_size),
+                false,
+                true,
+            )
+            .await
+            .map(|(images, next)| (Paginated::whole(images), next)),
+            None => home::service::get_images(
+                registry_api_client,
+                pagination,
+                ImageSort::default(),
+                false,
+                true,
+            )
+            .await
+            .map(|images| (images, None)),
+        }
+        .map_err(|_| StatusCode::BAD_GATEWAY.into_response())?;
+        // The pages clamp a page past the end to the last one, API clients are told instead
+        if let Some(requested_page) = requested_page
+            && !cursor_mode
+            && requested_page != repositories.page
+        {
+            return Err(bad_request(format!(
+                "page {requested_page} is past the last page {}",
+                repositories.page
+            )));
+        }
+        Ok(Json(RepositoriesResponse { repositories, next }))
+    }
+
+    fn bad_request(error: String) -> Response {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response()
+    }
+
+    /// Deletes the manifest `tag` points to, 404 when the tag does not exist and 403 when it is
+    /// protected
+    pub async fn delete_tag(
+        Path((image_name, tag_name)): Path<(String, String)>,
+        State(AppState {
+            registry_api_client,
+            catalog_snapshot,
+            ..
+        }): State<AppState>,
+        RequireAdmin(username): RequireAdmin,
+    ) -> Result<Json<TagDeletionResponse>, StatusCode> {
+        let result =
+            image::service::delete_tag_by_name(&registry_api_client, &image_name, &tag_name).await;
+        if let Ok(Some(deletion)) = &result
+            && deletion.deleted
+        {
+            home::service::patch_snapshot_tag_count(
+                &catalog_snapshot,
+                &image_name,
+                deletion.tags.len(),
+            );
+        }
+        image::service::record_tag_deletion(username, &image_name, &tag_name, &result).await;
+        match result {
+            Ok(Some(TagDeletion {
+                digest,
+                tags,
+                deleted: true,
+                ..
+            })) => Ok(Json(TagDeletionResponse {
+                digest,
+                deleted_tags: tags,
+            })),
+            Ok(_) => Err(StatusCode::NOT_FOUND),
+            Err(Error::ReadOnly | Error::Protected) => Err(StatusCode::FORBIDDEN),
+            Err(Error::Timeout(_)) => Err(StatusCode::GATEWAY_TIMEOUT),
+            Err(Error::Unknown | Error::Unreachable(_) | Error::Unauthorized(_)) => {
+                Err(StatusCode::BAD_GATEWAY)
+            }
+        }
+    }
+}
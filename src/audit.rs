@@ -0,0 +1,238 @@
+pub mod dto {
+    use serde::{Deserialize, Serialize};
+
+    /// One line of the audit log
+    #[derive(Serialize, Deserialize)]
+    pub struct AuditEntry {
+        /// RFC 3339
+        pub timestamp: String,
+        pub username: String,
+        /// Empty in entries written before several registries could be browsed
+        #[serde(default)]
+        pub registry: String,
+        pub deletion: Deletion,
+        pub image: String,
+        /// `None` for actions spanning several manifests
+        pub digest: Option<String>,
+        pub tags: Vec<String>,
+        /// Tags whose digest could not be resolved, they may have pointed to the manifest too
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub unresolved_tags: Vec<String>,
+        pub success: bool,
+    }
+
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Deletion {
+        Tag,
+        AllTags,
+        DanglingManifest,
+        OldTags,
+        Repository,
+    }
+
+    impl Deletion {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Tag => "Delete tag",
+                Self::AllTags => "Delete all tags",
+                Self::DanglingManifest => "Delete dangling manifest",
+                Self::OldTags => "Delete old tags",
+                Self::Repository => "Delete repository",
+            }
+        }
+    }
+}
+
+pub mod handler {
+    use joy_error::log::ResultLogExt;
+    use maud::Markup;
+
+    use crate::{
+        audit::{service, view},
+        auth::middleware::RequireAdmin,
+        common::service::env,
+    };
+
+    const DISPLAYED_ENTRIES: usize = 100;
+
+    pub async fn index(_: RequireAdmin) -> Markup {
+        if env::audit_log_path().is_none() {
+            return view::index(None);
+        }
+        let entries = service::last_entries(DISPLAYED_ENTRIES)
+            .await
+            .error()
+            .log_err()
+            .unwrap_or_default();
+        view::index(Some(&entries))
+    }
+}
+
+pub mod service {
+    use std::io::SeekFrom;
+
+    use anyhow::Context;
+    use joy_error::log::ResultLogExt;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+    use crate::{
+        audit::dto::{AuditEntry, Deletion},
+        common::service::env,
+    };
+
+    /// Keeps concurrent deletions from interleaving their lines
+    static AUDIT_LOG_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    /// Emits the entry as a `tracing` event with the `audit` target and appends it to
+    /// `AUDIT_LOG_PATH` when set, a failed write is logged but never fails the deletion
+    pub async fn record(
+        username: &str,
+        deletion: Deletion,
+        image: &str,
+        digest: Option<&str>,
+        tags: Vec<String>,
+        unresolved_tags: Vec<String>,
+        success: bool,
+    ) {
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            username: username.to_owned(),
+            registry: env::registry_name().to_owned(),
+            deletion,
+            image: image.to_owned(),
+            digest: digest.map(ToOwned::to_owned),
+            tags,
+            unresolved_tags,
+            success,
+        };
+        tracing::info!(
+            target: "audit",
+            username = entry.username,
+            registry = entry.registry,
+            deletion = entry.deletion.as_str(),
+            image = entry.image,
+            digest = entry.digest,
+            tags = ?entry.tags,
+            unresolved_tags = ?entry.unresolved_tags,
+            success = entry.success,
+        );
+        if let Some(path) = env::audit_log_path() {
+            let _ = append(path, &entry).await.error().log_err();
+        }
+    }
+
+    async fn append(path: &str, entry: &AuditEntry) -> anyhow::Result<()> {
+        let line = format!("{}\n", serde_json::to_string(entry)?);
+        let _guard = AUDIT_LOG_LOCK.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("could not open audit log {path}"))?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Most recent entries first, unreadable lines are skipped. The log is read backwards one
+    /// chunk at a time, until `count` entries are found
+    pub async fn last_entries(count: usize) -> anyhow::Result<Vec<AuditEntry>> {
+        const CHUNK_SIZE: u64 = 64 * 1024;
+
+        let path = env::audit_log_path().context("AUDIT_LOG_PATH is not set")?;
+        let mut file = match tokio::fs::File::open(path).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err).context(format!("could not open audit log {path}")),
+        };
+        let mut end = file.metadata().await?.len();
+        let mut entries = Vec::new();
+        // Start of the earliest line read, it may begin in the chunk before
+        let mut partial_line = Vec::new();
+        while end > 0 && entries.len() < count {
+            let start = end.saturating_sub(CHUNK_SIZE);
+            let mut chunk = vec![0; usize::try_from(end - start)?];
+            file.seek(SeekFrom::Start(start)).await?;
+            file.read_exact(&mut chunk)
+                .await
+                .with_context(|| format!("could not read audit log {path}"))?;
+            chunk.append(&mut partial_line);
+            end = start;
+            let lines_start = match chunk.iter().position(|&byte| byte == b'\n') {
+                _ if start == 0 => 0,
+                Some(newline) => newline + 1,
+                None => {
+                    partial_line = chunk;
+                    continue;
+                }
+            };
+            entries.extend(
+                chunk[lines_start..]
+                    .split(|&byte| byte == b'\n')
+                    .rev()
+                    .filter_map(|line| serde_json::from_slice::<AuditEntry>(line).ok())
+                    .take(count - entries.len()),
+            );
+            chunk.truncate(lines_start);
+            partial_line = chunk;
+        }
+        Ok(entries)
+    }
+}
+
+pub mod view {
+    use maud::{Markup, html};
+
+    use crate::{audit::dto::AuditEntry, auth::dto::Role, common};
+
+    /// `None` when there is no log file to read back
+    pub fn index(entries: Option<&[AuditEntry]>) -> Markup {
+        common::view::page()
+            .role(Role::Admin)
+            .content(html! {
+                .m-2 {
+                    h1 { "Audit log" }
+                    @match entries {
+                        None => p { "Set AUDIT_LOG_PATH to keep deletions in a file and list them here." },
+                        Some([]) => p { "No deletion recorded yet." },
+                        Some(entries) => table .table .table-striped .table-bordered .align-middle .text-center {
+                            thead {
+                                tr {
+                                    th { "Date" }
+                                    th { "User" }
+                                    th { "Registry" }
+                                    th { "Action" }
+                                    th { "Image" }
+                                    th { "Digest" }
+                                    th { "Tags" }
+                                    th { "Unresolved tags" }
+                                    th { "Result" }
+                                }
+                            }
+                            tbody {
+                                @for entry in entries {
+                                    tr {
+                                        td { (entry.timestamp) }
+                                        td { (entry.username) }
+                                        td { (entry.registry) }
+                                        td { (entry.deletion.as_str()) }
+                                        td { a href=(format!("/{}", entry.image)) { (entry.image) } }
+                                        td { code { (entry.digest.as_deref().unwrap_or("")) } }
+                                        td { (entry.tags.join(", ")) }
+                                        td { (entry.unresolved_tags.join(", ")) }
+                                        @if entry.success {
+                                            td .text-success { "Success" }
+                                        } @else {
+                                            td .text-danger { "Failed" }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
+            })
+            .call()
+    }
+}
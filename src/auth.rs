@@ -1,34 +1,161 @@
 pub mod middleware {
-    use axum::{RequestPartsExt, extract::FromRequestParts, response::Redirect};
+    use axum::{
+        RequestPartsExt,
+        extract::{FromRequestParts, Request},
+        http::{HeaderMap, StatusCode, header},
+        middleware::Next,
+        response::{IntoResponse, Redirect, Response},
+    };
     use axum_extra::extract::CookieJar;
     use joy_error::ResultInfallibleExt;
 
-    use crate::auth::{self, service::CookieJarExtUtils};
+    use crate::{
+        auth::{
+            self,
+            dto::{AuthMode, Role},
+            service::CookieJarExtUtils,
+        },
+        common::{
+            self,
+            service::{env, forwarded},
+        },
+    };
 
-    pub struct Authenticated;
+    pub struct Authenticated(pub Role);
 
     impl<S> FromRequestParts<S> for Authenticated
     where
         S: Send + Sync,
     {
-        type Rejection = (CookieJar, Redirect);
+        type Rejection = Response;
 
         async fn from_request_parts(
             parts: &mut axum::http::request::Parts,
             _: &S,
         ) -> Result<Self, Self::Rejection> {
+            // Browsers attach cached basic credentials to requests sent by other sites too, and the
+            // session cookie as well with `COOKIE_SAME_SITE=None` or from a host of `COOKIE_DOMAIN`
+            if !parts.method.is_safe() && !auth::service::is_same_origin(&parts.headers) {
+                tracing::warn!("Cross-site {} {} refused", parts.method, parts.uri.path());
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    common::view::error_page(
+                        None,
+                        StatusCode::FORBIDDEN,
+                        "Changes can only be made from the explorer pages.",
+                    ),
+                )
+                    .into_response());
+            }
+            if env::auth_mode() == AuthMode::Basic {
+                return auth::service::basic_credentials(&parts.headers)
+                    .and_then(|(username, password)| {
+                        auth::service::authenticate(&username, &password)
+                    })
+                    .map(Self)
+                    .ok_or_else(|| {
+                        tracing::info!("No valid basic credentials on {}", parts.uri.path());
+                        (
+                            StatusCode::UNAUTHORIZED,
+                            [(
+                                header::WWW_AUTHENTICATE,
+                                format!(
+                                    "Basic realm=\"{}\", charset=\"UTF-8\"",
+                                    env::instance_title().replace('"', "'")
+                                ),
+                            )],
+                            common::view::error_page(
+                                None,
+                                StatusCode::UNAUTHORIZED,
+                                "The explorer credentials are required.",
+                            ),
+                        )
+                            .into_response()
+                    });
+            }
             let cookie_jar = parts.extract::<CookieJar>().await.unwrap_infallible();
-            let token = cookie_jar.get_auth_token();
-            let redirection = || Redirect::to(&format!("/auth/login?from={}", parts.uri.path()));
-            if let Some(token) = token {
-                let (good_username, good_password) = auth::service::get_credentials();
-                let hash = auth::service::hash_credentials(&good_username, &good_password);
-                (token == hash)
-                    .then_some(Self)
-                    .ok_or_else(|| (cookie_jar.remove_auth_token(), redirection()))
-            } else {
-                Err((cookie_jar, redirection()))
+            let path = parts.uri.path();
+            match cookie_jar.get_auth_token() {
+                Some(token) => match auth::service::role_for_token(token) {
+                    Some(role) if !auth::service::is_idle(&cookie_jar, token) => {
+                        return Ok(Self(role));
+                    }
+                    Some(_) => tracing::info!("Idle session on {path}"),
+                    None => tracing::info!(
+                        "Stale auth token on {path}, the explorer credentials probably changed"
+                    ),
+                },
+                None => tracing::info!("No auth token on {path}"),
             }
+            // Both paths end the same way, whether the cookie goes away is up to the operator
+            let cookie_jar = if env::auth_clear_stale_cookie() {
+                cookie_jar.remove_auth_token()
+            } else {
+                cookie_jar
+            };
+            let from = parts
+                .uri
+                .path_and_query()
+                .map_or(path, |path_and_query| path_and_query.as_str());
+            let login = format!(
+                "/auth/login?{}",
+                serde_urlencoded::to_string([("from", from)]).unwrap_or_default()
+            );
+            Err((
+                cookie_jar,
+                Redirect::to(&forwarded::external_url(&parts.headers, &login)),
+            )
+                .into_response())
+        }
+    }
+
+    /// Stamps the activity of a live session once its request is answered, see
+    /// `EXPLORER_IDLE_TIMEOUT_MINUTES`. Login and logout handle the stamp themselves, pages
+    /// reloading on their own do not count as activity
+    pub async fn slide_idle_timeout(
+        cookie_jar: CookieJar,
+        headers: HeaderMap,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let live_token = env::explorer_idle_timeout_minutes()
+            .filter(|_| env::auth_mode() == AuthMode::Form)
+            .filter(|_| !request.uri().path().starts_with("/auth/"))
+            .filter(|_| auth::service::is_user_initiated(request.uri().path(), &headers))
+            .and(cookie_jar.get_auth_token())
+            .filter(|token| {
+                auth::service::role_for_token(token).is_some()
+                    && !auth::service::is_idle(&cookie_jar, token)
+            })
+            .map(ToOwned::to_owned);
+        let response = next.run(request).await;
+        match live_token {
+            Some(token) => (
+                cookie_jar.set_activity(&token, forwarded::cookie_secure(&headers)),
+                response,
+            )
+                .into_response(),
+            None => response,
+        }
+    }
+
+    /// Authenticated as an admin, viewers get a 403. Holds the admin username
+    pub struct RequireAdmin(pub &'static str);
+
+    impl<S> FromRequestParts<S> for RequireAdmin
+    where
+        S: Send + Sync,
+    {
+        type Rejection = Response;
+
+        async fn from_request_parts(
+            parts: &mut axum::http::request::Parts,
+            state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            let Authenticated(role) = Authenticated::from_request_parts(parts, state).await?;
+            (role == Role::Admin)
+                .then(|| Self(env::explorer_username()))
+                .ok_or_else(|| StatusCode::FORBIDDEN.into_response())
         }
     }
 }
@@ -46,7 +173,8 @@ pub mod dto {
     pub struct LoginQuery {
         pub error: Option<LoginError>,
         pub from: Option<String>,
-        pub username: Option<String>,
+        /// Set by the default logout redirect, whatever its value
+        pub logged_out: Option<String>,
     }
 
     #[derive(Deserialize)]
@@ -54,6 +182,36 @@ pub mod dto {
         pub from: Option<String>,
     }
 
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub enum Role {
+        /// Can delete tags and manifests
+        Admin,
+        /// Can only browse
+        Viewer,
+    }
+
+    /// How users prove who they are
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum AuthMode {
+        /// Login page, then the auth cookie
+        #[default]
+        Form,
+        /// `Authorization: Basic` on every request, the browser prompts for it
+        Basic,
+    }
+
+    impl std::str::FromStr for AuthMode {
+        type Err = ();
+
+        fn from_str(value: &str) -> Result<Self, Self::Err> {
+            match value.to_lowercase().as_str() {
+                "form" => Ok(Self::Form),
+                "basic" => Ok(Self::Basic),
+                _ => Err(()),
+            }
+        }
+    }
+
     #[derive(Deserialize)]
     pub enum LoginError {
         #[serde(rename = "invalid_credentials")]
@@ -62,47 +220,84 @@ pub mod dto {
 }
 
 pub mod handler {
-    use axum::{Form, extract::Query, response::Redirect};
+    use axum::{Form, extract::Query, http::HeaderMap, response::Redirect};
     use axum_extra::extract::CookieJar;
     use maud::Markup;
 
-    use crate::auth::{
-        dto::{self, AuthenticateQuery, LoginForm, LoginQuery},
-        service::{self, CookieJarExtUtils},
-        view,
+    use crate::{
+        auth::{
+            dto::{self, AuthMode, AuthenticateQuery, LoginForm, LoginQuery},
+            service::{self, CookieJarExtUtils},
+            view,
+        },
+        common::{
+            self,
+            service::{env, forwarded},
+        },
     };
 
     pub async fn login_index(
         Query(LoginQuery {
             error,
             from,
-            username,
+            logged_out,
         }): Query<LoginQuery>,
-    ) -> Result<Markup, Redirect> {
-        Ok(view::login_index(error, from, username.as_deref()))
+        cookie_jar: CookieJar,
+    ) -> Result<(CookieJar, Markup), Redirect> {
+        // The browser prompts for the credentials, there is no form to fill
+        if env::auth_mode() == AuthMode::Basic {
+            return Err(Redirect::to("/"));
+        }
+        let (cookie_jar, username) = cookie_jar.take_login_username();
+        Ok((
+            cookie_jar,
+            view::login_index(error, from, username.as_deref(), logged_out.is_some()),
+        ))
     }
 
     pub async fn logout(cookie_jar: CookieJar) -> (CookieJar, Redirect) {
-        (cookie_jar.remove_auth_token(), Redirect::to("/"))
+        (
+            cookie_jar.remove_auth_token(),
+            Redirect::to(env::logout_redirect_url()),
+        )
     }
 
     pub async fn authenticate(
+        headers: HeaderMap,
         cookie_jar: CookieJar,
         Query(AuthenticateQuery { from }): Query<AuthenticateQuery>,
         Form(LoginForm { username, password }): Form<dto::LoginForm>,
     ) -> (CookieJar, Redirect) {
-        if service::authenticate(&username, &password) {
+        if service::authenticate(&username, &password).is_some() {
+            let secure = forwarded::cookie_secure(&headers);
+            if secure && service::is_likely_plain_http(&headers) {
+                tracing::warn!(
+                    "Login over what looks like plain HTTP: the browser will drop the Secure auth cookie and redirect back to the login page. Serve the explorer behind HTTPS, set TRUSTED_PROXY=true behind a TLS-terminating proxy or set COOKIE_SECURE=false"
+                );
+            }
             let hash = service::hash_credentials(&username, &password);
+            // Only paths of the explorer, `from` must not send the user to another site
+            let from = from
+                .as_deref()
+                .and_then(common::service::local_redirect_target)
+                .unwrap_or("/");
             (
-                cookie_jar.set_auth_token(hash),
-                Redirect::to(from.as_deref().unwrap_or("/")),
+                cookie_jar
+                    .set_activity(&hash, secure)
+                    .set_auth_token(hash, secure),
+                Redirect::to(&forwarded::external_url(&headers, from)),
             )
         } else {
+            // The username is carried by a cookie, not to end up in logs and browser history
+            let mut params = vec![("error", "invalid_credentials")];
+            if let Some(from) = &from {
+                params.push(("from", from));
+            }
             (
-                cookie_jar,
+                cookie_jar.set_login_username(&username, forwarded::cookie_secure(&headers)),
                 Redirect::to(&format!(
-                    "/auth/login?error=invalid_credentials&username={username}{}",
-                    from.map_or(String::new(), |from| format!("&from={from}"))
+                    "/auth/login?{}",
+                    serde_urlencoded::to_string(params).unwrap_or_default()
                 )),
             )
         }
@@ -110,20 +305,99 @@ pub mod handler {
 }
 
 pub mod service {
-    use std::env;
-
+    use axum::http::{HeaderMap, header};
     use axum_extra::extract::{
         CookieJar,
         cookie::{Cookie, SameSite},
     };
+    use base64::Engine;
+    use hmac::{Hmac, Mac};
     use sha2::{Digest, Sha256};
-    use time::macros::datetime;
+    use time::{OffsetDateTime, macros::datetime};
+
+    use crate::{
+        auth::dto::{AuthMode, Role},
+        common::service::{env, forwarded},
+    };
 
     pub const AUTH_TOKEN_COOKIE_NAME: &str = "auth_token";
+    /// `<unix seconds>.<signature>` of the last request of the session
+    pub const AUTH_ACTIVITY_COOKIE_NAME: &str = "auth_activity";
+    /// Username of a failed login, prefills the form it is sent back to
+    pub const LOGIN_USERNAME_COOKIE_NAME: &str = "login_username";
 
-    pub fn authenticate(username: &str, password: &str) -> bool {
-        let (good_username, good_password) = get_credentials();
-        username == good_username && password == good_password
+    pub fn authenticate(username: &str, password: &str) -> Option<Role> {
+        accounts()
+            .into_iter()
+            .find_map(|(role, good_username, good_password)| {
+                (username == good_username && password == good_password).then_some(role)
+            })
+    }
+
+    /// Username and password of an `Authorization: Basic` header, the scheme is case insensitive
+    pub fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+        let (scheme, encoded) = headers
+            .get(header::AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("basic") {
+            return None;
+        }
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .ok()?;
+        let credentials = String::from_utf8(decoded).ok()?;
+        let (username, password) = credentials.split_once(':')?;
+        Some((username.to_owned(), password.to_owned()))
+    }
+
+    /// Whether the request was sent by a page of the explorer, from `Sec-Fetch-Site` or else
+    /// `Origin`. Clients sending neither are not browsers
+    pub fn is_same_origin(headers: &HeaderMap) -> bool {
+        if let Some(site) = headers
+            .get("sec-fetch-site")
+            .and_then(|site| site.to_str().ok())
+        {
+            return matches!(site, "same-origin" | "none");
+        }
+        match headers
+            .get(header::ORIGIN)
+            .and_then(|origin| origin.to_str().ok())
+        {
+            Some(origin) => forwarded::host(headers).is_some_and(|host| {
+                origin
+                    .split_once("://")
+                    .is_some_and(|(_, origin_host)| origin_host.eq_ignore_ascii_case(host))
+            }),
+            None => true,
+        }
+    }
+
+    /// Account the request authenticates as, even with an idle session. Only tells who made the
+    /// request, `Authenticated` decides whether it is let in
+    pub fn request_username(headers: &HeaderMap) -> Option<&'static str> {
+        let role = match env::auth_mode() {
+            AuthMode::Basic => {
+                let (username, password) = basic_credentials(headers)?;
+                authenticate(&username, &password)?
+            }
+            AuthMode::Form => role_for_token(CookieJar::from_headers(headers).get_auth_token()?)?,
+        };
+        // Each role has a single account
+        accounts()
+            .into_iter()
+            .find(|(account_role, _, _)| *account_role == role)
+            .map(|(_, username, _)| username)
+    }
+
+    /// The session token is the hash of the account credentials, so it also tells the role
+    pub fn role_for_token(token: &str) -> Option<Role> {
+        accounts()
+            .into_iter()
+            .find_map(|(role, username, password)| {
+                (token == hash_credentials(username, password)).then_some(role)
+            })
     }
 
     pub fn hash_credentials(username: &str, password: &str) -> String {
@@ -132,73 +406,225 @@ pub mod service {
         format!("{:X}", hasher.finalize())
     }
 
-    pub fn get_credentials() -> (String, String) {
-        let username =
-            env::var("EXPLORER_USERNAME").expect("EXPLORER_USERNAME environment variable not set");
-        let password =
-            env::var("EXPLORER_PASSWORD").expect("EXPLORER_PASSWORD environment variable not set");
-        (username, password)
+    /// Whether the session of `token` has been idle longer than `EXPLORER_IDLE_TIMEOUT_MINUTES`,
+    /// a missing or forged activity stamp counts as idle
+    pub fn is_idle(cookie_jar: &CookieJar, token: &str) -> bool {
+        let Some(idle_timeout) = env::explorer_idle_timeout_minutes() else {
+            return false;
+        };
+        let Some((seconds, signature)) = cookie_jar
+            .get(AUTH_ACTIVITY_COOKIE_NAME)
+            .and_then(|cookie| cookie.value().split_once('.'))
+        else {
+            return true;
+        };
+        let Ok(last_activity) = seconds.parse::<i64>() else {
+            return true;
+        };
+        signature != activity_signature(token, last_activity)
+            || OffsetDateTime::now_utc().unix_timestamp() - last_activity
+                > i64::try_from(idle_timeout.saturating_mul(60)).unwrap_or(i64::MAX)
+    }
+
+    /// HMAC keyed with the admin password, someone holding a stolen cookie cannot push the
+    /// stamp forward
+    fn activity_signature(token: &str, last_activity: i64) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(env::explorer_password().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(format!("{token}.{last_activity}").as_bytes());
+        format!("{:X}", mac.finalize().into_bytes())
+    }
+
+    /// Event streams, and navigations the user did not trigger such as `live-refresh` reloads
+    /// or a `meta` refresh, are not activity. Only browsers send `Sec-Fetch-*`, other clients
+    /// always count
+    pub fn is_user_initiated(path: &str, headers: &HeaderMap) -> bool {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        path != "/events"
+            && (header("sec-fetch-mode") != Some("navigate")
+                || header("sec-fetch-user") == Some("?1"))
+    }
+
+    /// `SameSite` of the session cookies, a `SameSite=None` cookie falls back to `Lax` when it
+    /// cannot be `Secure`
+    fn session_same_site(secure: bool) -> SameSite {
+        match env::cookie_same_site() {
+            SameSite::None if !secure => SameSite::Lax,
+            same_site => same_site,
+        }
+    }
+
+    /// The admin comes first so that it wins when the viewer shares its credentials
+    fn accounts() -> Vec<(Role, &'static str, &'static str)> {
+        let mut accounts = vec![(
+            Role::Admin,
+            env::explorer_username(),
+            env::explorer_password(),
+        )];
+        if let Some((username, password)) = env::explorer_viewer_credentials() {
+            accounts.push((Role::Viewer, username, password));
+        }
+        accounts
+    }
+
+    /// The explorer does not terminate TLS, so a request is considered HTTPS only when a trusted
+    /// proxy says so. Browsers accept `Secure` cookies from `localhost` even over HTTP
+    pub fn is_likely_plain_http(headers: &HeaderMap) -> bool {
+        let forwarded_https =
+            forwarded::proto(headers).is_some_and(|proto| proto.eq_ignore_ascii_case("https"));
+        let localhost = forwarded::host(headers)
+            .map(|host| host.rsplit_once(':').map_or(host, |(host, _)| host))
+            .is_some_and(|host| matches!(host, "localhost" | "127.0.0.1" | "[::1]"));
+        !forwarded_https && !localhost
     }
 
     #[easy_ext::ext(CookieJarExtUtils)]
     impl CookieJar {
-        pub fn set_auth_token(self, token: String) -> Self {
-            self.add(
-                Cookie::build((AUTH_TOKEN_COOKIE_NAME, token))
-                    .http_only(true)
-                    .secure(true)
-                    .path("/")
-                    .same_site(SameSite::Strict)
-                    .expires(datetime!(9999-01-01 0:00 UTC)),
-            )
+        /// `secure` comes from `forwarded::cookie_secure` for the login request
+        pub fn set_auth_token(self, token: String, secure: bool) -> Self {
+            let mut cookie = Cookie::build((AUTH_TOKEN_COOKIE_NAME, token))
+                .http_only(true)
+                .secure(secure)
+                .path("/")
+                .same_site(session_same_site(secure))
+                .expires(datetime!(9999-01-01 0:00 UTC));
+            if let Some(domain) = env::cookie_domain() {
+                cookie = cookie.domain(domain);
+            }
+            self.add(cookie)
         }
 
+        /// Stamps the session of `token` as active now, only with
+        /// `EXPLORER_IDLE_TIMEOUT_MINUTES`
+        pub fn set_activity(self, token: &str, secure: bool) -> Self {
+            let Some(idle_timeout) = env::explorer_idle_timeout_minutes() else {
+                return self;
+            };
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            let mut cookie = Cookie::build((
+                AUTH_ACTIVITY_COOKIE_NAME,
+                format!("{now}.{}", activity_signature(token, now)),
+            ))
+            .http_only(true)
+            .secure(secure)
+            .path("/")
+            .same_site(session_same_site(secure))
+            .max_age(time::Duration::minutes(
+                i64::try_from(idle_timeout)
+                    .map_or(i64::MAX / 60, |minutes| minutes.min(i64::MAX / 60)),
+            ));
+            if let Some(domain) = env::cookie_domain() {
+                cookie = cookie.domain(domain);
+            }
+            self.add(cookie)
+        }
+
+        /// Browsers only remove a cookie matching the path and domain it was set with
         pub fn remove_auth_token(self) -> Self {
-            self.remove(Cookie::build(AUTH_TOKEN_COOKIE_NAME).path("/").build())
+            [AUTH_TOKEN_COOKIE_NAME, AUTH_ACTIVITY_COOKIE_NAME]
+                .into_iter()
+                .fold(self, |cookie_jar, name| {
+                    let mut cookie = Cookie::build(name).path("/");
+                    if let Some(domain) = env::cookie_domain() {
+                        cookie = cookie.domain(domain);
+                    }
+                    cookie_jar.remove(cookie.build())
+                })
         }
 
         pub fn get_auth_token(&self) -> Option<&str> {
             self.get(AUTH_TOKEN_COOKIE_NAME).map(Cookie::value)
         }
+
+        /// Only lives until the login page is shown again
+        pub fn set_login_username(self, username: &str, secure: bool) -> Self {
+            self.add(
+                Cookie::build((
+                    LOGIN_USERNAME_COOKIE_NAME,
+                    serde_urlencoded::to_string([("username", username)]).unwrap_or_default(),
+                ))
+                .http_only(true)
+                .secure(secure)
+                .path("/auth")
+                .same_site(SameSite::Strict)
+                .max_age(time::Duration::minutes(1)),
+            )
+        }
+
+        /// Reads the username of the failed login and removes it so it prefills the form once
+        pub fn take_login_username(self) -> (CookieJar, Option<String>) {
+            let Some(cookie) = self.get(LOGIN_USERNAME_COOKIE_NAME) else {
+                return (self, None);
+            };
+            let username = serde_urlencoded::from_str::<Vec<(String, String)>>(cookie.value())
+                .ok()
+                .and_then(|params| {
+                    params
+                        .into_iter()
+                        .find_map(|(key, value)| (key == "username").then_some(value))
+                });
+            (
+                self.remove(
+                    Cookie::build(LOGIN_USERNAME_COOKIE_NAME)
+                        .path("/auth")
+                        .build(),
+                ),
+                username,
+            )
+        }
     }
 }
 
 pub mod view {
     use maud::{Markup, html};
 
-    use crate::{auth::dto::LoginError, common};
+    use crate::{
+        auth::dto::LoginError,
+        common,
+        i18n::{self, service::t},
+    };
 
     pub fn login_index(
         error: Option<LoginError>,
         from: Option<String>,
         username: Option<&str>,
+        logged_out: bool,
     ) -> Markup {
         let authenticate_action = from.map_or_else(
             || "/auth/authenticate".to_string(),
-            |from| format!("/auth/authenticate?from={from}"),
+            |from| {
+                format!(
+                    "/auth/authenticate?{}",
+                    serde_urlencoded::to_string([("from", from)]).unwrap_or_default()
+                )
+            },
         );
 
         let error = error.map(|error| match error {
-            LoginError::InvalidCredentials => "Invalid username or password",
+            LoginError::InvalidCredentials => t("invalid_credentials"),
         });
 
         html! {
-            html {
-                (common::view::head())
+            html lang=(i18n::service::locale().as_str()) {
+                (common::view::head(Some(t("login"))))
                 body {
-                    (common::view::header())
+                    (common::view::header(None))
 
                     @if let Some(error) = error {
                         .alert .alert-danger .m-2 {
                             (error)
                         }
+                    } @else if logged_out {
+                        .alert .alert-success .m-2 {
+                            (t("logged_out"))
+                        }
                     }
 
                     form .my-2 .mx-auto .d-flex .flex-column .gap-2 .col-3 .justify-content-center .align-items-end method="post" action=(authenticate_action) {
-                        input .form-control type="text" name="username" autofocus[username.is_none()] value=[username] placeholder="Username";
-                        input .form-control autofocus[username.is_some()] type="password" name="password" placeholder="Password";
+                        input .form-control type="text" name="username" autofocus[username.is_none()] value=[username] placeholder=(t("username"));
+                        input .form-control autofocus[username.is_some()] type="password" name="password" placeholder=(t("password"));
                         button .min-content .btn .btn-primary type="submit" {
-                            "Login"
+                            (t("login"))
                         }
                     }
                 }
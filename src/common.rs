@@ -1,16 +1,216 @@
+pub mod dto {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{auth::dto::Role, common::service::env};
+
+    #[derive(Serialize)]
+    pub struct HealthStatus {
+        pub app_version: &'static str,
+        pub uptime_secs: u64,
+        pub registry_reachable: bool,
+        /// RFC 3339 date of the registry probe `registry_reachable` comes from
+        pub registry_checked_at: String,
+        /// RFC 3339 date of the last successful catalog fetch
+        pub last_catalog_fetch: Option<String>,
+        pub read_only: bool,
+    }
+
+    /// `serialize_with` helper writing a duration as whole seconds
+    pub fn serialize_duration_secs<S: serde::Serializer>(
+        duration: &Option<chrono::Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&duration.num_seconds()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// How delete actions are rendered for the current request
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum DeleteAction {
+        Allowed,
+        /// Shown but unusable because the registry refuses deletions
+        Disabled,
+        /// Viewers and read-only mode
+        Hidden,
+    }
+
+    impl DeleteAction {
+        /// `deletions_enabled` is `None` when unknown, the registry answers the deletion itself
+        pub fn new(deletions_enabled: Option<bool>, role: Role) -> Self {
+            if env::read_only() || role == Role::Viewer {
+                Self::Hidden
+            } else if deletions_enabled == Some(false) {
+                Self::Disabled
+            } else {
+                Self::Allowed
+            }
+        }
+    }
+
+    /// One-shot message shown on the next rendered page
+    #[derive(Serialize, Deserialize)]
+    pub struct Flash {
+        pub level: FlashLevel,
+        pub message: String,
+    }
+
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum FlashLevel {
+        Success,
+        Warning,
+        Danger,
+    }
+
+    impl Flash {
+        pub fn success(message: impl Into<String>) -> Self {
+            Self {
+                level: FlashLevel::Success,
+                message: message.into(),
+            }
+        }
+
+        pub fn warning(message: impl Into<String>) -> Self {
+            Self {
+                level: FlashLevel::Warning,
+                message: message.into(),
+            }
+        }
+
+        pub fn danger(message: impl Into<String>) -> Self {
+            Self {
+                level: FlashLevel::Danger,
+                message: message.into(),
+            }
+        }
+    }
+
+    impl FlashLevel {
+        /// Bootstrap alert variant
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Success => "success",
+                Self::Warning => "warning",
+                Self::Danger => "danger",
+            }
+        }
+    }
+
+    /// Row height of the home and tag tables, kept in a cookie
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum TableDensity {
+        #[default]
+        Comfortable,
+        Compact,
+    }
+
+    impl TableDensity {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Comfortable => "comfortable",
+                Self::Compact => "compact",
+            }
+        }
+
+        pub fn from_str(value: &str) -> Option<Self> {
+            match value {
+                "comfortable" => Some(Self::Comfortable),
+                "compact" => Some(Self::Compact),
+                _ => None,
+            }
+        }
+
+        pub const fn toggled(self) -> Self {
+            match self {
+                Self::Comfortable => Self::Compact,
+                Self::Compact => Self::Comfortable,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct TableDensityForm {
+        pub density: TableDensity,
+        /// Page to go back to, only local paths are followed
+        pub from: String,
+    }
+
+    /// `?dry_run=1` on a bulk deletion renders what would be deleted instead
+    #[derive(Deserialize)]
+    pub struct DryRunQuery {
+        #[serde(default, deserialize_with = "deserialize_flag")]
+        pub dry_run: bool,
+    }
+
+    /// `deserialize_with` helper accepting `1`, `true` and `yes` like the boolean variables
+    pub fn deserialize_flag<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<bool, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(matches!(
+            value.to_lowercase().as_str(),
+            "1" | "true" | "yes"
+        ))
+    }
+
+    #[derive(Deserialize)]
+    pub struct RetryForm {
+        /// Page to go back to, only local paths are followed
+        pub from: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct HealthQuery {
+        pub format: Option<HealthFormat>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum HealthFormat {
+        Json,
+        Text,
+    }
+}
+
 pub mod handler {
-    use crate::common::service::Paginated;
+    use std::{any::Any, time::Instant};
+
+    use crate::{
+        AppState,
+        api::dto::ErrorResponse,
+        auth::{self, dto::Role, middleware::Authenticated},
+        common::{
+            dto::{HealthFormat, HealthQuery, HealthStatus, RetryForm, TableDensityForm},
+            service::{self, Paginated, RegistryProbe, density::CookieJarDensityExt},
+            view,
+        },
+        error::service::Error,
+    };
     use anyhow::ensure;
-    use axum::response::IntoResponse;
+    use axum::{
+        Form, Json,
+        extract::{FromRequestParts, Query, Request, State},
+        http::{StatusCode, header},
+        middleware::Next,
+        response::{IntoResponse, Redirect, Response},
+    };
+    use axum_extra::extract::CookieJar;
+    use maud::Markup;
     use serde::Deserialize;
 
     #[derive(Clone, Copy, Deserialize)]
     pub struct PaginationQuery {
         pub page: Option<usize>,
         pub size: Option<usize>,
+        /// 1 indexed page typed in the "Go to page" input, takes precedence over `page`
+        pub goto: Option<usize>,
     }
 
     impl PaginationQuery {
+        /// A page past the end is clamped to the last one
         pub fn into_paginated<T: Clone>(
             self,
             default_page_size: usize,
@@ -18,13 +218,17 @@ pub mod handler {
         ) -> anyhow::Result<Paginated<T>> {
             let size = self.size.unwrap_or(default_page_size);
             ensure!(size > 0);
-            let page = self.page.unwrap_or(0);
+            let last_page = data.len().div_ceil(size).saturating_sub(1);
+            let page = self
+                .goto
+                .map_or(self.page.unwrap_or(0), |goto| goto.saturating_sub(1))
+                .min(last_page);
             let total_element_count = data.len();
 
             let start = page * size;
             let end = (start + size).min(data.len());
             ensure!(end <= data.len());
-            ensure!(start < end);
+            ensure!(start < end || (page == 0 && data.is_empty()));
             let data = data[start..end].to_vec();
             Ok(Paginated {
                 page,
@@ -35,13 +239,257 @@ pub mod handler {
         }
     }
 
-    pub async fn health() -> impl IntoResponse {
-        "OK"
+    pub async fn not_found() -> (StatusCode, Markup) {
+        (
+            StatusCode::NOT_FOUND,
+            view::error_page(None, StatusCode::NOT_FOUND, "This page does not exist."),
+        )
+    }
+
+    /// Error page of a failed service call, with the status telling the registry apart from the
+    /// request: 504 on a timeout, 502 when the registry fails, 404 for an unknown image or page
+    pub fn service_error(role: Role, err: &Error, message: &str) -> Response {
+        let (status, message) = match err {
+            Error::Timeout(operation) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                format!("{message} {} timed out.", operation.label()),
+            ),
+            Error::Unreachable(_) | Error::Unauthorized(_) => (
+                StatusCode::BAD_GATEWAY,
+                format!("{message} The registry did not answer."),
+            ),
+            Error::ReadOnly => (
+                StatusCode::FORBIDDEN,
+                format!("{message} The explorer is in read-only mode."),
+            ),
+            Error::Protected => (
+                StatusCode::CONFLICT,
+                format!("{message} The manifest is protected."),
+            ),
+            Error::Unknown => (StatusCode::NOT_FOUND, message.to_owned()),
+        };
+        (status, view::error_page(Some(role), status, &message)).into_response()
+    }
+
+    /// The configured favicon, or the embedded default when the file is missing
+    pub async fn favicon() -> Response {
+        let path = service::env::favicon_path()
+            .map(ToOwned::to_owned)
+            .or_else(|| service::env::static_dir().map(|dir| format!("{dir}/favicon.svg")));
+        let cache_control = (header::CACHE_CONTROL, "public, max-age=86400");
+        if let Some(path) = path {
+            match tokio::fs::read(&path).await {
+                Ok(favicon) => {
+                    return (
+                        [
+                            (header::CONTENT_TYPE, service::asset_content_type(&path)),
+                            cache_control,
+                        ],
+                        favicon,
+                    )
+                        .into_response();
+                }
+                Err(err) => {
+                    tracing::debug!("Favicon {path} unavailable, serving the default one: {err}");
+                }
+            }
+        }
+        (
+            [(header::CONTENT_TYPE, "image/svg+xml"), cache_control],
+            service::DEFAULT_FAVICON,
+        )
+            .into_response()
+    }
+
+    /// `/static` without `STATIC_DIR`, from the assets compiled into the binary
+    #[cfg(feature = "embed-static")]
+    pub async fn embedded_static(
+        axum::extract::Path(path): axum::extract::Path<String>,
+    ) -> Response {
+        match service::embedded_static::get(&path) {
+            Some(asset) => (
+                [(header::CONTENT_TYPE, service::asset_content_type(&path))],
+                asset,
+            )
+                .into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    /// Disallows every crawler, routed unless `ALLOW_INDEXING` is set
+    pub async fn robots_txt() -> &'static str {
+        "User-agent: *\nDisallow: /\n"
+    }
+
+    pub async fn set_table_density(
+        _: Authenticated,
+        cookie_jar: CookieJar,
+        Form(TableDensityForm { density, from }): Form<TableDensityForm>,
+    ) -> (CookieJar, Redirect) {
+        let from = service::local_redirect_target(&from).unwrap_or("/");
+        (cookie_jar.set_table_density(density), Redirect::to(from))
+    }
+
+    /// Access log line once the request is answered, see `ACCESS_LOG`
+    pub async fn access_log(request: Request, next: Next) -> Response {
+        let started_at = Instant::now();
+        let method = request.method().clone();
+        let path = match request.uri().query() {
+            Some(query) => format!(
+                "{}?{}",
+                request.uri().path(),
+                service::redacted_query(query)
+            ),
+            None => request.uri().path().to_owned(),
+        };
+        let user = auth::service::request_username(request.headers());
+        let response = next.run(request).await;
+        tracing::info!(
+            target: "access",
+            %method,
+            path,
+            status = response.status().as_u16(),
+            duration_ms = started_at.elapsed().as_millis(),
+            user = user.unwrap_or("-"),
+        );
+        response
+    }
+
+    /// Renders the maintenance page in place of the requested one while the registry probe
+    /// fails, anonymous users go on to the login redirect
+    pub async fn maintenance(
+        State(AppState { registry_probe, .. }): State<AppState>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let probe = registry_probe
+            .read()
+            .ok()
+            .map(|registry_probe| *registry_probe);
+        let Some(RegistryProbe {
+            reachable: false,
+            checked_at,
+        }) = probe
+        else {
+            return next.run(request).await;
+        };
+        let (mut parts, body) = request.into_parts();
+        let Ok(Authenticated(role)) = Authenticated::from_request_parts(&mut parts, &()).await
+        else {
+            return next.run(Request::from_parts(parts, body)).await;
+        };
+        let retry_after = [(
+            header::RETRY_AFTER,
+            service::env::registry_probe_secs().to_string(),
+        )];
+        // API clients expect JSON, not a page
+        if parts.uri.path().starts_with("/api/") {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                retry_after,
+                Json(ErrorResponse {
+                    error: "The registry is unreachable".to_owned(),
+                }),
+            )
+                .into_response();
+        }
+        let from = parts
+            .uri
+            .path_and_query()
+            .map_or("/", |path_and_query| path_and_query.as_str());
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            retry_after,
+            view::maintenance_page(role, checked_at, from),
+        )
+            .into_response()
+    }
+
+    /// Pings the registry right away instead of waiting for the next probe
+    pub async fn retry_registry_probe(
+        State(AppState {
+            registry_api_client,
+            registry_probe,
+            ..
+        }): State<AppState>,
+        _: Authenticated,
+        Form(RetryForm { from }): Form<RetryForm>,
+    ) -> Redirect {
+        service::probe_registry(&registry_api_client, &registry_probe).await;
+        Redirect::to(service::local_redirect_target(&from).unwrap_or("/"))
+    }
+
+    /// Renders the 500 page in place of the dropped connection a panicking handler leaves
+    pub fn panic_page(panic: Box<dyn Any + Send + 'static>) -> Response {
+        let message = panic
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| panic.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown panic");
+        tracing::error!("Handler panicked: {message}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            view::error_page(
+                None,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Something went wrong, the error was logged.",
+            ),
+        )
+            .into_response()
+    }
+
+    /// Reports the last registry probe instead of pinging the registry, so orchestrator probes do
+    /// not reach it
+    pub async fn health(
+        Query(HealthQuery { format }): Query<HealthQuery>,
+        State(AppState {
+            started_at,
+            last_catalog_fetch,
+            registry_probe,
+            ..
+        }): State<AppState>,
+    ) -> Response {
+        if matches!(format, Some(HealthFormat::Text)) {
+            return "OK".into_response();
+        }
+
+        let Ok(registry_probe) = registry_probe.read().map(|registry_probe| *registry_probe) else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let last_catalog_fetch = last_catalog_fetch
+            .read()
+            .ok()
+            .and_then(|last_catalog_fetch| *last_catalog_fetch)
+            .map(|date| date.to_rfc3339());
+
+        Json(HealthStatus {
+            app_version: service::APP_VERSION,
+            uptime_secs: started_at.elapsed().as_secs(),
+            registry_reachable: registry_probe.reachable,
+            registry_checked_at: registry_probe.checked_at.to_rfc3339(),
+            last_catalog_fetch,
+            read_only: service::env::read_only(),
+        })
+        .into_response()
     }
 }
 
 pub mod service {
-    use std::sync::LazyLock;
+    use std::{
+        sync::{Arc, LazyLock, RwLock},
+        time::Duration,
+    };
+
+    use axum_extra::extract::cookie::SameSite;
+
+    use itertools::Itertools;
+
+    use crate::{
+        auth::dto::AuthMode,
+        i18n::dto::Locale,
+        registries::dto::RegistryConfig,
+        registry::{self, dto::RegistryFlavor},
+    };
 
     pub const APP_VERSION: &str = const {
         if cfg!(debug_assertions) {
@@ -51,14 +499,100 @@ pub mod service {
         }
     };
 
+    /// Served when the configured favicon is missing from the mounted static dir
+    pub const DEFAULT_FAVICON: &[u8] = include_bytes!("../static/favicon.svg");
+
+    /// Guessed from the extension of the favicon or of a shipped asset, SVG otherwise
+    pub fn asset_content_type(path: &str) -> &'static str {
+        match path
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("ico") => "image/x-icon",
+            Some("png") => "image/png",
+            Some("css") => "text/css; charset=utf-8",
+            Some("js") => "text/javascript; charset=utf-8",
+            _ => "image/svg+xml",
+        }
+    }
+
     static REGISTRY_HOST: LazyLock<String> =
         LazyLock::new(|| std::env::var("REGISTRY_HOST").expect("REGISTRY_HOST"));
 
-    static REGISTRY_USERNAME: LazyLock<String> =
-        LazyLock::new(|| std::env::var("REGISTRY_USERNAME").expect("REGISTRY_USERNAME"));
+    static DOCKER_CONFIG: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("DOCKER_CONFIG").ok());
+
+    /// Credentials from the Docker config when it has an entry for the registry, otherwise from
+    /// `REGISTRY_USERNAME` and `REGISTRY_PASSWORD`
+    static REGISTRY_CREDENTIALS: LazyLock<(String, String)> = LazyLock::new(|| {
+        env::docker_config()
+            .and_then(|path| {
+                docker_config::credentials(path, &image_host(&host_url(env::registry_host())))
+                    .inspect_err(|err| {
+                        tracing::warn!("Could not read credentials from {path}: {err:#}");
+                    })
+                    .ok()
+                    .flatten()
+            })
+            .unwrap_or_else(|| {
+                (
+                    std::env::var("REGISTRY_USERNAME").expect("REGISTRY_USERNAME"),
+                    std::env::var("REGISTRY_PASSWORD").expect("REGISTRY_PASSWORD"),
+                )
+            })
+    });
+
+    static REGISTRY_NAME: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("REGISTRY_NAME").ok());
+
+    /// `REGISTRY_HOST` first, then `REGISTRY_0_HOST`, `REGISTRY_1_HOST`... up to the first
+    /// missing index
+    static REGISTRIES: LazyLock<Vec<RegistryConfig>> = LazyLock::new(|| {
+        let main = RegistryConfig {
+            name: REGISTRY_NAME
+                .clone()
+                .unwrap_or_else(|| env::registry_host().to_owned()),
+            url: host_url(env::registry_host()),
+            username: env::registry_username().to_owned(),
+            password: env::registry_password().to_owned(),
+        };
+        let numbered = (0..).map_while(|index| {
+            let var = |field: &str| std::env::var(format!("REGISTRY_{index}_{field}")).ok();
+            let host = var("HOST")?;
+            Some(RegistryConfig {
+                name: var("NAME").unwrap_or_else(|| host.clone()),
+                url: host_url(&host),
+                username: var("USERNAME").unwrap_or_default(),
+                password: var("PASSWORD").unwrap_or_default(),
+            })
+        });
+        let registries = std::iter::once(main).chain(numbered).collect::<Vec<_>>();
+        assert!(
+            registries
+                .iter()
+                .map(|registry| &registry.name)
+                .all_unique(),
+            "Registry names must be unique, set REGISTRY_NAME and REGISTRY_<n>_NAME"
+        );
+        registries
+    });
+
+    /// `host` with an explicit scheme (https by default) and without trailing slash
+    fn host_url(host: &str) -> String {
+        let host = host.trim_end_matches('/');
+        if host.starts_with("http://") || host.starts_with("https://") {
+            host.to_owned()
+        } else {
+            format!("https://{host}")
+        }
+    }
 
-    static REGISTRY_PASSWORD: LazyLock<String> =
-        LazyLock::new(|| std::env::var("REGISTRY_PASSWORD").expect("REGISTRY_PASSWORD"));
+    /// `url` without its scheme, as it appears in image references
+    fn image_host(url: &str) -> String {
+        url.split_once("://")
+            .map_or_else(|| url.to_owned(), |(_, host)| host.to_owned())
+    }
 
     static LISTEN_ADDR: LazyLock<String> =
         LazyLock::new(|| std::env::var("LISTEN_ADDR").expect("LISTEN_ADDR"));
@@ -66,8 +600,15 @@ pub mod service {
     static LISTEN_PORT: LazyLock<String> =
         LazyLock::new(|| std::env::var("LISTEN_PORT").expect("LISTEN_PORT"));
 
-    static STATIC_DIR: LazyLock<String> =
-        LazyLock::new(|| std::env::var("STATIC_DIR").expect("STATIC_DIR"));
+    /// Optional when the assets are compiled in with the `embed-static` feature
+    static STATIC_DIR: LazyLock<Option<String>> = LazyLock::new(|| {
+        let static_dir = std::env::var("STATIC_DIR");
+        if cfg!(feature = "embed-static") {
+            static_dir.ok()
+        } else {
+            Some(static_dir.expect("STATIC_DIR"))
+        }
+    });
 
     static EXPLORER_USERNAME: LazyLock<String> =
         LazyLock::new(|| std::env::var("EXPLORER_USERNAME").expect("EXPLORER_USERNAME"));
@@ -75,22 +616,428 @@ pub mod service {
     static EXPLORER_PASSWORD: LazyLock<String> =
         LazyLock::new(|| std::env::var("EXPLORER_PASSWORD").expect("EXPLORER_PASSWORD"));
 
+    /// Optional read-only account, both variables must be set
+    static EXPLORER_VIEWER_CREDENTIALS: LazyLock<Option<(String, String)>> =
+        LazyLock::new(|| {
+            match (
+                std::env::var("EXPLORER_VIEWER_USERNAME").ok(),
+                std::env::var("EXPLORER_VIEWER_PASSWORD").ok(),
+            ) {
+                (Some(username), Some(password)) => Some((username, password)),
+                (None, None) => None,
+                _ => panic!(
+                    "EXPLORER_VIEWER_USERNAME and EXPLORER_VIEWER_PASSWORD must be set together"
+                ),
+            }
+        });
+
+    static EXPLORER_IDLE_TIMEOUT_MINUTES: LazyLock<Option<u64>> = LazyLock::new(|| {
+        parsed_var("EXPLORER_IDLE_TIMEOUT_MINUTES").filter(|idle_timeout| *idle_timeout > 0)
+    });
+
+    static REGISTRY_POOL_MAX_IDLE_PER_HOST: LazyLock<Option<usize>> =
+        LazyLock::new(|| parsed_var("REGISTRY_POOL_MAX_IDLE_PER_HOST"));
+
+    static REGISTRY_POOL_IDLE_TIMEOUT_SECS: LazyLock<Option<u64>> =
+        LazyLock::new(|| parsed_var("REGISTRY_POOL_IDLE_TIMEOUT_SECS"));
+
+    static REGISTRY_CATALOG_TIMEOUT_SECS: LazyLock<Option<u64>> =
+        LazyLock::new(|| parsed_var("REGISTRY_CATALOG_TIMEOUT_SECS"));
+
+    static REGISTRY_MANIFEST_TIMEOUT_SECS: LazyLock<Option<u64>> =
+        LazyLock::new(|| parsed_var("REGISTRY_MANIFEST_TIMEOUT_SECS"));
+
+    static REGISTRY_HTTP1_ONLY: LazyLock<bool> =
+        LazyLock::new(|| flag_var("REGISTRY_HTTP1_ONLY", false));
+
+    static REGISTRY_HTTP_PROXY: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("REGISTRY_HTTP_PROXY").ok());
+
+    /// Optional proxy authentication, both variables must be set
+    static REGISTRY_HTTP_PROXY_CREDENTIALS: LazyLock<Option<(String, String)>> = LazyLock::new(
+        || match (
+            std::env::var("REGISTRY_HTTP_PROXY_USERNAME").ok(),
+            std::env::var("REGISTRY_HTTP_PROXY_PASSWORD").ok(),
+        ) {
+            (Some(username), Some(password)) => Some((username, password)),
+            (None, None) => None,
+            _ => panic!(
+                "REGISTRY_HTTP_PROXY_USERNAME and REGISTRY_HTTP_PROXY_PASSWORD must be set together"
+            ),
+        },
+    );
+
+    static REGISTRY_NO_PROXY: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("REGISTRY_NO_PROXY").ok());
+
+    static REGISTRY_USER_AGENT_SUFFIX: LazyLock<Option<String>> = LazyLock::new(|| {
+        std::env::var("REGISTRY_USER_AGENT_SUFFIX")
+            .ok()
+            .map(|suffix| suffix.trim().to_owned())
+            .filter(|suffix| !suffix.is_empty())
+    });
+
+    /// Comma-separated `name=value` pairs
+    static REGISTRY_EXTRA_HEADERS: LazyLock<Vec<(String, String)>> = LazyLock::new(|| {
+        std::env::var("REGISTRY_EXTRA_HEADERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or_else(|| {
+                    panic!("REGISTRY_EXTRA_HEADERS entries must be name=value pairs: {pair}")
+                });
+                (name.trim().to_owned(), value.trim().to_owned())
+            })
+            .collect()
+    });
+
+    static REGISTRY_FLAVOR: LazyLock<RegistryFlavor> =
+        LazyLock::new(|| parsed_var("REGISTRY_FLAVOR").unwrap_or_default());
+
+    static REGISTRY_PROXY_DISABLED: LazyLock<bool> =
+        LazyLock::new(|| flag_var("REGISTRY_PROXY_DISABLED", false));
+
+    static REGISTRY_REVALIDATE_BEFORE_DELETE: LazyLock<bool> =
+        LazyLock::new(|| flag_var("REGISTRY_REVALIDATE_BEFORE_DELETE", true));
+
+    static REGISTRY_MAX_BODY_BYTES: LazyLock<usize> =
+        LazyLock::new(|| parsed_var("REGISTRY_MAX_BODY_BYTES").unwrap_or(16 * 1024 * 1024));
+
+    static REGISTRY_MAX_CONCURRENT_REQUESTS: LazyLock<usize> =
+        LazyLock::new(|| parsed_var("REGISTRY_MAX_CONCURRENT_REQUESTS").unwrap_or(16));
+
+    static DELETE_BATCH_SIZE: LazyLock<usize> = LazyLock::new(|| {
+        let size = parsed_var("DELETE_BATCH_SIZE").unwrap_or(8);
+        assert!(size > 0, "DELETE_BATCH_SIZE must be greater than 0");
+        size
+    });
+
+    static READ_ONLY: LazyLock<bool> = LazyLock::new(|| flag_var("READ_ONLY", false));
+
+    static PROBE_DELETIONS: LazyLock<bool> = LazyLock::new(|| flag_var("PROBE_DELETIONS", false));
+
+    /// Comma-separated glob patterns
+    static PROTECTED_TAGS: LazyLock<Vec<String>> = LazyLock::new(|| {
+        std::env::var("PROTECTED_TAGS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    });
+
+    static REGISTRY_NAMESPACE: LazyLock<Option<String>> = LazyLock::new(|| {
+        std::env::var("REGISTRY_NAMESPACE")
+            .ok()
+            .map(|namespace| namespace.trim().trim_matches('/').to_owned())
+            .filter(|namespace| !namespace.is_empty())
+    });
+
+    /// Comma-separated repository names, sorted like a catalog so its `last` cursor applies
+    static KNOWN_REPOSITORIES: LazyLock<Vec<String>> = LazyLock::new(|| {
+        let mut repositories = std::env::var("KNOWN_REPOSITORIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|repository| !repository.is_empty())
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        repositories.sort_unstable();
+        repositories.dedup();
+        repositories
+    });
+
+    /// Comma-separated glob patterns
+    static HIDDEN_REPOSITORIES: LazyLock<Vec<String>> = LazyLock::new(|| {
+        std::env::var("HIDDEN_REPOSITORIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    });
+
+    static FAVICON_PATH: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("FAVICON_PATH").ok());
+
+    static AUDIT_LOG_PATH: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("AUDIT_LOG_PATH").ok());
+
+    /// Path of the distribution API under the registry URL, empty when mounted at the root
+    static REGISTRY_API_PREFIX: LazyLock<String> = LazyLock::new(|| {
+        let prefix = std::env::var("REGISTRY_API_PREFIX").unwrap_or_else(|_| "/v2".to_owned());
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("/{prefix}")
+        }
+    });
+
+    static INSTANCE_TITLE: LazyLock<String> = LazyLock::new(|| {
+        std::env::var("INSTANCE_TITLE")
+            .ok()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| "Docker Registry Explorer".to_owned())
+    });
+
+    static INSTANCE_LOGO_URL: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("INSTANCE_LOGO_URL").ok());
+
+    static INSTANCE_BANNER: LazyLock<Option<String>> = LazyLock::new(|| {
+        std::env::var("INSTANCE_BANNER")
+            .ok()
+            .filter(|banner| !banner.trim().is_empty())
+    });
+
+    static VULN_SCAN_URL_TEMPLATE: LazyLock<Option<String>> =
+        LazyLock::new(|| std::env::var("VULN_SCAN_URL_TEMPLATE").ok());
+
+    static PREFER_PLATFORM: LazyLock<String> = LazyLock::new(|| {
+        std::env::var("PREFER_PLATFORM").unwrap_or_else(|_| "linux/amd64".to_owned())
+    });
+
+    static VERIFY_DIGESTS: LazyLock<bool> = LazyLock::new(|| flag_var("VERIFY_DIGESTS", false));
+
+    static MAX_CATALOG_SIZE: LazyLock<Option<usize>> = LazyLock::new(|| {
+        parsed_var("MAX_CATALOG_SIZE").filter(|max_catalog_size| *max_catalog_size > 0)
+    });
+
+    static MAX_ENRICHED_TAGS: LazyLock<Option<usize>> = LazyLock::new(|| {
+        parsed_var("MAX_ENRICHED_TAGS").filter(|max_enriched_tags| *max_enriched_tags > 0)
+    });
+
+    static HOME_REFRESH_SECS: LazyLock<Option<u64>> = LazyLock::new(|| {
+        parsed_var("HOME_REFRESH_SECS").filter(|home_refresh_secs| *home_refresh_secs > 0)
+    });
+
+    static AUTH_CLEAR_STALE_COOKIE: LazyLock<bool> =
+        LazyLock::new(|| flag_var("AUTH_CLEAR_STALE_COOKIE", true));
+
+    static TRUSTED_PROXY: LazyLock<bool> = LazyLock::new(|| flag_var("TRUSTED_PROXY", false));
+
+    static COOKIE_SECURE: LazyLock<bool> = LazyLock::new(|| flag_var("COOKIE_SECURE", true));
+
+    static AUTH_MODE: LazyLock<AuthMode> =
+        LazyLock::new(|| parsed_var("AUTH_MODE").unwrap_or_default());
+
+    static LOGOUT_REDIRECT_URL: LazyLock<String> = LazyLock::new(|| {
+        std::env::var("LOGOUT_REDIRECT_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| "/auth/login?logged_out=1".to_owned())
+    });
+
+    static REGISTRY_PROBE_SECS: LazyLock<u64> = LazyLock::new(|| {
+        parsed_var("REGISTRY_PROBE_SECS")
+            .filter(|registry_probe_secs| *registry_probe_secs > 0)
+            .unwrap_or(30)
+    });
+
+    static ALLOW_INDEXING: LazyLock<bool> = LazyLock::new(|| flag_var("ALLOW_INDEXING", false));
+
+    static ACCESS_LOG: LazyLock<bool> = LazyLock::new(|| flag_var("ACCESS_LOG", false));
+
+    static WARM_CACHE_ON_START: LazyLock<bool> =
+        LazyLock::new(|| flag_var("WARM_CACHE_ON_START", false));
+
+    static COOKIE_SAME_SITE: LazyLock<SameSite> = LazyLock::new(|| {
+        let same_site = match std::env::var("COOKIE_SAME_SITE") {
+            Err(_) => SameSite::Strict,
+            Ok(value) => match value.to_lowercase().as_str() {
+                "strict" => SameSite::Strict,
+                "lax" => SameSite::Lax,
+                "none" => SameSite::None,
+                _ => panic!("COOKIE_SAME_SITE has an invalid value: {value}"),
+            },
+        };
+        // Browsers reject `SameSite=None` cookies that are not `Secure`
+        assert!(
+            !same_site.is_none() || *COOKIE_SECURE,
+            "COOKIE_SAME_SITE=None requires COOKIE_SECURE"
+        );
+        same_site
+    });
+
+    static COOKIE_DOMAIN: LazyLock<Option<String>> = LazyLock::new(|| {
+        std::env::var("COOKIE_DOMAIN")
+            .ok()
+            .map(|domain| domain.trim().to_owned())
+            .filter(|domain| !domain.is_empty())
+    });
+
+    static DEFAULT_LOCALE: LazyLock<Locale> =
+        LazyLock::new(|| parsed_var("DEFAULT_LOCALE").unwrap_or_default());
+
+    static DEFAULT_PAGE_SIZE: LazyLock<usize> = LazyLock::new(|| {
+        let size = parsed_var("DEFAULT_PAGE_SIZE").unwrap_or(10);
+        assert!(size > 0, "DEFAULT_PAGE_SIZE must be greater than 0");
+        size
+    });
+
+    /// Optional variable parsed as `T`, panics when set to an invalid value
+    fn parsed_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+        std::env::var(name).ok().map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{name} has an invalid value: {value}"))
+        })
+    }
+
+    /// Boolean variable, `1`, `true` and `yes` (case insensitive) are truthy
+    fn flag_var(name: &str, default: bool) -> bool {
+        std::env::var(name).map_or(default, |value| {
+            matches!(value.to_lowercase().as_str(), "1" | "true" | "yes")
+        })
+    }
+
     pub mod env {
+        use axum_extra::extract::cookie::SameSite;
+
+        use crate::{
+            auth::dto::AuthMode, i18n::dto::Locale, registries, registries::dto::RegistryConfig,
+            registry::dto::RegistryFlavor,
+        };
+
         use super::{
-            EXPLORER_PASSWORD, EXPLORER_USERNAME, LISTEN_ADDR, LISTEN_PORT, REGISTRY_HOST,
-            REGISTRY_PASSWORD, REGISTRY_USERNAME, STATIC_DIR,
+            ACCESS_LOG, ALLOW_INDEXING, AUDIT_LOG_PATH, AUTH_CLEAR_STALE_COOKIE, AUTH_MODE,
+            COOKIE_DOMAIN, COOKIE_SAME_SITE, COOKIE_SECURE, DEFAULT_LOCALE, DEFAULT_PAGE_SIZE,
+            DELETE_BATCH_SIZE, DOCKER_CONFIG, EXPLORER_IDLE_TIMEOUT_MINUTES, EXPLORER_PASSWORD,
+            EXPLORER_USERNAME, EXPLORER_VIEWER_CREDENTIALS, FAVICON_PATH, HIDDEN_REPOSITORIES,
+            HOME_REFRESH_SECS, INSTANCE_BANNER, INSTANCE_LOGO_URL, INSTANCE_TITLE,
+            KNOWN_REPOSITORIES, LISTEN_ADDR, LISTEN_PORT, LOGOUT_REDIRECT_URL, MAX_CATALOG_SIZE,
+            MAX_ENRICHED_TAGS, PREFER_PLATFORM, PROBE_DELETIONS, PROTECTED_TAGS, READ_ONLY,
+            REGISTRIES, REGISTRY_API_PREFIX, REGISTRY_CATALOG_TIMEOUT_SECS, REGISTRY_CREDENTIALS,
+            REGISTRY_EXTRA_HEADERS, REGISTRY_FLAVOR, REGISTRY_HOST, REGISTRY_HTTP_PROXY,
+            REGISTRY_HTTP_PROXY_CREDENTIALS, REGISTRY_HTTP1_ONLY, REGISTRY_MANIFEST_TIMEOUT_SECS,
+            REGISTRY_MAX_BODY_BYTES, REGISTRY_MAX_CONCURRENT_REQUESTS, REGISTRY_NAMESPACE,
+            REGISTRY_NO_PROXY, REGISTRY_POOL_IDLE_TIMEOUT_SECS, REGISTRY_POOL_MAX_IDLE_PER_HOST,
+            REGISTRY_PROBE_SECS, REGISTRY_PROXY_DISABLED, REGISTRY_REVALIDATE_BEFORE_DELETE,
+            REGISTRY_USER_AGENT_SUFFIX, STATIC_DIR, TRUSTED_PROXY, VERIFY_DIGESTS,
+            VULN_SCAN_URL_TEMPLATE, WARM_CACHE_ON_START, host_url, image_host,
         };
 
         pub fn registry_host() -> &'static str {
             &REGISTRY_HOST
         }
 
+        /// URL of the registry the request browses, of `REGISTRY_HOST` outside of a request
+        pub fn registry_url() -> String {
+            registries::service::selected().map_or_else(
+                || host_url(registry_host()),
+                |registry| registry.url.clone(),
+            )
+        }
+
+        /// Name of the registry the request browses, of `REGISTRY_HOST` outside of a request
+        pub fn registry_name() -> &'static str {
+            registries::service::selected().map_or(&registries()[0].name, |registry| &registry.name)
+        }
+
+        /// Registry host as it appears in image references, e.g. `docker pull <host>/<image>`
+        pub fn registry_image_host() -> String {
+            image_host(&registry_url())
+        }
+
+        /// The browsed registries, `REGISTRY_HOST` first and named after `REGISTRY_NAME`. The
+        /// numbered ones take `REGISTRY_<n>_NAME`, `_USERNAME` and `_PASSWORD`, every other
+        /// setting applies to all of them
+        pub fn registries() -> &'static [RegistryConfig] {
+            &REGISTRIES
+        }
+
         pub fn registry_username() -> &'static str {
-            &REGISTRY_USERNAME
+            &REGISTRY_CREDENTIALS.0
         }
 
         pub fn registry_password() -> &'static str {
-            &REGISTRY_PASSWORD
+            &REGISTRY_CREDENTIALS.1
+        }
+
+        pub fn docker_config() -> Option<&'static str> {
+            DOCKER_CONFIG.as_deref()
+        }
+
+        pub fn registry_pool_max_idle_per_host() -> Option<usize> {
+            *REGISTRY_POOL_MAX_IDLE_PER_HOST
+        }
+
+        pub fn registry_pool_idle_timeout_secs() -> Option<u64> {
+            *REGISTRY_POOL_IDLE_TIMEOUT_SECS
+        }
+
+        /// Whole catalog listing, a large registry can legitimately take much longer than a
+        /// single manifest. No timeout when unset
+        pub fn registry_catalog_timeout_secs() -> Option<u64> {
+            *REGISTRY_CATALOG_TIMEOUT_SECS
+        }
+
+        /// Each manifest and config fetch behind the tag details. No timeout when unset
+        pub fn registry_manifest_timeout_secs() -> Option<u64> {
+            *REGISTRY_MANIFEST_TIMEOUT_SECS
+        }
+
+        pub fn registry_http1_only() -> bool {
+            *REGISTRY_HTTP1_ONLY
+        }
+
+        /// Proxy for every registry request, replaces the `HTTP(S)_PROXY` variables
+        pub fn registry_http_proxy() -> Option<&'static str> {
+            REGISTRY_HTTP_PROXY.as_deref()
+        }
+
+        pub fn registry_http_proxy_credentials() -> Option<(&'static str, &'static str)> {
+            REGISTRY_HTTP_PROXY_CREDENTIALS
+                .as_ref()
+                .map(|(username, password)| (username.as_str(), password.as_str()))
+        }
+
+        /// Comma-separated hosts, domains and CIDR ranges reached without `REGISTRY_HTTP_PROXY`, or
+        /// without the `HTTP(S)_PROXY` ones when it is not set, in place of `NO_PROXY`
+        pub fn registry_no_proxy() -> Option<&'static str> {
+            REGISTRY_NO_PROXY.as_deref()
+        }
+
+        /// `harbor` adds the pull counts of its API to the home page
+        pub fn registry_flavor() -> RegistryFlavor {
+            *REGISTRY_FLAVOR
+        }
+
+        /// Appended to the `User-Agent` of the registry requests
+        pub fn registry_user_agent_suffix() -> Option<&'static str> {
+            REGISTRY_USER_AGENT_SUFFIX.as_deref()
+        }
+
+        /// Headers sent with every registry request, e.g. an API key required by a gateway
+        pub fn registry_extra_headers() -> &'static [(String, String)] {
+            &REGISTRY_EXTRA_HEADERS
+        }
+
+        /// Ignores every proxy setting, including the `HTTP(S)_PROXY` variables
+        pub fn registry_proxy_disabled() -> bool {
+            *REGISTRY_PROXY_DISABLED
+        }
+
+        pub fn registry_revalidate_before_delete() -> bool {
+            *REGISTRY_REVALIDATE_BEFORE_DELETE
+        }
+
+        pub fn registry_max_body_bytes() -> usize {
+            *REGISTRY_MAX_BODY_BYTES
+        }
+
+        pub fn registry_max_concurrent_requests() -> usize {
+            *REGISTRY_MAX_CONCURRENT_REQUESTS
+        }
+
+        /// Manifests deleted at once when deleting every tag of an image, each batch is over
+        /// before the next one starts
+        pub fn delete_batch_size() -> usize {
+            *DELETE_BATCH_SIZE
         }
 
         pub fn listen_addr() -> &'static str {
@@ -101,8 +1048,14 @@ pub mod service {
             &LISTEN_PORT
         }
 
-        pub fn static_dir() -> &'static str {
-            &STATIC_DIR
+        /// Served at `/static` when set, even over the embedded assets
+        pub fn static_dir() -> Option<&'static str> {
+            STATIC_DIR.as_deref()
+        }
+
+        /// File served at `/favicon.ico`, `favicon.svg` of `STATIC_DIR` when unset
+        pub fn favicon_path() -> Option<&'static str> {
+            FAVICON_PATH.as_deref()
         }
 
         pub fn explorer_username() -> &'static str {
@@ -113,18 +1066,564 @@ pub mod service {
             &EXPLORER_PASSWORD
         }
 
+        /// Sessions without a request for this long go back to the login page, every
+        /// authenticated request pushes the deadline back
+        pub fn explorer_idle_timeout_minutes() -> Option<u64> {
+            *EXPLORER_IDLE_TIMEOUT_MINUTES
+        }
+
+        pub fn explorer_viewer_credentials() -> Option<(&'static str, &'static str)> {
+            EXPLORER_VIEWER_CREDENTIALS
+                .as_ref()
+                .map(|(username, password)| (username.as_str(), password.as_str()))
+        }
+
+        /// Removes every deletion route and action
+        pub fn read_only() -> bool {
+            *READ_ONLY
+        }
+
+        /// Sends a `DELETE` of a manifest that cannot exist at startup to find out whether the
+        /// registry allows deletions, off by default as it is a write request
+        pub fn probe_deletions() -> bool {
+            *PROBE_DELETIONS
+        }
+
+        /// Tags matching one of these globs, and the manifests they reference, cannot be deleted
+        pub fn protected_tags() -> &'static [String] {
+            &PROTECTED_TAGS
+        }
+
+        /// Repositories matching one of these globs are left out of the home list, admins can
+        /// reveal them
+        pub fn hidden_repositories() -> &'static [String] {
+            &HIDDEN_REPOSITORIES
+        }
+
+        /// Organization or project every repository lives under (GHCR, GCR, ...), the explorer
+        /// shows the names relative to it
+        pub fn registry_namespace() -> Option<&'static str> {
+            REGISTRY_NAMESPACE.as_deref()
+        }
+
+        /// Listed on the home page when the registry refuses `_catalog`, as most public
+        /// registries do. Without them only the repository search reaches an image
+        pub fn known_repositories() -> &'static [String] {
+            &KNOWN_REPOSITORIES
+        }
+
+        /// `/v2` unless a gateway mounts the API elsewhere, never ends with a slash
+        pub fn registry_api_prefix() -> &'static str {
+            &REGISTRY_API_PREFIX
+        }
+
+        /// Platform (`os/architecture[/variant]`) whose config describes a multi-platform tag
+        pub fn prefer_platform() -> &'static str {
+            &PREFER_PLATFORM
+        }
+
+        /// Page title and header text, "Docker Registry Explorer" when unset
+        pub fn instance_title() -> &'static str {
+            &INSTANCE_TITLE
+        }
+
+        /// Image shown before the title in the header
+        pub fn instance_logo_url() -> Option<&'static str> {
+            INSTANCE_LOGO_URL.as_deref()
+        }
+
+        /// Message on top of the home page, HTML is escaped and `[text](https://...)` become
+        /// links
+        pub fn instance_banner() -> Option<&'static str> {
+            INSTANCE_BANNER.as_deref()
+        }
+
+        /// Link to a scanner report for a tag, e.g.
+        /// `https://harbor.example.com/scan?image={image}&digest={digest}`
+        pub fn vuln_scan_url_template() -> Option<&'static str> {
+            VULN_SCAN_URL_TEMPLATE.as_deref()
+        }
+
+        /// File receiving one JSON line per deletion
+        pub fn audit_log_path() -> Option<&'static str> {
+            AUDIT_LOG_PATH.as_deref()
+        }
+
+        /// Browsers drop `Secure` cookies received over plain HTTP, disable this when the
+        /// explorer is not served behind HTTPS
+        pub fn cookie_secure() -> bool {
+            *COOKIE_SECURE
+        }
+
+        /// `basic` lets the browser prompt for the explorer credentials on every request instead
+        /// of the login page and its cookie
+        pub fn auth_mode() -> AuthMode {
+            *AUTH_MODE
+        }
+
+        /// Where logging out leads, e.g. the logout endpoint of an SSO provider, the login page
+        /// with a confirmation by default
+        pub fn logout_redirect_url() -> &'static str {
+            &LOGOUT_REDIRECT_URL
+        }
+
+        /// Period of the registry reachability probe, pages are replaced by the maintenance page
+        /// while it fails
+        pub fn registry_probe_secs() -> u64 {
+            *REGISTRY_PROBE_SECS
+        }
+
+        /// Lets search engines index the explorer, otherwise `/robots.txt` disallows everything
+        /// and every response carries `X-Robots-Tag: noindex, nofollow`
+        pub fn allow_indexing() -> bool {
+            *ALLOW_INDEXING
+        }
+
+        /// `SameSite` attribute of the auth cookie, `Lax` lets links from other sites open the
+        /// explorer logged in
+        pub fn cookie_same_site() -> SameSite {
+            *COOKIE_SAME_SITE
+        }
+
+        /// Domain the auth cookie is shared with, e.g. `example.com` for every subdomain, host
+        /// only when unset
+        pub fn cookie_domain() -> Option<&'static str> {
+            COOKIE_DOMAIN.as_deref()
+        }
+
+        /// Period of the background catalog refresh, the home page counts the tags on every
+        /// load when unset
+        pub fn home_refresh_secs() -> Option<u64> {
+            *HOME_REFRESH_SECS
+        }
+
+        /// Logs every request with the `access` target: method, path, status, duration and user.
+        /// Headers are left out and credential query parameters are redacted
+        pub fn access_log() -> bool {
+            *ACCESS_LOG
+        }
+
+        /// Fetches the catalog and its tag counts in the background once listening, without
+        /// `HOME_REFRESH_SECS` only the first home page load is served from it
+        pub fn warm_cache_on_start() -> bool {
+            *WARM_CACHE_ON_START
+        }
+
+        /// Removes the auth cookie of a request bounced to the login page
+        pub fn auth_clear_stale_cookie() -> bool {
+            *AUTH_CLEAR_STALE_COOKIE
+        }
+
+        /// The explorer sits behind a proxy whose `X-Forwarded-Proto` and `X-Forwarded-Host`
+        /// headers can be trusted
+        pub fn trusted_proxy() -> bool {
+            *TRUSTED_PROXY
+        }
+
+        /// Hashes every manifest and config received and flags those not matching their digest
+        pub fn verify_digests() -> bool {
+            *VERIFY_DIGESTS
+        }
+
+        /// Repositories the home page lists at most, the others are only reachable through the
+        /// repository search. Walking the catalog with the registry cursor is not capped
+        pub fn max_catalog_size() -> Option<usize> {
+            *MAX_CATALOG_SIZE
+        }
+
+        /// Images with more tags only fetch the manifest and config of this many tags per page
+        pub fn max_enriched_tags() -> Option<usize> {
+            *MAX_ENRICHED_TAGS
+        }
+
+        /// Page size used when the request does not specify one
+        pub fn default_page_size() -> usize {
+            *DEFAULT_PAGE_SIZE
+        }
+
+        /// Language of the pages when neither the `lang` cookie nor `Accept-Language` picks one
+        pub fn default_locale() -> Locale {
+            *DEFAULT_LOCALE
+        }
+
         pub fn check() {
             let _ = registry_host();
             let _ = registry_username();
             let _ = registry_password();
+            let _ = registries();
+            let _ = registry_pool_max_idle_per_host();
+            let _ = registry_pool_idle_timeout_secs();
+            let _ = registry_probe_secs();
+            let _ = registry_catalog_timeout_secs();
+            let _ = registry_manifest_timeout_secs();
+            let _ = registry_http1_only();
+            let _ = registry_max_body_bytes();
+            let _ = registry_max_concurrent_requests();
+            let _ = delete_batch_size();
+            let _ = registry_revalidate_before_delete();
+            let _ = registry_http_proxy();
+            let _ = registry_http_proxy_credentials();
+            let _ = registry_no_proxy();
+            let _ = registry_proxy_disabled();
+            let _ = registry_user_agent_suffix();
+            let _ = registry_flavor();
+            let _ = registry_extra_headers();
+            let _ = default_page_size();
+            let _ = default_locale();
+            let _ = home_refresh_secs();
+            let _ = warm_cache_on_start();
+            let _ = access_log();
+            let _ = max_catalog_size();
+            let _ = max_enriched_tags();
+            let _ = verify_digests();
+            let _ = cookie_secure();
+            let _ = cookie_same_site();
+            let _ = cookie_domain();
+            let _ = instance_title();
+            let _ = instance_logo_url();
+            let _ = instance_banner();
+            let _ = trusted_proxy();
+            let _ = allow_indexing();
+            let _ = logout_redirect_url();
+            let _ = auth_mode();
+            let _ = auth_clear_stale_cookie();
+            let _ = read_only();
+            let _ = protected_tags();
+            let _ = hidden_repositories();
+            let _ = registry_namespace();
+            let _ = known_repositories();
+            let _ = audit_log_path();
+            let _ = prefer_platform();
+            let _ = probe_deletions();
+            let _ = vuln_scan_url_template();
+            let _ = registry_api_prefix();
             let _ = listen_addr();
             let _ = listen_port();
             let _ = static_dir();
+            let _ = favicon_path();
             let _ = explorer_username();
             let _ = explorer_password();
+            let _ = explorer_viewer_credentials();
+            let _ = explorer_idle_timeout_minutes();
+            if let Some(warning) = super::credentials_warning() {
+                tracing::warn!("{warning}");
+            }
         }
     }
 
+    pub mod forwarded {
+        use axum::http::{HeaderMap, header};
+
+        use super::env;
+
+        /// First value of a header set by the trusted proxy, a chain of proxies appends theirs
+        fn forwarded_header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+            env::trusted_proxy()
+                .then(|| headers.get(name))
+                .flatten()
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        }
+
+        /// Scheme the browser used, only known behind a trusted proxy since the explorer does
+        /// not terminate TLS
+        pub fn proto(headers: &HeaderMap) -> Option<&str> {
+            forwarded_header(headers, "x-forwarded-proto")
+        }
+
+        pub fn host(headers: &HeaderMap) -> Option<&str> {
+            forwarded_header(headers, "x-forwarded-host").or_else(|| {
+                headers
+                    .get(header::HOST)
+                    .and_then(|value| value.to_str().ok())
+            })
+        }
+
+        /// Absolute URL of `path` as seen by the browser, `path` itself when the scheme is
+        /// unknown
+        pub fn external_url(headers: &HeaderMap, path: &str) -> String {
+            match (proto(headers), host(headers)) {
+                (Some(proto), Some(host)) => format!("{proto}://{host}{path}"),
+                _ => path.to_owned(),
+            }
+        }
+
+        /// `COOKIE_SECURE` unless a trusted proxy tells the scheme
+        pub fn cookie_secure(headers: &HeaderMap) -> bool {
+            proto(headers).map_or_else(env::cookie_secure, |proto| {
+                proto.eq_ignore_ascii_case("https")
+            })
+        }
+    }
+
+    /// `from` when it is a path of the explorer. Browsers read a backslash as a slash and ignore
+    /// some control characters, `/\host` would lead to another site
+    pub fn local_redirect_target(from: &str) -> Option<&str> {
+        let local = from.starts_with('/')
+            && !from.starts_with("//")
+            && !from.contains('\\')
+            && !from.chars().any(char::is_control);
+        local.then_some(from)
+    }
+
+    pub fn is_protected_tag(tag: &str) -> bool {
+        env::protected_tags()
+            .iter()
+            .any(|pattern| glob_match(pattern, tag))
+    }
+
+    /// `image_name` as `docker pull` knows it, host and `REGISTRY_NAMESPACE` included
+    pub fn image_reference(image_name: &str) -> String {
+        match env::registry_namespace() {
+            Some(namespace) => format!("{}/{namespace}/{image_name}", env::registry_image_host()),
+            None => format!("{}/{image_name}", env::registry_image_host()),
+        }
+    }
+
+    /// Query parameters whose value never reaches the access log: credentials pasted into a URL,
+    /// or sent by a script or form using `GET`, would otherwise be written in clear
+    const REDACTED_QUERY_PARAMS: [&str; 4] = ["username", "password", "token", "access_token"];
+
+    /// `query` with the values of credential parameters replaced by `REDACTED`
+    pub fn redacted_query(query: &str) -> String {
+        query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _))
+                    if REDACTED_QUERY_PARAMS.contains(&key.to_ascii_lowercase().as_str()) =>
+                {
+                    format!("{key}=REDACTED")
+                }
+                _ => pair.to_owned(),
+            })
+            .join("&")
+    }
+
+    pub fn is_hidden_repository(image_name: &str) -> bool {
+        env::hidden_repositories()
+            .iter()
+            .any(|pattern| glob_match(pattern, image_name))
+    }
+
+    /// Matches `text` against a pattern where `*` stands for any sequence and `?` for any
+    /// character
+    fn glob_match(pattern: &str, text: &str) -> bool {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        let text = text.chars().collect::<Vec<_>>();
+        let (mut p, mut t) = (0, 0);
+        let mut backtrack = None;
+        while t < text.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == text[t] => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match backtrack {
+                    Some((star, matched)) => {
+                        p = star + 1;
+                        t = matched + 1;
+                        backtrack = Some((star, matched + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+
+    /// Explains why the explorer or viewer credentials are unsafe, if they are
+    pub fn credentials_warning() -> Option<&'static str> {
+        const WEAK_CREDENTIALS: [(&str, &str); 4] = [
+            ("admin", "admin"),
+            ("admin", "password"),
+            ("root", "root"),
+            ("user", "password"),
+        ];
+
+        let registry_credentials = (env::registry_username(), env::registry_password());
+        let explorer_credentials = (env::explorer_username(), env::explorer_password());
+        let viewer_credentials = env::explorer_viewer_credentials();
+        if explorer_credentials == registry_credentials {
+            Some("The explorer credentials are the same as the registry credentials.")
+        } else if WEAK_CREDENTIALS.contains(&explorer_credentials) {
+            Some("The explorer credentials are a well-known default, change them.")
+        } else if viewer_credentials == Some(registry_credentials) {
+            Some("The viewer credentials are the same as the registry credentials.")
+        } else if viewer_credentials
+            .is_some_and(|credentials| WEAK_CREDENTIALS.contains(&credentials))
+        {
+            Some("The viewer credentials are a well-known default, change them.")
+        } else {
+            None
+        }
+    }
+
+    pub mod docker_config {
+        use std::{
+            collections::HashMap,
+            io::Write,
+            path::PathBuf,
+            process::{Command, Stdio},
+        };
+
+        use anyhow::{Context, anyhow, ensure};
+        use base64::Engine;
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DockerConfig {
+            #[serde(default)]
+            auths: HashMap<String, AuthEntry>,
+            creds_store: Option<String>,
+            #[serde(default)]
+            cred_helpers: HashMap<String, String>,
+        }
+
+        #[derive(Deserialize)]
+        struct AuthEntry {
+            auth: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct HelperCredentials {
+            username: String,
+            secret: String,
+        }
+
+        /// Looks up the credentials of `registry_host` in the Docker config at `path`
+        ///
+        /// `path` is either the `config.json` file or the directory containing it. Entries from
+        /// `credHelpers` come first, then `credsStore`, then the base64 `auths` entries.
+        pub fn credentials(
+            path: &str,
+            registry_host: &str,
+        ) -> anyhow::Result<Option<(String, String)>> {
+            let mut path = PathBuf::from(path);
+            if path.is_dir() {
+                path.push("config.json");
+            }
+            let config = std::fs::read_to_string(&path)
+                .with_context(|| format!("could not read {}", path.display()))?;
+            let config = serde_json::from_str::<DockerConfig>(&config)?;
+
+            let helper = config
+                .cred_helpers
+                .iter()
+                .find(|(host, _)| normalize_host(host) == registry_host)
+                .map(|(_, helper)| helper)
+                .or(config.creds_store.as_ref());
+            if let Some(helper) = helper {
+                return run_helper(helper, registry_host).map(Some);
+            }
+
+            config
+                .auths
+                .iter()
+                .find(|(host, _)| normalize_host(host) == registry_host)
+                .and_then(|(_, entry)| entry.auth.as_deref())
+                .map(decode_auth)
+                .transpose()
+        }
+
+        /// `auths` keys may carry a scheme and an API path, e.g. `https://host/v1/`
+        fn normalize_host(host: &str) -> &str {
+            let host = host.split_once("://").map_or(host, |(_, host)| host);
+            host.split_once('/').map_or(host, |(host, _)| host)
+        }
+
+        fn decode_auth(auth: &str) -> anyhow::Result<(String, String)> {
+            let auth = base64::engine::general_purpose::STANDARD.decode(auth)?;
+            let auth = String::from_utf8(auth)?;
+            let (username, password) = auth
+                .split_once(':')
+                .ok_or_else(|| anyhow!("auth entry is not username:password"))?;
+            Ok((username.to_owned(), password.to_owned()))
+        }
+
+        fn run_helper(helper: &str, registry_host: &str) -> anyhow::Result<(String, String)> {
+            let program = format!("docker-credential-{helper}");
+            let mut child = Command::new(&program)
+                .arg("get")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .with_context(|| format!("could not run {program}"))?;
+            child
+                .stdin
+                .take()
+                .ok_or_else(|| anyhow!("{program} stdin unavailable"))?
+                .write_all(registry_host.as_bytes())?;
+            let output = child.wait_with_output()?;
+            ensure!(output.status.success(), "{program} failed");
+            let credentials = serde_json::from_slice::<HelperCredentials>(&output.stdout)?;
+            Ok((credentials.username, credentials.secret))
+        }
+    }
+
+    /// Outcome of the last registry ping
+    #[derive(Clone, Copy)]
+    pub struct RegistryProbe {
+        pub reachable: bool,
+        pub checked_at: chrono::DateTime<chrono::Utc>,
+    }
+
+    pub type SharedRegistryProbe = Arc<RwLock<RegistryProbe>>;
+
+    /// Pings the registry and stores the outcome, each failure is only logged at debug level
+    pub async fn probe_registry(
+        registry_api_client: &registry::api::Client,
+        registry_probe: &SharedRegistryProbe,
+    ) -> RegistryProbe {
+        let reachable = registry_api_client
+            .ping()
+            .await
+            .inspect_err(|err| tracing::debug!("Registry probe failed: {err}"))
+            .is_ok();
+        let probe = RegistryProbe {
+            reachable,
+            checked_at: chrono::Utc::now(),
+        };
+        if let Ok(mut registry_probe) = registry_probe.write() {
+            *registry_probe = probe;
+        }
+        probe
+    }
+
+    /// Pings the registry every `period`, the state is only logged when it changes
+    pub fn spawn_registry_probe(
+        registry_api_client: registry::api::Client,
+        registry_probe: SharedRegistryProbe,
+        period: Duration,
+    ) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The startup ping already filled the state
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let was_reachable = registry_probe
+                    .read()
+                    .is_ok_and(|registry_probe| registry_probe.reachable);
+                let probe = probe_registry(&registry_api_client, &registry_probe).await;
+                if probe.reachable && !was_reachable {
+                    tracing::info!("Registry reachable again");
+                } else if !probe.reachable && was_reachable {
+                    tracing::warn!("Registry unreachable, pages show the maintenance page");
+                }
+            }
+        });
+    }
+
     /// Pagination struct
     ///
     /// `page` is 0 indexed
@@ -136,7 +1635,32 @@ pub mod service {
         pub data: Vec<T>,
     }
 
+    /// Written by hand to include `total_pages`, which is derived from the other fields
+    impl<T: serde::Serialize> serde::Serialize for Paginated<T> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+
+            let mut state = serializer.serialize_struct("Paginated", 5)?;
+            state.serialize_field("page", &self.page)?;
+            state.serialize_field("size", &self.size)?;
+            state.serialize_field("total_element_count", &self.total_element_count)?;
+            state.serialize_field("total_pages", &self.total_pages())?;
+            state.serialize_field("data", &self.data)?;
+            state.end()
+        }
+    }
+
     impl<T> Paginated<T> {
+        /// Single page holding all of `data`, for data already paginated upstream
+        pub fn whole(data: Vec<T>) -> Self {
+            Self {
+                page: 0,
+                size: data.len().max(1),
+                total_element_count: data.len(),
+                data,
+            }
+        }
+
         pub const fn previous(&self) -> usize {
             self.page.saturating_sub(1)
         }
@@ -184,7 +1708,7 @@ pub mod service {
                 page: self.page,
                 size: self.size,
                 total_element_count: self.total_element_count,
-                data: futures::future::join_all(self.data.into_iter()).await,
+                data: futures::future::join_all(self.data).await,
             }
         }
     }
@@ -202,20 +1726,144 @@ pub mod service {
     }
 
     pub mod auth {}
+
+    /// Assets of the `static` dir compiled into the binary, so that it runs without
+    /// `STATIC_DIR`
+    #[cfg(feature = "embed-static")]
+    pub mod embedded_static {
+        use include_dir::{Dir, File, include_dir};
+
+        static ASSETS: Dir = include_dir!("$CARGO_MANIFEST_DIR/static");
+
+        pub fn get(path: &str) -> Option<&'static [u8]> {
+            ASSETS.get_file(path).map(File::contents)
+        }
+    }
+
+    pub mod density {
+        use axum_extra::extract::{
+            CookieJar,
+            cookie::{Cookie, SameSite},
+        };
+
+        use crate::common::dto::TableDensity;
+
+        pub const TABLE_DENSITY_COOKIE_NAME: &str = "table_density";
+
+        #[easy_ext::ext(CookieJarDensityExt)]
+        impl CookieJar {
+            pub fn set_table_density(self, density: TableDensity) -> Self {
+                self.add(
+                    Cookie::build((TABLE_DENSITY_COOKIE_NAME, density.as_str()))
+                        .http_only(true)
+                        .secure(super::env::cookie_secure())
+                        .path("/")
+                        .same_site(SameSite::Strict)
+                        .permanent(),
+                )
+            }
+
+            pub fn table_density(&self) -> TableDensity {
+                self.get(TABLE_DENSITY_COOKIE_NAME)
+                    .and_then(|cookie| TableDensity::from_str(cookie.value()))
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    pub mod flash {
+        use axum_extra::extract::{
+            CookieJar,
+            cookie::{Cookie, SameSite},
+        };
+
+        use crate::common::dto::Flash;
+
+        pub const FLASH_COOKIE_NAME: &str = "flash";
+
+        #[easy_ext::ext(CookieJarFlashExt)]
+        impl CookieJar {
+            pub fn set_flash(self, flash: &Flash) -> Self {
+                self.add(
+                    Cookie::build((
+                        FLASH_COOKIE_NAME,
+                        serde_urlencoded::to_string(flash).unwrap_or_default(),
+                    ))
+                    .http_only(true)
+                    .secure(super::env::cookie_secure())
+                    .path("/")
+                    .same_site(SameSite::Strict),
+                )
+            }
+
+            /// Reads the pending flash and removes it so it is only shown once
+            pub fn take_flash(self) -> (CookieJar, Option<Flash>) {
+                let flash = self
+                    .get(FLASH_COOKIE_NAME)
+                    .and_then(|cookie| serde_urlencoded::from_str::<Flash>(cookie.value()).ok());
+                if flash.is_some() {
+                    (
+                        self.remove(Cookie::build(FLASH_COOKIE_NAME).path("/").build()),
+                        flash,
+                    )
+                } else {
+                    (self, None)
+                }
+            }
+        }
+    }
 }
 
 pub mod view {
     use maud::{Markup, html};
 
-    use crate::common::service;
+    use crate::{
+        auth::dto::{AuthMode, Role},
+        common::{
+            dto::{Flash, TableDensity},
+            service::{self, Paginated},
+        },
+        i18n::{self, service::t},
+        registries,
+    };
+
+    /// Switches to the other density and comes back to `from`
+    pub fn density_toggle(density: TableDensity, from: &str) -> Markup {
+        html! {
+            form .m-0 method="post" action="/density" {
+                input type="hidden" name="density" value=(density.toggled().as_str());
+                input type="hidden" name="from" value=(from);
+                button .btn .btn-outline-secondary type="submit" {
+                    @match density {
+                        TableDensity::Comfortable => (t("compact_rows")),
+                        TableDensity::Compact => (t("comfortable_rows")),
+                    }
+                }
+            }
+        }
+    }
 
-    pub fn head_with_extra(js: Vec<&'static str>, css: Vec<&'static str>) -> Markup {
+    /// `title` names the page in browser tabs and bookmarks, before the instance title
+    pub fn head_with_extra(
+        title: Option<&str>,
+        js: Vec<&'static str>,
+        css: Vec<&'static str>,
+        refresh_secs: Option<u64>,
+    ) -> Markup {
         html! {
             head {
-                title { "Docker Registry Explorer" }
+                title {
+                    @if let Some(title) = title {
+                        (title) " \u{2013} "
+                    }
+                    (service::env::instance_title())
+                }
                 meta charset="utf-8";
                 meta name="viewport" content="width=device-width, initial-scale=1";
-                link rel="icon" href="static/favicon.svg" sizes="any" type="image/svg+xml";
+                @if let Some(refresh_secs) = refresh_secs {
+                    meta http-equiv="refresh" content=(refresh_secs);
+                }
+                link rel="icon" href="/favicon.ico" sizes="any";
                 link href="https://cdn.jsdelivr.net/npm/bootstrap@5.3.8/dist/css/bootstrap.min.css" rel="stylesheet" integrity="sha384-sRIl4kxILFvY47J16cr9ZwB07vP4J8+LH7qKQnuqkuIAvNWLzeN8tE5YBujZqJLB" crossorigin="anonymous";
                 link rel="stylesheet" href="/static/css/main.css";
                 @for css in css {
@@ -228,30 +1876,235 @@ pub mod view {
         }
     }
 
-    pub fn head() -> Markup {
-        head_with_extra(vec![], vec![])
+    pub fn head(title: Option<&str>) -> Markup {
+        head_with_extra(title, vec![], vec![], None)
     }
 
-    pub fn header() -> Markup {
+    /// The registry selector and the logout button need an authenticated user, `role` is `None`
+    /// on the login and anonymous error pages
+    pub fn header(role: Option<Role>) -> Markup {
         html! {
             header .d-flex .justify-content-between .align-items-center .py-2 .px-2 {
-                h1 .m-0 { "Docker Registry Explorer" }
-                form .m-0 method="post" action="/auth/logout" {
-                     button .btn .btn-primary type="submit" { "Logout" }
+                .d-flex .align-items-center .gap-2 {
+                    @if let Some(logo_url) = service::env::instance_logo_url() {
+                        img src=(logo_url) alt="" height="40";
+                    }
+                    h1 .m-0 { (service::env::instance_title()) }
+                }
+                @if role.is_some() {
+                    .d-flex .align-items-center .gap-2 {
+                        (registries::view::selector())
+                        // Browsers keep basic credentials until they are closed
+                        @if service::env::auth_mode() == AuthMode::Form {
+                            form .m-0 method="post" action="/auth/logout" {
+                                 button .btn .btn-primary type="submit" { (t("logout")) }
+                            }
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// `INSTANCE_BANNER`, one paragraph per line
+    pub fn instance_banner() -> Markup {
+        html! {
+            @if let Some(banner) = service::env::instance_banner() {
+                .alert .alert-info .m-2 {
+                    @for line in banner.lines().filter(|line| !line.trim().is_empty()) {
+                        p .m-0 {
+                            @for part in banner_parts(line) {
+                                @match part {
+                                    BannerPart::Text(text) => (text),
+                                    BannerPart::Link { text, url } => a .alert-link href=(url) target="_blank" rel="noopener" { (text) },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    enum BannerPart<'a> {
+        Text(&'a str),
+        Link { text: &'a str, url: &'a str },
+    }
+
+    /// Splits the `[text](url)` links out of a banner line, only http(s) URLs are linked
+    fn banner_parts(mut line: &str) -> Vec<BannerPart<'_>> {
+        let mut parts = Vec::new();
+        while let Some(start) = line.find('[') {
+            let link = line[start + 1..].split_once("](").and_then(|(text, rest)| {
+                let (url, after) = rest.split_once(')')?;
+                (url.starts_with("https://") || url.starts_with("http://"))
+                    .then_some((text, url, after))
+            });
+            match link {
+                Some((text, url, after)) => {
+                    parts.push(BannerPart::Text(&line[..start]));
+                    parts.push(BannerPart::Link { text, url });
+                    line = after;
+                }
+                None => {
+                    parts.push(BannerPart::Text(&line[..=start]));
+                    line = &line[start + 1..];
+                }
+            }
+        }
+        parts.push(BannerPart::Text(line));
+        parts
+    }
+
+    /// Stands in for a delete action when the registry does not allow deletions, a disabled
+    /// button shows no tooltip so it is carried by a wrapper
+    pub fn disabled_delete_button(label: &str) -> Markup {
+        html! {
+            span .d-inline-block tabindex="0" title="Deletions are disabled on this registry (REGISTRY_STORAGE_DELETE_ENABLED)" {
+                button .btn .btn-danger type="button" disabled { (label) }
+            }
+        }
+    }
+
+    /// Outcome of a bulk deletion run with `?dry_run=1`, nothing was deleted
+    pub fn dry_run(
+        role: Role,
+        title: &str,
+        back_href: &str,
+        manifests: &[(String, Vec<String>)],
+    ) -> Markup {
+        let tag_count = manifests.iter().map(|(_, tags)| tags.len()).sum::<usize>();
+        page()
+            .role(role)
+            .content(html! {
+                .m-2 {
+                    h1 { (title) " (dry run)" }
+                    .alert .alert-info {
+                        "Would delete " (tag_count) " tag(s) and " (manifests.len()) " manifest(s). Nothing was deleted."
+                    }
+                    @if !manifests.is_empty() {
+                        table .table .table-striped .table-bordered .align-middle .text-center {
+                            thead { tr { th { "Digest" } th { "Tags" } } }
+                            tbody {
+                                @for (digest, tags) in manifests {
+                                    tr {
+                                        td { code { (digest) } }
+                                        td { (tags.join(", ")) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    a .btn .btn-secondary href=(back_href) { "Back" }
+                }
+            })
+            .call()
+    }
+
+    /// Whole page for an HTTP error, with a way back to the image list. `role` is `None` for
+    /// anonymous requests
+    pub fn error_page(role: Option<Role>, status: axum::http::StatusCode, message: &str) -> Markup {
+        page()
+            .maybe_role(role)
+            .content(html! {
+                .m-2 .text-center {
+                    h1 { (status.as_u16()) " " (status.canonical_reason().unwrap_or_default()) }
+                    p { (message) }
+                    a .btn .btn-primary href="/" { (t("back_to_images")) }
+                }
+            })
+            .call()
+    }
+
+    /// Shown by the maintenance layer in place of any page while the registry is unreachable
+    pub fn maintenance_page(
+        role: Role,
+        checked_at: chrono::DateTime<chrono::Utc>,
+        from: &str,
+    ) -> Markup {
+        page()
+            .role(role)
+            .content(html! {
+                .d-flex .justify-content-center .m-4 {
+                    .card .border-warning .text-center style="max-width: 32rem" {
+                        .card-body {
+                            h1 .card-title .fs-3 { (t("registry_unavailable")) }
+                            p .card-text {
+                                (t("registry_down.before")) " " code { (service::env::registry_url()) }
+                                " " (t("registry_down.after"))
+                            }
+                            p .text-muted title=(checked_at.to_rfc3339()) {
+                                (i18n::service::tf("last_checked", crate::image::view::format_duration(chrono::Utc::now() - checked_at)))
+                            }
+                            form .m-0 method="post" action="/maintenance/retry" {
+                                input type="hidden" name="from" value=(from);
+                                button .btn .btn-primary type="submit" { (t("retry")) }
+                            }
+                        }
+                    }
+                }
+            })
+            .call()
+    }
+
     pub fn footer() -> Markup {
         html! {
             footer .d-flex .justify-content-center .align-items-center .py-2 .px-2 .mx-2 .border-top {
                 p .m-0 { "Docker Registry Explorer \u{B7} " (format!("{}", service::APP_VERSION)) }
+                (i18n::view::selector())
             }
         }
     }
 
-    impl<S: page_builder::State> PageBuilder<S> {
+    /// Previous/next links and a "Go to page" input for `pagination`
+    ///
+    /// `params` are the extra query parameters (filter, sort, ...) carried over to the linked pages
+    pub fn pagination_fragment<T>(
+        pagination: &Paginated<T>,
+        prefix: &str,
+        params: &[(&str, &str)],
+    ) -> Markup {
+        let page = pagination.page;
+        let total_pages = pagination.total_pages();
+
+        let page_href = |page: usize| {
+            let page = page.to_string();
+            let size = pagination.size.to_string();
+            let query = [("page", page.as_str()), ("size", size.as_str())]
+                .into_iter()
+                .chain(params.iter().copied())
+                .filter(|(_, value)| !value.is_empty())
+                .collect::<Vec<_>>();
+            format!(
+                "{prefix}?{}",
+                serde_urlencoded::to_string(query).unwrap_or_default()
+            )
+        };
+
+        html! {
+            .d-flex .my-2 .gap-2 {
+                @if page > 0 {
+                    a .btn .btn-primary href=(page_href(pagination.previous())) { (t("previous")) }
+                }
+                span .align-self-center { (page + 1) " / " (total_pages) }
+                @if page + 1 < total_pages {
+                    a .btn .btn-primary href=(page_href(pagination.next())) { (t("next")) }
+                }
+                @if total_pages > 2 {
+                    form .d-flex .gap-1 .m-0 method="get" action=(prefix) {
+                        input type="hidden" name="size" value=(pagination.size);
+                        @for (name, value) in params.iter().filter(|(_, value)| !value.is_empty()) {
+                            input type="hidden" name=(name) value=(value);
+                        }
+                        input .form-control .form-control-sm style="width: 5rem" type="number" name="goto" min="1" max=(total_pages) placeholder=(t("page")) aria-label=(t("go_to_page")) required;
+                        button .btn .btn-sm .btn-outline-primary type="submit" { (t("go")) }
+                    }
+                }
+            }
+        }
+    }
+
+    impl<S: page_builder::State> PageBuilder<'_, S> {
         pub fn js(mut self, value: &'static str) -> Self {
             self.js.push(value);
             self
@@ -269,13 +2122,27 @@ pub mod view {
     pub fn page(
         #[builder(field)] js: Vec<&'static str>,
         #[builder(field)] css: Vec<&'static str>,
+        title: Option<&str>,
+        /// Role of the authenticated user, `None` on anonymous pages
+        role: Option<Role>,
+        flash: Option<Flash>,
+        /// Reloads the page after that many seconds, for pages following a background task
+        refresh_secs: Option<u64>,
         content: Markup,
     ) -> Markup {
         html! {
-            html {
-                (head_with_extra(js, css))
+            html lang=(i18n::service::locale().as_str()) {
+                (head_with_extra(title, js, css, refresh_secs))
                 body .d-flex .flex-column .min-vh-100 {
-                    (header())
+                    (header(role))
+                    @if let Some(warning) = service::credentials_warning().filter(|_| role == Some(Role::Admin)) {
+                        .alert .alert-danger .m-2 { (warning) }
+                    }
+                    @if let Some(flash) = flash {
+                        div class=(format!("alert alert-{} m-2", flash.level.as_str())) {
+                            (flash.message)
+                        }
+                    }
                     main .flex-fill {
                         (content)
                     }
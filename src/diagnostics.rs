@@ -0,0 +1,226 @@
+pub mod dto {
+    use crate::registry::dto::{AuthScheme, CacheStats, StorageUsage};
+
+    /// What the connected registry supports, every probe fails on its own
+    pub struct Diagnostics {
+        /// `Docker-Distribution-Api-Version` announced by the API base
+        pub api_version: Result<Option<String>, String>,
+        pub auth_scheme: Result<AuthScheme, String>,
+        pub catalog: Result<bool, String>,
+        /// `None` when no repository is known to probe it with
+        pub referrers: Option<Result<bool, String>>,
+        /// `None` when `REGISTRY_FLAVOR` does not expose it
+        pub storage: Option<Result<StorageUsage, String>>,
+        /// Probed once at startup with `PROBE_DELETIONS`, `None` when unknown
+        pub deletions_enabled: Option<bool>,
+        pub cache: CacheStats,
+        /// Repository count and date of the home page snapshot
+        pub catalog_snapshot: Option<(usize, chrono::DateTime<chrono::Utc>)>,
+        pub last_catalog_fetch: Option<chrono::DateTime<chrono::Utc>>,
+    }
+}
+
+pub mod handler {
+    use axum::extract::State;
+    use maud::Markup;
+
+    use crate::{
+        AppState,
+        auth::middleware::RequireAdmin,
+        diagnostics::{service, view},
+    };
+
+    pub async fn index(
+        State(AppState {
+            registry_api_client,
+            catalog_snapshot,
+            last_catalog_fetch,
+            deletions_enabled,
+            ..
+        }): State<AppState>,
+        _: RequireAdmin,
+    ) -> Markup {
+        view::index(
+            &service::diagnose(
+                &registry_api_client,
+                &catalog_snapshot,
+                &last_catalog_fetch,
+                deletions_enabled,
+            )
+            .await,
+        )
+    }
+}
+
+pub mod service {
+    use crate::{
+        diagnostics::dto::Diagnostics,
+        home::service::{LastCatalogFetch, SharedCatalogSnapshot},
+        registry,
+    };
+
+    /// Runs the probes side by side, the referrers API is probed on the first listed repository
+    pub async fn diagnose(
+        registry_api_client: &registry::api::Client,
+        catalog_snapshot: &SharedCatalogSnapshot,
+        last_catalog_fetch: &LastCatalogFetch,
+        deletions_enabled: Option<bool>,
+    ) -> Diagnostics {
+        let (api_version, auth_scheme, catalog, storage) = futures::join!(
+            registry_api_client.ping(),
+            registry_api_client.auth_scheme(),
+            registry_api_client.catalog_page(1, None),
+            registry_api_client.storage_usage(),
+        );
+        let (catalog, first_repository) = match catalog {
+            Ok(page) => (
+                Ok(!registry_api_client.catalog_unsupported()),
+                page.repositories.into_iter().next(),
+            ),
+            Err(err) => (Err(format!("{err:#}")), None),
+        };
+        let referrers = match first_repository {
+            Some(repository) => Some(
+                registry_api_client
+                    .referrers_supported(&repository)
+                    .await
+                    .map_err(|err| format!("{err:#}")),
+            ),
+            None => None,
+        };
+        Diagnostics {
+            api_version: api_version.map_err(|err| format!("{err:#}")),
+            auth_scheme: auth_scheme.map_err(|err| format!("{err:#}")),
+            catalog,
+            referrers,
+            storage: storage
+                .transpose()
+                .map(|storage| storage.map_err(|err| format!("{err:#}"))),
+            deletions_enabled,
+            cache: registry_api_client.cache_stats(),
+            catalog_snapshot: catalog_snapshot.read().ok().and_then(|snapshot| {
+                snapshot
+                    .as_ref()
+                    .map(|snapshot| (snapshot.images.len(), snapshot.taken_at))
+            }),
+            last_catalog_fetch: last_catalog_fetch
+                .read()
+                .ok()
+                .and_then(|last_catalog_fetch| *last_catalog_fetch),
+        }
+    }
+}
+
+pub mod view {
+    use maud::{Markup, html};
+
+    use crate::{
+        auth::dto::Role,
+        common::{self, service::env},
+        diagnostics::dto::Diagnostics,
+        image::view::format_size,
+        registry::dto::AuthScheme,
+    };
+
+    pub fn index(diagnostics: &Diagnostics) -> Markup {
+        let cache = &diagnostics.cache;
+        common::view::page()
+            .role(Role::Admin)
+            .content(html! {
+                .m-2 {
+                    h1 { "Diagnostics" }
+                    p .text-muted {
+                        "Probed now against " code { (env::registry_url()) (env::registry_api_prefix()) }
+                    }
+                    table .table .table-bordered .align-middle {
+                        tbody {
+                            tr {
+                                th { "API version" }
+                                @match &diagnostics.api_version {
+                                    Ok(Some(version)) if version == "registry/2.0" => td .text-success { (version) },
+                                    Ok(Some(version)) => td .text-warning { (version) },
+                                    Ok(None) => td .text-warning { "Not announced, the registry may not be a distribution registry" },
+                                    Err(err) => td .text-danger { "Unreachable: " (err) },
+                                }
+                            }
+                            tr {
+                                th { "Authentication" }
+                                @match &diagnostics.auth_scheme {
+                                    Ok(AuthScheme::Anonymous) => td { "None, anonymous clients are let in" },
+                                    Ok(AuthScheme::Basic) => td { "Basic" },
+                                    Ok(AuthScheme::Bearer { realm }) => td { "Bearer, tokens from " code { (realm) } },
+                                    Ok(AuthScheme::Other(scheme)) => td .text-warning { "Unsupported scheme " code { (scheme) } },
+                                    Err(err) => td .text-danger { (err) },
+                                }
+                            }
+                            tr {
+                                th { "Catalog" }
+                                @match &diagnostics.catalog {
+                                    Ok(true) => td .text-success { "Listed" },
+                                    Ok(false) => td .text-warning { "Refused, KNOWN_REPOSITORIES is listed instead" },
+                                    Err(err) => td .text-danger { (err) },
+                                }
+                            }
+                            tr {
+                                th { "Deletions" }
+                                @if env::read_only() {
+                                    td { "Disabled by READ_ONLY" }
+                                } @else {
+                                    @match diagnostics.deletions_enabled {
+                                        Some(true) => td .text-success { "Enabled" },
+                                        Some(false) => td .text-warning { "Disabled in the registry" },
+                                        None => td .text-muted { "Unknown, set PROBE_DELETIONS or the startup probe failed" },
+                                    }
+                                }
+                            }
+                            tr {
+                                th { "Referrers API" }
+                                @match &diagnostics.referrers {
+                                    Some(Ok(true)) => td .text-success { "Supported" },
+                                    Some(Ok(false)) => td { "Not supported" },
+                                    Some(Err(err)) => td .text-danger { (err) },
+                                    None => td .text-muted { "No repository to probe it with" },
+                                }
+                            }
+                            @if let Some(storage) = &diagnostics.storage {
+                                tr {
+                                    th { "Storage" }
+                                    @match storage {
+                                        Ok(usage) => td {
+                                            (format_size(usage.used)) " used"
+                                            @if let Some(quota) = usage.quota {
+                                                " of a " (format_size(quota)) " quota"
+                                            } @else {
+                                                ", no quota"
+                                            }
+                                        },
+                                        Err(err) => td .text-danger { (err) },
+                                    }
+                                }
+                            }
+                            tr {
+                                th { "Client cache" }
+                                td {
+                                    (cache.manifests) " manifest(s), "
+                                    (cache.seen_digests) " digest(s) seen, "
+                                    (cache.tokens) " bearer token(s)"
+                                }
+                            }
+                            tr {
+                                th { "Catalog snapshot" }
+                                @match diagnostics.catalog_snapshot {
+                                    Some((images, taken_at)) => td { (images) " repositories, taken " (taken_at.to_rfc3339()) },
+                                    None => td .text-muted { "None, set HOME_REFRESH_SECS or WARM_CACHE_ON_START" },
+                                }
+                            }
+                            tr {
+                                th { "Last catalog fetch" }
+                                td { (diagnostics.last_catalog_fetch.map_or_else(|| "Never".to_owned(), |date| date.to_rfc3339())) }
+                            }
+                        }
+                    }
+                }
+            })
+            .call()
+    }
+}
@@ -4,6 +4,56 @@ pub mod service {
     #[derive(Debug, DisplayFromDebug)]
     pub enum Error {
         Unknown,
+        /// A deletion was attempted while `READ_ONLY` is set
+        ReadOnly,
+        /// The manifest is referenced by a tag matching `PROTECTED_TAGS`
+        Protected,
+        /// The registry could not be reached or refused the request
+        Unreachable(String),
+        /// The registry refused the configured credentials
+        Unauthorized(String),
+        /// A registry request went past `REGISTRY_CATALOG_TIMEOUT_SECS` or
+        /// `REGISTRY_MANIFEST_TIMEOUT_SECS`
+        Timeout(Operation),
+    }
+
+    /// Registry requests with their own timeout
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Operation {
+        Catalog,
+        TagDetails,
+    }
+
+    impl Operation {
+        pub const fn label(self) -> &'static str {
+            match self {
+                Self::Catalog => "Catalog",
+                Self::TagDetails => "Tag details",
+            }
+        }
+    }
+
+    impl Error {
+        /// `Timeout` when `err` comes from a request that timed out, `Unauthorized` from a 401 or
+        /// 403 answer, `Unreachable` otherwise
+        pub fn registry(operation: Operation, err: &anyhow::Error) -> Self {
+            let causes = err
+                .chain()
+                .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+                .collect::<Vec<_>>();
+            if causes.iter().any(|cause| cause.is_timeout()) {
+                Self::Timeout(operation)
+            } else if causes.iter().any(|cause| {
+                matches!(
+                    cause.status(),
+                    Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN)
+                )
+            }) {
+                Self::Unauthorized(format!("{err:#}"))
+            } else {
+                Self::Unreachable(format!("{err:#}"))
+            }
+        }
     }
 
     pub type ServiceResult<T> = Result<T, Error>;
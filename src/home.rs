@@ -1,136 +1,1454 @@
 pub mod dto {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, PartialEq, Eq, Serialize)]
     pub struct Image {
         pub name: String,
         pub tag_count: usize,
+        /// Only known when `REGISTRY_FLAVOR` exposes pull counts
+        pub pull_count: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct ImageListQuery {
+        #[serde(default)]
+        pub sort: ImageSort,
+        /// Reveals the repositories matching `HIDDEN_REPOSITORIES`, ignored for viewers
+        #[serde(default)]
+        pub show_hidden: bool,
+        /// Lists the repositories without tags so they can be deleted, ignored for viewers
+        #[serde(default)]
+        pub show_empty: bool,
+        /// Exact name looked up in the registry, the catalog may be cut by `MAX_CATALOG_SIZE`
+        pub repository: Option<String>,
+        /// Lists the catalog from the registry instead of the snapshot, set by the retry button
+        #[serde(default, deserialize_with = "crate::common::dto::deserialize_flag")]
+        pub refresh: bool,
+        /// Walks the catalog with the registry cursor from the repository after this one instead
+        /// of paginating it here, empty for the first page
+        pub last: Option<String>,
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ImageSort {
+        /// Catalog order
+        #[default]
+        Name,
+        /// Most pulled first, needs a `REGISTRY_FLAVOR` exposing pull counts
+        Pulls,
+    }
+
+    impl ImageSort {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Name => "name",
+                Self::Pulls => "pulls",
+            }
+        }
+    }
+
+    /// Whole catalog with its tag counts, refreshed in the background when `HOME_REFRESH_SECS`
+    /// is set
+    #[derive(Clone)]
+    pub struct CatalogSnapshot {
+        pub images: Vec<Image>,
+        pub taken_at: chrono::DateTime<chrono::Utc>,
+    }
+
+    /// Toggles of the catalog page, `None` when there is nothing to reveal or nobody allowed to
+    #[derive(Clone, Copy, Hash)]
+    pub struct CatalogToggles {
+        /// Repositories matching `HIDDEN_REPOSITORIES`
+        pub hidden: Option<bool>,
+        /// Repositories without tags, e.g. after deleting all their tags
+        pub empty: Option<bool>,
+    }
+
+    /// Outcome of deleting a repository, only empty ones are deleted
+    pub enum RepositoryDeletion {
+        Deleted,
+        NotEmpty(usize),
+        /// Harbor keeps untagged artifacts, deleting the repository would delete them too
+        UntaggedArtifacts(u64),
+        /// `REGISTRY_FLAVOR` has no API to delete repositories
+        Unsupported,
+    }
+
+    /// What deleting every tag of an image involves, shown before confirming
+    pub struct ImageDeletionPlan {
+        pub tag_count: usize,
+        /// Distinct manifests, tags sharing a manifest are removed by a single deletion
+        pub manifest_count: usize,
+    }
+
+    pub struct ImageDeletionSummary {
+        pub deleted_tags: Vec<String>,
+        pub deleted_manifests: usize,
+        /// Tags whose manifest could not be resolved or deleted
+        pub failed_tags: Vec<String>,
+        /// Tags kept because they share a manifest with a protected tag
+        pub protected_tags: Vec<String>,
+        /// One entry per manifest, in deletion order
+        pub manifests: Vec<ManifestDeletion>,
+    }
+
+    /// Outcome of the deletion of one manifest and of the tags it removes
+    pub struct ManifestDeletion {
+        pub digest: String,
+        pub tags: Vec<String>,
+        pub deleted: bool,
+    }
+
+    /// Deletion of every tag of an image running in the background, polled by its result page
+    pub struct ImageDeletionJob {
+        pub image_name: String,
+        /// Manifests to delete
+        pub total: usize,
+        /// Manifests whose deletion is over, successful or not
+        pub processed: usize,
+        /// Filled once every batch is done
+        pub summary: Option<ImageDeletionSummary>,
     }
 }
 
 pub mod handler {
+    use std::convert::Infallible;
+
     use axum::{
-        extract::{Path, State},
-        response::Redirect,
+        extract::{Path, Query, RawQuery, State},
+        http::{HeaderMap, StatusCode, header},
+        response::{
+            IntoResponse, Redirect, Response,
+            sse::{Event, KeepAlive, Sse},
+        },
     };
-    use maud::Markup;
+    use axum_extra::extract::CookieJar;
+    use futures::Stream;
+    use maud::{Markup, html};
+    use tokio::sync::broadcast::error::RecvError;
 
     use crate::{
         AppState,
-        auth::middleware::Authenticated,
-        home::{service, view},
+        audit::{self, dto::Deletion},
+        auth::{
+            dto::Role,
+            middleware::{Authenticated, RequireAdmin},
+        },
+        common::{
+            self,
+            dto::{DeleteAction, DryRunQuery, Flash},
+            handler::PaginationQuery,
+            service::{Paginated, density::CookieJarDensityExt, env, flash::CookieJarFlashExt},
+        },
+        error::service::Error,
+        home::{
+            dto::{CatalogToggles, ImageListQuery, RepositoryDeletion},
+            service, view,
+        },
     };
 
     pub async fn index(
+        Query(pagination): Query<PaginationQuery>,
+        Query(ImageListQuery {
+            sort,
+            show_hidden,
+            show_empty,
+            repository,
+            refresh,
+            last,
+        }): Query<ImageListQuery>,
         State(AppState {
             registry_api_client,
+            last_catalog_fetch,
+            catalog_snapshot,
+            deletions_enabled,
             ..
         }): State<AppState>,
-        _: Authenticated,
-    ) -> Result<Markup, Redirect> {
-        let Ok(images) = service::get_images(registry_api_client).await else {
-            return Ok(view::index(view::error("Could not retrieve images")));
+        Authenticated(role): Authenticated,
+        headers: HeaderMap,
+        RawQuery(query): RawQuery,
+        cookie_jar: CookieJar,
+    ) -> Response {
+        let density = cookie_jar.table_density();
+        let (cookie_jar, flash) = cookie_jar.take_flash();
+        if let Some(repository) = repository
+            .as_deref()
+            .map(|repository| repository.trim().trim_matches('/'))
+            .filter(|repository| !repository.is_empty())
+        {
+            let (status, message) =
+                match service::repository_exists(&registry_api_client, repository).await {
+                    Ok(true) => return Redirect::to(&format!("/{repository}")).into_response(),
+                    Ok(false) => (
+                        StatusCode::NOT_FOUND,
+                        format!("No repository named {repository}"),
+                    ),
+                    Err(Error::Timeout(operation)) => (
+                        StatusCode::GATEWAY_TIMEOUT,
+                        format!("{} timed out", operation.label()),
+                    ),
+                    Err(Error::Unreachable(reason)) => (
+                        StatusCode::BAD_GATEWAY,
+                        format!("Could not reach the registry: {reason}"),
+                    ),
+                    Err(_) => (
+                        StatusCode::BAD_GATEWAY,
+                        format!("Could not look up {repository}"),
+                    ),
+                };
+            return (
+                status,
+                cookie_jar,
+                view::index(
+                    role,
+                    html! {
+                        (view::error(&message))
+                        (view::repository_search(Some(repository)))
+                    },
+                    flash,
+                    false,
+                ),
+            )
+                .into_response();
+        }
+        let delete_action = DeleteAction::new(deletions_enabled, role);
+        let walking_catalog = last.is_some();
+        let toggles = CatalogToggles {
+            hidden: (role == Role::Admin && !env::hidden_repositories().is_empty())
+                .then_some(show_hidden),
+            empty: (role == Role::Admin).then_some(show_empty),
+        };
+        let show_hidden = toggles.hidden == Some(true);
+        let show_empty = toggles.empty == Some(true);
+        let images = match last {
+            Some(last) => {
+                let count = pagination.size.unwrap_or_else(env::default_page_size);
+                service::get_images_after(
+                    &registry_api_client,
+                    &last,
+                    count,
+                    show_hidden,
+                    show_empty,
+                )
+                .await
+                .map(|(images, next)| (Paginated::whole(images), next, None))
+            }
+            None if refresh => service::get_images(
+                registry_api_client.clone(),
+                pagination,
+                sort,
+                show_hidden,
+                show_empty,
+            )
+            .await
+            .map(|images| (images, None, None)),
+            None if env::home_refresh_secs().is_some() => service::get_snapshot_images(
+                &registry_api_client,
+                &catalog_snapshot,
+                &last_catalog_fetch,
+                pagination,
+                sort,
+                show_hidden,
+                show_empty,
+            )
+            .await
+            .map(|(images, taken_at)| (images, None, Some(taken_at))),
+            None => match service::take_warm_snapshot(&catalog_snapshot) {
+                Some(snapshot) => {
+                    service::snapshot_page(snapshot, pagination, sort, show_hidden, show_empty)
+                        .map(|(images, taken_at)| (images, None, Some(taken_at)))
+                }
+                None => service::get_images(
+                    registry_api_client.clone(),
+                    pagination,
+                    sort,
+                    show_hidden,
+                    show_empty,
+                )
+                .await
+                .map(|images| (images, None, None)),
+            },
+        };
+        let (images, next_cursor, snapshot_taken_at) = match images {
+            Ok(images) => images,
+            Err(err) => {
+                return (
+                    cookie_jar,
+                    view::index(
+                        role,
+                        view::catalog_error(&err, query.as_deref()),
+                        flash,
+                        false,
+                    ),
+                )
+                    .into_response();
+            }
+        };
+        if snapshot_taken_at.is_none()
+            && let Ok(mut last_catalog_fetch) = last_catalog_fetch.write()
+        {
+            *last_catalog_fetch = Some(chrono::Utc::now());
+        }
+        // Reachable but empty, as opposed to the end of a catalog walk
+        let live = snapshot_taken_at.is_some();
+        let catalog_unsupported = registry_api_client.catalog_unsupported();
+        if images.total_element_count == 0 && !walking_catalog {
+            let content = if catalog_unsupported {
+                view::catalog_unsupported()
+            } else {
+                view::empty_catalog()
+            };
+            return (cookie_jar, view::index(role, content, flash, live)).into_response();
+        }
+        let table = view::image_table(
+            &images,
+            next_cursor.as_deref(),
+            snapshot_taken_at,
+            sort,
+            toggles,
+            density,
+            delete_action,
+        );
+        let table = match registry_api_client.catalog_truncated() {
+            _ if catalog_unsupported => html! {
+                (view::catalog_unsupported())
+                (table)
+            },
+            Some(max_catalog_size) if !walking_catalog => html! {
+                (view::truncated_catalog(max_catalog_size))
+                (table)
+            },
+            _ => table,
+        };
+
+        // A page carrying a flash is shown once and must not be cached
+        if flash.is_some() {
+            return (cookie_jar, view::index(role, table, flash, live)).into_response();
+        }
+        let etag = service::catalog_etag(
+            &images,
+            next_cursor.as_deref(),
+            snapshot_taken_at,
+            sort,
+            toggles,
+            density,
+            delete_action,
+        );
+        let cache_headers = [
+            (header::ETAG, etag.clone()),
+            (header::CACHE_CONTROL, "private, no-cache".to_owned()),
+            (header::VARY, "Accept-Language, Cookie".to_owned()),
+        ];
+        let not_modified = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.split(',').any(|candidate| candidate.trim() == etag));
+        if not_modified {
+            return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+        }
+        (
+            cookie_jar,
+            cache_headers,
+            view::index(role, table, None, live),
+        )
+            .into_response()
+    }
+
+    /// Rebuilds the catalog snapshot right away instead of waiting for the next refresh
+    pub async fn refresh_catalog(
+        State(AppState {
+            registry_api_client,
+            last_catalog_fetch,
+            catalog_snapshot,
+            ..
+        }): State<AppState>,
+        _: RequireAdmin,
+        cookie_jar: CookieJar,
+    ) -> (CookieJar, Redirect) {
+        let cookie_jar = match service::refresh_catalog_snapshot(
+            &registry_api_client,
+            &catalog_snapshot,
+            &last_catalog_fetch,
+        )
+        .await
+        {
+            Ok(_) => cookie_jar,
+            Err(_) => cookie_jar.set_flash(&Flash::danger("Could not refresh the catalog")),
         };
-        Ok(view::index(view::image_table(images)))
+        (cookie_jar, Redirect::to("/"))
+    }
+
+    /// Server-sent `catalog` events, one per background refresh that changed the catalog. The
+    /// stream ends when the explorer shuts down
+    pub async fn events(
+        State(AppState {
+            catalog_events,
+            shutdown,
+            ..
+        }): State<AppState>,
+        _: Authenticated,
+    ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        let receiver = catalog_events.subscribe();
+        let stream = futures::stream::unfold(
+            (receiver, shutdown),
+            |(mut receiver, mut shutdown)| async move {
+                let event = tokio::select! {
+                    event = receiver.recv() => event,
+                    _ = shutdown.wait_for(|shutting_down| *shutting_down) => return None,
+                };
+                match event {
+                    // A lagging page only needs to know the catalog changed
+                    Ok(()) | Err(RecvError::Lagged(_)) => Some((
+                        Ok(Event::default().event("catalog").data("changed")),
+                        (receiver, shutdown),
+                    )),
+                    Err(RecvError::Closed) => None,
+                }
+            },
+        );
+        Sse::new(stream).keep_alive(KeepAlive::default())
+    }
+
+    pub async fn confirm_delete_all_image_tags(
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        _: RequireAdmin,
+        Path(image_name): Path<String>,
+    ) -> Response {
+        service::plan_image_deletion(&registry_api_client, &image_name)
+            .await
+            .map_or_else(
+                |err| {
+                    common::handler::service_error(
+                        Role::Admin,
+                        &err,
+                        &format!("Could not list the tags of {image_name}."),
+                    )
+                },
+                |plan| view::confirm_delete_all_image_tags(&image_name, &plan).into_response(),
+            )
     }
 
     pub async fn delete_all_image_tags(
         State(AppState {
             registry_api_client,
+            catalog_snapshot,
+            deletion_jobs,
             ..
         }): State<AppState>,
-        _: Authenticated,
+        RequireAdmin(username): RequireAdmin,
+        cookie_jar: CookieJar,
+        Path(image_name): Path<String>,
+        Query(DryRunQuery { dry_run }): Query<DryRunQuery>,
+    ) -> Response {
+        if dry_run {
+            return match service::resolve_tag_digests(&registry_api_client, &image_name).await {
+                Ok((tag_digests, _)) => common::view::dry_run(
+                    Role::Admin,
+                    &format!("Delete all tags of {image_name}"),
+                    "/",
+                    &service::deletable_manifests(&tag_digests),
+                )
+                .into_response(),
+                Err(err) => common::handler::service_error(
+                    Role::Admin,
+                    &err,
+                    &format!("Could not list the tags of {image_name}."),
+                ),
+            };
+        }
+        let flash = match service::start_image_deletion(
+            &registry_api_client,
+            &catalog_snapshot,
+            &deletion_jobs,
+            username,
+            &image_name,
+        )
+        .await
+        {
+            Ok(job) => {
+                return Redirect::to(&format!("/{image_name}/deletions/{job}")).into_response();
+            }
+            Err(Error::ReadOnly) => Flash::danger("The explorer is in read-only mode"),
+            Err(
+                Error::Unknown
+                | Error::Protected
+                | Error::Unreachable(_)
+                | Error::Unauthorized(_)
+                | Error::Timeout(_),
+            ) => Flash::danger(format!("Could not list the tags of {image_name}")),
+        };
+        (cookie_jar.set_flash(&flash), Redirect::to("/")).into_response()
+    }
+
+    /// Progress of a deletion started by `delete_all_image_tags`, then its outcome. Reloading it
+    /// never deletes anything again
+    pub async fn image_deletion(
+        State(AppState { deletion_jobs, .. }): State<AppState>,
+        _: RequireAdmin,
+        Path((image_name, job)): Path<(String, u64)>,
+    ) -> Response {
+        let page = deletion_jobs.read().ok().and_then(|jobs| {
+            jobs.get(&job)
+                .filter(|job| job.image_name == image_name)
+                .map(view::image_deletion)
+        });
+        match page {
+            Some(page) => page.into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                common::view::error_page(
+                    Some(Role::Admin),
+                    StatusCode::NOT_FOUND,
+                    "This deletion is unknown, only the most recent ones are kept",
+                ),
+            )
+                .into_response(),
+        }
+    }
+
+    pub async fn confirm_delete_repository(
+        _: RequireAdmin,
+        Path(image_name): Path<String>,
+    ) -> Markup {
+        view::confirm_delete_repository(&image_name)
+    }
+
+    pub async fn delete_repository(
+        State(AppState {
+            registry_api_client,
+            catalog_snapshot,
+            ..
+        }): State<AppState>,
+        RequireAdmin(username): RequireAdmin,
+        cookie_jar: CookieJar,
         Path(image_name): Path<String>,
-    ) -> Redirect {
-        let _ = service::delete_all_image_tags(registry_api_client, &image_name).await;
-        Redirect::to("/")
+    ) -> (CookieJar, Redirect) {
+        let result = service::delete_empty_repository(&registry_api_client, &image_name).await;
+        if !matches!(
+            result,
+            Ok(RepositoryDeletion::Unsupported) | Err(Error::ReadOnly)
+        ) {
+            audit::service::record(
+                username,
+                Deletion::Repository,
+                &image_name,
+                None,
+                Vec::new(),
+                Vec::new(),
+                matches!(result, Ok(RepositoryDeletion::Deleted)),
+            )
+            .await;
+        }
+        let flash = match result {
+            Ok(RepositoryDeletion::Deleted) => {
+                service::remove_snapshot_image(&catalog_snapshot, &image_name);
+                Flash::success(format!("Deleted the repository {image_name}"))
+            }
+            Ok(RepositoryDeletion::NotEmpty(tag_count)) => Flash::warning(format!(
+                "{image_name} still has {tag_count} tag(s), delete them first"
+            )),
+            Ok(RepositoryDeletion::UntaggedArtifacts(artifact_count)) => Flash::warning(format!(
+                "{image_name} still has {artifact_count} untagged artifact(s), delete them in Harbor first"
+            )),
+            Ok(RepositoryDeletion::Unsupported) => Flash::warning(format!(
+                "{} registries cannot delete repositories",
+                env::registry_flavor().as_str()
+            )),
+            Err(Error::ReadOnly) => Flash::danger("The explorer is in read-only mode"),
+            Err(
+                Error::Unknown
+                | Error::Protected
+                | Error::Unreachable(_)
+                | Error::Unauthorized(_)
+                | Error::Timeout(_),
+            ) => Flash::danger(format!("Could not delete the repository {image_name}")),
+        };
+        (
+            cookie_jar.set_flash(&flash),
+            Redirect::to("/?show_empty=true"),
+        )
     }
 }
 
 pub mod service {
+    use std::{
+        cmp::Reverse,
+        collections::{BTreeMap, HashMap, HashSet},
+        hash::{DefaultHasher, Hash, Hasher},
+        sync::{
+            Arc, RwLock,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::Duration,
+    };
+
+    use anyhow::Context;
     use itertools::Itertools;
     use joy_error::log::ResultLogExt;
 
     use crate::{
-        error::service::ServiceResult,
-        home::dto::Image,
-        registry::{self, dto::TagManifest},
+        audit::{self, dto::Deletion},
+        common::{
+            self,
+            dto::{DeleteAction, TableDensity},
+            handler::PaginationQuery,
+            service::{Paginated, env},
+        },
+        error::service::{Error, Operation, ServiceResult},
+        home::dto::{
+            CatalogSnapshot, CatalogToggles, Image, ImageDeletionJob, ImageDeletionPlan,
+            ImageDeletionSummary, ImageSort, ManifestDeletion, RepositoryDeletion,
+        },
+        i18n,
+        metrics::service::RegistryCallExt,
+        registry,
     };
 
-    pub async fn delete_all_image_tags(
-        registry_api_client: registry::api::Client,
+    pub type SharedCatalogSnapshot = Arc<RwLock<Option<CatalogSnapshot>>>;
+
+    pub type LastCatalogFetch = Arc<RwLock<Option<chrono::DateTime<chrono::Utc>>>>;
+
+    /// Notified when a background refresh finds a catalog different from the previous snapshot
+    pub type CatalogEvents = tokio::sync::broadcast::Sender<()>;
+
+    /// Deletions of every tag of an image by id, the oldest are dropped past `MAX_DELETION_JOBS`
+    pub type DeletionJobs = Arc<RwLock<BTreeMap<u64, ImageDeletionJob>>>;
+
+    const MAX_DELETION_JOBS: usize = 32;
+
+    static NEXT_DELETION_JOB: AtomicU64 = AtomicU64::new(1);
+
+    /// Bumped by each deletion patching a snapshot, so a refresh counting the tags meanwhile
+    /// does not store the counts from before the deletion
+    static SNAPSHOT_PATCHES: AtomicU64 = AtomicU64::new(0);
+
+    /// Refreshes racing deletions start over this many times before giving up on storing
+    const MAX_SNAPSHOT_REFRESHES: usize = 3;
+
+    /// Resolves the manifest of every tag of `image_name`, tags that cannot be resolved are
+    /// returned apart
+    pub async fn resolve_tag_digests(
+        registry_api_client: &registry::api::Client,
         image_name: &str,
-    ) -> ServiceResult<()> {
+    ) -> ServiceResult<(Vec<(String, String)>, Vec<String>)> {
         let tags = registry_api_client
             .tags(image_name)
+            .counted()
             .await
+            .with_context(|| format!("could not list the tags of {image_name}"))
             .error()
-            .log_err()?;
-        if let Some(tags) = tags.tags {
-            let digests = futures::future::join_all(
-                tags.iter()
-                    .map(|tag| registry_api_client.manifest(image_name, tag)),
-            )
-            .await
-            .into_iter()
-            .collect::<Result<Vec<TagManifest>, _>>()?
+            .log_err()?
+            .tags
+            .unwrap_or_default();
+        let manifests = futures::future::join_all(
+            tags.iter()
+                .map(|tag| registry_api_client.manifest(image_name, tag).counted()),
+        )
+        .await;
+
+        let mut tag_digests = Vec::new();
+        let mut unresolved_tags = Vec::new();
+        for (tag, manifest) in tags.into_iter().zip(manifests) {
+            match manifest
+                .with_context(|| format!("could not fetch the manifest of {image_name}:{tag}"))
+                .error()
+                .log_err()
+            {
+                Ok(manifest) => tag_digests.push((tag, manifest.digest().to_owned())),
+                Err(_) => unresolved_tags.push(tag),
+            }
+        }
+        Ok((tag_digests, unresolved_tags))
+    }
+
+    pub async fn plan_image_deletion(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<ImageDeletionPlan> {
+        let (tag_digests, unresolved_tags) =
+            resolve_tag_digests(registry_api_client, image_name).await?;
+        Ok(ImageDeletionPlan {
+            tag_count: tag_digests.len() + unresolved_tags.len(),
+            manifest_count: tag_digests
+                .iter()
+                .map(|(_, digest)| digest)
+                .unique()
+                .count(),
+        })
+    }
+
+    /// Manifests referenced by a tag matching `PROTECTED_TAGS`
+    fn protected_digests(tag_digests: &[(String, String)]) -> HashSet<String> {
+        tag_digests
+            .iter()
+            .filter(|(tag, _)| common::service::is_protected_tag(tag))
+            .map(|(_, digest)| digest.clone())
+            .collect()
+    }
+
+    /// Manifests removed by deleting every tag, with their tags, those shared with a protected
+    /// tag are left out
+    pub fn deletable_manifests(tag_digests: &[(String, String)]) -> Vec<(String, Vec<String>)> {
+        let protected_digests = protected_digests(tag_digests);
+        tag_digests
+            .iter()
+            .filter(|(_, digest)| !protected_digests.contains(digest))
+            .map(|(tag, digest)| (digest.clone(), tag.clone()))
+            .into_group_map()
             .into_iter()
-            .map(|m| m.digest().to_owned())
-            .unique();
-            for digest in digests {
+            .sorted()
+            .collect()
+    }
+
+    /// Resolves the tags of `image_name` then deletes their manifests in the background, the
+    /// returned id follows the progress in `deletion_jobs`. The outcome is audited once over, or
+    /// right away when the tags cannot be resolved
+    pub async fn start_image_deletion(
+        registry_api_client: &registry::api::Client,
+        catalog_snapshot: &SharedCatalogSnapshot,
+        deletion_jobs: &DeletionJobs,
+        username: &'static str,
+        image_name: &str,
+    ) -> ServiceResult<u64> {
+        if env::read_only() {
+            return Err(Error::ReadOnly);
+        }
+        let (tag_digests, failed_tags) =
+            match resolve_tag_digests(registry_api_client, image_name).await {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    audit::service::record(
+                        username,
+                        Deletion::AllTags,
+                        image_name,
+                        None,
+                        Vec::new(),
+                        Vec::new(),
+                        false,
+                    )
+                    .await;
+                    return Err(err);
+                }
+            };
+        let id = NEXT_DELETION_JOB.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut jobs) = deletion_jobs.write() {
+            while jobs.len() >= MAX_DELETION_JOBS {
+                jobs.pop_first();
+            }
+            jobs.insert(
+                id,
+                ImageDeletionJob {
+                    image_name: image_name.to_owned(),
+                    total: deletable_manifests(&tag_digests).len(),
+                    processed: 0,
+                    summary: None,
+                },
+            );
+        }
+
+        let registry_api_client = registry_api_client.clone();
+        let catalog_snapshot = catalog_snapshot.clone();
+        let deletion_jobs = deletion_jobs.clone();
+        let image_name = image_name.to_owned();
+        tokio::spawn(async move {
+            let summary = delete_all_image_tags(
+                &registry_api_client,
+                &image_name,
+                tag_digests,
+                failed_tags,
+                |processed| {
+                    if let Ok(mut jobs) = deletion_jobs.write()
+                        && let Some(job) = jobs.get_mut(&id)
+                    {
+                        job.processed = processed;
+                    }
+                },
+            )
+            .await;
+            audit::service::record(
+                username,
+                Deletion::AllTags,
+                &image_name,
+                None,
+                summary.deleted_tags.clone(),
+                Vec::new(),
+                summary.failed_tags.is_empty(),
+            )
+            .await;
+            patch_snapshot_tag_count(&catalog_snapshot, &image_name, summary.deleted_tags.len());
+            if let Ok(mut jobs) = deletion_jobs.write()
+                && let Some(job) = jobs.get_mut(&id)
+            {
+                job.summary = Some(summary);
+            }
+        });
+        Ok(id)
+    }
+
+    /// Deletes every manifest referenced by a tag of `image_name`, each manifest once and
+    /// `DELETE_BATCH_SIZE` at a time. `on_batch` is told how many are processed after each batch
+    ///
+    /// A failed deletion does not stop the others, the tags it concerns are reported in the
+    /// summary along with `failed_tags`, those that could not be resolved
+    async fn delete_all_image_tags(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        tag_digests: Vec<(String, String)>,
+        mut failed_tags: Vec<String>,
+        on_batch: impl Fn(usize),
+    ) -> ImageDeletionSummary {
+        let protected_digests = protected_digests(&tag_digests);
+        let deletable_manifests = deletable_manifests(&tag_digests);
+        let total = deletable_manifests.len();
+        let mut manifests = Vec::with_capacity(total);
+        for batch in deletable_manifests.chunks(env::delete_batch_size()) {
+            let deleted = futures::future::join_all(batch.iter().map(|(digest, _)| async move {
                 registry_api_client
-                    .delete_tag(image_name, &digest)
+                    .delete_tag(image_name, digest)
+                    .counted()
                     .await
+                    .with_context(|| format!("could not delete {image_name}@{digest}"))
                     .error()
-                    .log_err()?;
+                    .log_err()
+                    .is_ok()
+            }))
+            .await;
+            manifests.extend(batch.iter().cloned().zip(deleted).map(
+                |((digest, tags), deleted)| ManifestDeletion {
+                    digest,
+                    tags,
+                    deleted,
+                },
+            ));
+            tracing::info!(
+                "Deleting all tags of {image_name}: {}/{total} manifest(s) processed",
+                manifests.len()
+            );
+            on_batch(manifests.len());
+        }
+        let failed_digests = manifests
+            .iter()
+            .filter(|manifest| !manifest.deleted)
+            .map(|manifest| manifest.digest.as_str())
+            .collect::<HashSet<_>>();
+        let deleted_manifests = manifests.len() - failed_digests.len();
+
+        let mut deleted_tags = Vec::new();
+        let mut protected_tags = Vec::new();
+        for (tag, digest) in tag_digests {
+            if protected_digests.contains(&digest) {
+                protected_tags.push(tag);
+            } else if failed_digests.contains(digest.as_str()) {
+                failed_tags.push(tag);
+            } else {
+                deleted_tags.push(tag);
             }
         }
-        Ok(())
+
+        ImageDeletionSummary {
+            deleted_tags,
+            deleted_manifests,
+            failed_tags,
+            protected_tags,
+            manifests,
+        }
     }
 
-    #[tracing::instrument]
-    pub async fn get_images(
-        registry_api_client: registry::api::Client,
+    /// Weak ETag of a rendered catalog page, any push or deletion changes a tag count and
+    /// therefore the tag
+    pub fn catalog_etag(
+        images: &Paginated<Image>,
+        next_cursor: Option<&str>,
+        snapshot_taken_at: Option<chrono::DateTime<chrono::Utc>>,
+        sort: ImageSort,
+        toggles: CatalogToggles,
+        density: TableDensity,
+        delete_action: DeleteAction,
+    ) -> String {
+        let mut hasher = DefaultHasher::new();
+        images.page.hash(&mut hasher);
+        images.size.hash(&mut hasher);
+        images.total_element_count.hash(&mut hasher);
+        for image in images.iter() {
+            image.name.hash(&mut hasher);
+            image.tag_count.hash(&mut hasher);
+            image.pull_count.hash(&mut hasher);
+        }
+        sort.hash(&mut hasher);
+        toggles.hash(&mut hasher);
+        density.hash(&mut hasher);
+        next_cursor.hash(&mut hasher);
+        // The page shows the snapshot age with a minute precision
+        snapshot_taken_at
+            .map(|taken_at| (taken_at, (chrono::Utc::now() - taken_at).num_minutes()))
+            .hash(&mut hasher);
+        delete_action.hash(&mut hasher);
+        // Each registry has its own router but they share the cookie jar and thus the cache
+        env::registry_name().hash(&mut hasher);
+        // The same counts are rendered differently in each language
+        i18n::service::locale().hash(&mut hasher);
+        format!("W/\"{:x}\"", hasher.finish())
+    }
+
+    /// Up to `count` images after `last` in the catalog and the cursor of the following page,
+    /// only that page is fetched from the registry
+    pub async fn get_images_after(
+        registry_api_client: &registry::api::Client,
+        last: &str,
+        count: usize,
+        show_hidden: bool,
+        show_empty: bool,
+    ) -> ServiceResult<(Vec<Image>, Option<String>)> {
+        let page = registry_api_client
+            .catalog_page(count, Some(last).filter(|last| !last.is_empty()))
+            .counted()
+            .await
+            .with_context(|| format!("could not fetch the catalog page after {last}"))
+            .error()
+            .log_err()
+            .map_err(|err| Error::registry(Operation::Catalog, &err))?;
+        let pull_counts = pull_counts(registry_api_client).await;
+        // The cursor stays the last repository of the registry page, hidden or not
+        let images = futures::future::join_all(
+            page.repositories
+                .into_iter()
+                .filter(|image| show_hidden || !common::service::is_hidden_repository(image))
+                .map(|image| async {
+                    let tag_count = registry_api_client
+                        .count_tags(&image)
+                        .counted()
+                        .await
+                        .with_context(|| format!("could not count the tags of {image}"))?;
+                    anyhow::Ok(Image {
+                        pull_count: pull_counts.get(&image).copied(),
+                        name: image,
+                        tag_count,
+                    })
+                }),
+        )
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .error()
+        .log_err()?
+        .into_iter()
+        .filter(|image| show_empty || image.tag_count > 0)
+        .collect();
+        Ok((images, page.next))
+    }
+
+    /// Counts the tags of every repository of the catalog and replaces the snapshot. A deletion
+    /// patching the snapshot during the count makes it start over, the snapshot is left as is
+    /// when deletions keep racing it
+    pub async fn refresh_catalog_snapshot(
+        registry_api_client: &registry::api::Client,
+        catalog_snapshot: &SharedCatalogSnapshot,
+        last_catalog_fetch: &LastCatalogFetch,
+    ) -> ServiceResult<CatalogSnapshot> {
+        let mut attempt = 1;
+        loop {
+            let patches = SNAPSHOT_PATCHES.load(Ordering::SeqCst);
+            let snapshot = CatalogSnapshot {
+                images: count_catalog(registry_api_client).await?,
+                taken_at: chrono::Utc::now(),
+            };
+            // Patches bump the counter while holding the lock, the check and the write cannot
+            // be split by one
+            let stored = catalog_snapshot.write().is_ok_and(|mut catalog_snapshot| {
+                let stored = SNAPSHOT_PATCHES.load(Ordering::SeqCst) == patches;
+                if stored {
+                    *catalog_snapshot = Some(snapshot.clone());
+                }
+                stored
+            });
+            if stored {
+                if let Ok(mut last_catalog_fetch) = last_catalog_fetch.write() {
+                    *last_catalog_fetch = Some(snapshot.taken_at);
+                }
+                return Ok(snapshot);
+            }
+            if attempt == MAX_SNAPSHOT_REFRESHES {
+                tracing::debug!("Catalog snapshot not stored, deletions kept patching it");
+                return Ok(snapshot);
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Every repository of the catalog with its tag count
+    async fn count_catalog(
+        registry_api_client: &registry::api::Client,
     ) -> ServiceResult<Vec<Image>> {
-        let images = registry_api_client
+        let names = registry_api_client
             .catalog()
+            .counted()
             .await
+            .context("could not fetch the catalog")
             .error()
-            .log_err()?
+            .log_err()
+            .map_err(|err| Error::registry(Operation::Catalog, &err))?
             .repositories;
-
-        let tag_counts = futures::future::join_all(
-            images
-                .iter()
-                .map(|image| registry_api_client.count_tags(image)),
-        )
+        let pull_counts = pull_counts(registry_api_client).await;
+        let images = futures::future::join_all(names.into_iter().map(|image| async {
+            let tag_count = registry_api_client
+                .count_tags(&image)
+                .counted()
+                .await
+                .with_context(|| format!("could not count the tags of {image}"))?;
+            anyhow::Ok(Image {
+                pull_count: pull_counts.get(&image).copied(),
+                name: image,
+                tag_count,
+            })
+        }))
         .await
         .into_iter()
         .collect::<anyhow::Result<Vec<_>>>()
         .error()
         .log_err()?;
+        Ok(images)
+    }
 
-        let images = images
-            .into_iter()
-            .zip(tag_counts)
-            .map(|(image, tag_count)| Image {
+    pub async fn repository_exists(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<bool> {
+        registry_api_client
+            .repository_exists(image_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not look up the repository {image_name}"))
+            .error()
+            .log_err()
+            .map_err(|err| Error::registry(Operation::Catalog, &err))
+    }
+
+    /// Fills the snapshot once without holding up the caller, the registry calls share the
+    /// client concurrency limit with the page loads
+    pub fn spawn_cache_warming(
+        registry_api_client: registry::api::Client,
+        catalog_snapshot: SharedCatalogSnapshot,
+        last_catalog_fetch: LastCatalogFetch,
+    ) {
+        tokio::spawn(async move {
+            let started_at = std::time::Instant::now();
+            if let Ok(snapshot) = refresh_catalog_snapshot(
+                &registry_api_client,
+                &catalog_snapshot,
+                &last_catalog_fetch,
+            )
+            .await
+            {
+                tracing::info!(
+                    "Cache warmed with {} images in {:.1?}",
+                    snapshot.images.len(),
+                    started_at.elapsed()
+                );
+            }
+        });
+    }
+
+    /// The snapshot warmed at startup when no background refresh keeps it up to date, it is
+    /// handed out once so later loads are not served a stale catalog
+    pub fn take_warm_snapshot(catalog_snapshot: &SharedCatalogSnapshot) -> Option<CatalogSnapshot> {
+        if env::home_refresh_secs().is_some() {
+            return None;
+        }
+        catalog_snapshot.write().ok()?.take()
+    }
+
+    /// Refreshes the snapshot every `period`, starting right away, a failed refresh keeps the
+    /// previous snapshot. A refresh that changes an image or a tag count is sent to
+    /// `catalog_events`
+    pub fn spawn_catalog_refresh(
+        registry_api_client: registry::api::Client,
+        catalog_snapshot: SharedCatalogSnapshot,
+        last_catalog_fetch: LastCatalogFetch,
+        catalog_events: CatalogEvents,
+        period: Duration,
+    ) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let previous_images = catalog_snapshot
+                    .read()
+                    .ok()
+                    .and_then(|snapshot| snapshot.as_ref().map(|snapshot| snapshot.images.clone()));
+                if let Ok(snapshot) = refresh_catalog_snapshot(
+                    &registry_api_client,
+                    &catalog_snapshot,
+                    &last_catalog_fetch,
+                )
+                .await
+                    && previous_images.is_some_and(|previous| {
+                        // Pull counts change all the time, they wait for the next page load
+                        previous
+                            .iter()
+                            .map(|image| (&image.name, image.tag_count))
+                            .ne(snapshot
+                                .images
+                                .iter()
+                                .map(|image| (&image.name, image.tag_count)))
+                    })
+                {
+                    // Fails only when no page is listening
+                    let _ = catalog_events.send(());
+                }
+            }
+        });
+    }
+
+    /// One page of the snapshot, built on the spot when the first background refresh has not
+    /// completed yet
+    pub async fn get_snapshot_images(
+        registry_api_client: &registry::api::Client,
+        catalog_snapshot: &SharedCatalogSnapshot,
+        last_catalog_fetch: &LastCatalogFetch,
+        pagination: PaginationQuery,
+        sort: ImageSort,
+        show_hidden: bool,
+        show_empty: bool,
+    ) -> ServiceResult<(Paginated<Image>, chrono::DateTime<chrono::Utc>)> {
+        let snapshot = catalog_snapshot
+            .read()
+            .ok()
+            .and_then(|snapshot| snapshot.clone());
+        let snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            None => {
+                refresh_catalog_snapshot(registry_api_client, catalog_snapshot, last_catalog_fetch)
+                    .await?
+            }
+        };
+        snapshot_page(snapshot, pagination, sort, show_hidden, show_empty)
+    }
+
+    pub fn snapshot_page(
+        snapshot: CatalogSnapshot,
+        pagination: PaginationQuery,
+        sort: ImageSort,
+        show_hidden: bool,
+        show_empty: bool,
+    ) -> ServiceResult<(Paginated<Image>, chrono::DateTime<chrono::Utc>)> {
+        // The snapshot keeps every repository, it is shared by all requests
+        let mut images = snapshot.images;
+        images.retain(|image| {
+            (show_hidden || !common::service::is_hidden_repository(&image.name))
+                && (show_empty || image.tag_count > 0)
+        });
+        if sort == ImageSort::Pulls {
+            images.sort_by_key(|image| Reverse(image.pull_count));
+        }
+        let images = pagination.into_paginated(env::default_page_size(), &images)?;
+        Ok((images, snapshot.taken_at))
+    }
+
+    /// Deletes `image_name` when it has no tag left, tags pushed since the page was rendered
+    /// keep it
+    pub async fn delete_empty_repository(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<RepositoryDeletion> {
+        if env::read_only() {
+            return Err(Error::ReadOnly);
+        }
+        if !env::registry_flavor().can_delete_repositories() {
+            return Ok(RepositoryDeletion::Unsupported);
+        }
+        let tag_count = registry_api_client
+            .count_tags(image_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not count the tags of {image_name}"))
+            .error()
+            .log_err()?;
+        if tag_count > 0 {
+            return Ok(RepositoryDeletion::NotEmpty(tag_count));
+        }
+        let artifact_count = registry_api_client
+            .artifact_count(image_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not count the artifacts of {image_name}"))
+            .error()
+            .log_err()?;
+        if artifact_count > 0 {
+            return Ok(RepositoryDeletion::UntaggedArtifacts(artifact_count));
+        }
+        registry_api_client
+            .delete_repository(image_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not delete the repository {image_name}"))
+            .error()
+            .log_err()?;
+        Ok(RepositoryDeletion::Deleted)
+    }
+
+    /// Drops a deleted repository from the snapshot until the next refresh
+    pub fn remove_snapshot_image(catalog_snapshot: &SharedCatalogSnapshot, image_name: &str) {
+        if let Ok(mut catalog_snapshot) = catalog_snapshot.write() {
+            SNAPSHOT_PATCHES.fetch_add(1, Ordering::SeqCst);
+            if let Some(snapshot) = catalog_snapshot.as_mut() {
+                snapshot.images.retain(|image| image.name != image_name);
+            }
+        }
+    }
+
+    /// Keeps the snapshot in line with a deletion until the next refresh
+    pub fn patch_snapshot_tag_count(
+        catalog_snapshot: &SharedCatalogSnapshot,
+        image_name: &str,
+        removed_tags: usize,
+    ) {
+        if let Ok(mut catalog_snapshot) = catalog_snapshot.write() {
+            SNAPSHOT_PATCHES.fetch_add(1, Ordering::SeqCst);
+            if let Some(image) = catalog_snapshot
+                .iter_mut()
+                .flat_map(|snapshot| &mut snapshot.images)
+                .find(|image| image.name == image_name)
+            {
+                image.tag_count = image.tag_count.saturating_sub(removed_tags);
+            }
+        }
+    }
+
+    /// Pull counts of the `REGISTRY_FLAVOR` extension API, empty when the registry does not
+    /// expose them after all
+    async fn pull_counts(registry_api_client: &registry::api::Client) -> HashMap<String, u64> {
+        registry_api_client
+            .pull_counts()
+            .counted()
+            .await
+            .unwrap_or_else(|err| {
+                tracing::debug!("Pull counts unavailable: {err}");
+                HashMap::new()
+            })
+    }
+
+    #[tracing::instrument(skip(pagination))]
+    pub async fn get_images(
+        registry_api_client: registry::api::Client,
+        pagination: PaginationQuery,
+        sort: ImageSort,
+        show_hidden: bool,
+        show_empty: bool,
+    ) -> ServiceResult<Paginated<Image>> {
+        let (catalog, pull_counts) = futures::join!(
+            registry_api_client.catalog().counted(),
+            pull_counts(&registry_api_client)
+        );
+        let mut images = catalog
+            .context("could not fetch the catalog")
+            .error()
+            .log_err()
+            .map_err(|err| Error::registry(Operation::Catalog, &err))?
+            .repositories;
+        if !show_hidden {
+            images.retain(|image| !common::service::is_hidden_repository(image));
+        }
+        if sort == ImageSort::Pulls {
+            // Stable, equal counts keep the catalog order
+            images.sort_by_key(|image| Reverse(pull_counts.get(image)));
+        }
+
+        let count_tags = |image: String| async {
+            let tag_count = registry_api_client
+                .count_tags(&image)
+                .counted()
+                .await
+                .with_context(|| format!("could not count the tags of {image}"))?;
+            anyhow::Ok(Image {
+                pull_count: pull_counts.get(&image).copied(),
                 name: image,
                 tag_count,
             })
-            .collect();
+        };
 
-        Ok(images)
+        if show_empty {
+            return Ok(pagination
+                .into_paginated(env::default_page_size(), &images)?
+                .map(count_tags)
+                .into_future()
+                .await
+                .into_result()
+                .error()
+                .log_err()?);
+        }
+        // Empty repositories are left out before paginating, so every page is full
+        let images = futures::future::join_all(images.into_iter().map(count_tags))
+            .await
+            .into_iter()
+            .filter(|image| !image.as_ref().is_ok_and(|image| image.tag_count == 0))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .error()
+            .log_err()?;
+        Ok(pagination.into_paginated(env::default_page_size(), &images)?)
     }
 }
 
 pub mod view {
+    use itertools::Itertools;
     use maud::{Markup, html};
 
-    use crate::{common, home::dto::Image};
+    use crate::{
+        auth::dto::Role,
+        common::{
+            self,
+            dto::{DeleteAction, Flash, TableDensity},
+            service::{Paginated, env},
+        },
+        error::service::Error,
+        home::dto::{
+            CatalogToggles, Image, ImageDeletionJob, ImageDeletionPlan, ImageDeletionSummary,
+            ImageSort,
+        },
+        i18n::service::{t, tf},
+        image,
+    };
 
-    pub fn index(body: Markup) -> Markup {
+    /// `live` pages reload themselves when the background refresh changes the catalog
+    pub fn index(role: Role, body: Markup, flash: Option<Flash>, live: bool) -> Markup {
+        let page = common::view::page().title("Repositories").role(role);
+        let page = if live { page.js("live-refresh") } else { page };
         html! {
-            (common::view::page().content(body).call())
+            (page.maybe_flash(flash).content(html! {
+                (common::view::instance_banner())
+                (body)
+            }).call())
         }
     }
 
+    pub fn confirm_delete_all_image_tags(image_name: &str, plan: &ImageDeletionPlan) -> Markup {
+        common::view::page()
+            .role(Role::Admin)
+            .content(html! {
+                .m-2 {
+                    h1 { "Delete all tags of " (image_name) }
+                    p {
+                        (plan.tag_count) " tag(s) referencing " (plan.manifest_count)
+                        " manifest(s) will be deleted."
+                    }
+                    .alert .alert-warning {
+                        "Storage is only reclaimed once the registry garbage collector has run."
+                    }
+                    form .d-flex .gap-2 method="post" action=(format!("/{image_name}/delete")) {
+                        a .btn .btn-secondary href="/" { "Cancel" }
+                        button .btn .btn-outline-secondary type="submit" formaction=(format!("/{image_name}/delete?dry_run=1")) { "Dry run" }
+                        button .btn .btn-danger type="submit" { "Delete " (plan.tag_count) " tag(s)" }
+                    }
+                }
+            })
+            .call()
+    }
+
+    pub fn confirm_delete_repository(image_name: &str) -> Markup {
+        common::view::page()
+            .role(Role::Admin)
+            .content(html! {
+                .m-2 {
+                    h1 { "Delete the repository " (image_name) }
+                    p {
+                        "The repository is removed from the registry, it is kept if tags were pushed to it meanwhile."
+                    }
+                    form .d-flex .gap-2 method="post" action=(format!("/{image_name}/repository/delete")) {
+                        a .btn .btn-secondary href="/?show_empty=true" { "Cancel" }
+                        button .btn .btn-danger type="submit" { "Delete repository" }
+                    }
+                }
+            })
+            .call()
+    }
+
+    /// Reloads every second until the deletion is over, then shows its outcome
+    pub fn image_deletion(job: &ImageDeletionJob) -> Markup {
+        if let Some(summary) = &job.summary {
+            return delete_all_image_tags_results(&job.image_name, summary);
+        }
+        let percent = (job.processed * 100).checked_div(job.total).unwrap_or(100);
+        common::view::page()
+            .role(Role::Admin)
+            .refresh_secs(1)
+            .content(html! {
+                .m-2 {
+                    h1 { "Delete all tags of " (job.image_name) }
+                    p { (job.processed) " of " (job.total) " manifest(s) processed" }
+                    .progress role="progressbar" aria-label="Deletion progress" aria-valuenow=(job.processed) aria-valuemin="0" aria-valuemax=(job.total) {
+                        .progress-bar .progress-bar-striped .progress-bar-animated style=(format!("width: {percent}%")) {}
+                    }
+                }
+            })
+            .call()
+    }
+
+    /// Outcome of each manifest deletion. Retrying deletes the tags left, manifests already
+    /// deleted have no tag anymore and are skipped
+    fn delete_all_image_tags_results(image_name: &str, summary: &ImageDeletionSummary) -> Markup {
+        let deleted = format!(
+            "Deleted {} tag(s) of {image_name} ({} manifest(s))",
+            summary.deleted_tags.len(),
+            summary.deleted_manifests
+        );
+        let flash = if summary.failed_tags.is_empty() {
+            Flash::success(deleted)
+        } else {
+            Flash::warning(format!(
+                "{deleted}, could not delete: {}",
+                summary.failed_tags.join(", ")
+            ))
+        };
+        common::view::page()
+            .role(Role::Admin)
+            .flash(flash)
+            .content(html! {
+                .m-2 {
+                    h1 { "Delete all tags of " (image_name) }
+                    @if !summary.protected_tags.is_empty() {
+                        .alert .alert-info { "Kept protected: " (summary.protected_tags.join(", ")) }
+                    }
+                    @if !summary.manifests.is_empty() {
+                        table .table .table-striped .table-bordered .align-middle .text-center {
+                            thead {
+                                tr {
+                                    th { "Digest" }
+                                    th { "Tags" }
+                                    th { "Result" }
+                                }
+                            }
+                            tbody {
+                                @for manifest in &summary.manifests {
+                                    tr {
+                                        td { code { (manifest.digest) } }
+                                        td { (manifest.tags.join(", ")) }
+                                        @if manifest.deleted {
+                                            td .text-success { "Deleted" }
+                                        } @else {
+                                            td .text-danger { "Failed" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    .d-flex .gap-2 {
+                        a .btn .btn-secondary href="/" { "Back to the repositories" }
+                        @if !summary.failed_tags.is_empty() {
+                            form .m-0 method="post" action=(format!("/{image_name}/delete")) {
+                                button .btn .btn-danger type="submit" title="Deletes the tags left, including those pushed since" { "Retry" }
+                            }
+                        }
+                    }
+                }
+            })
+            .call()
+    }
+
     pub fn error(message: &str) -> Markup {
         html! {
             div .alert .alert-danger {
@@ -139,34 +1457,247 @@ pub mod view {
         }
     }
 
-    pub fn image_table(images: Vec<Image>) -> Markup {
+    /// Why the catalog could not be listed, with a retry bypassing the snapshot
+    /// The retry link keeps the page, sort and filters of `query`
+    pub fn catalog_error(err: &Error, query: Option<&str>) -> Markup {
+        let (category, message, hint) = match err {
+            Error::Timeout(operation) => (
+                "Timeout",
+                format!("{} timed out", operation.label()),
+                "The registry is slow to answer, retry in a moment.",
+            ),
+            Error::Unreachable(reason) => (
+                "Unreachable",
+                format!("Could not reach the registry: {reason}"),
+                "Check that the registry is up and REGISTRY_HOST points to it.",
+            ),
+            Error::Unauthorized(reason) => (
+                "Authentication",
+                format!("The registry refused the credentials: {reason}"),
+                "Check REGISTRY_USERNAME and REGISTRY_PASSWORD.",
+            ),
+            Error::Unknown | Error::ReadOnly | Error::Protected => (
+                "Error",
+                "Could not retrieve images".to_owned(),
+                "See the explorer logs for details.",
+            ),
+        };
+        let retry_query = query
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some("refresh"))
+            .chain(["refresh=1"])
+            .join("&");
+        html! {
+            div .alert .alert-danger .d-flex .justify-content-between .align-items-center .gap-2 {
+                div {
+                    span .badge .text-bg-danger .me-2 { (category) }
+                    (message)
+                    .small { (hint) }
+                }
+                a .btn .btn-outline-danger href=(format!("/?{retry_query}")) { "Retry" }
+            }
+        }
+    }
+
+    /// Shown above the list when `MAX_CATALOG_SIZE` left repositories out of it
+    pub fn truncated_catalog(max_catalog_size: usize) -> Markup {
+        html! {
+            div .alert .alert-warning .m-2 {
+                p {
+                    "Showing the first " (max_catalog_size) " repositories of a larger catalog, "
+                    "search a repository by name to find the others."
+                }
+                (repository_search(None))
+            }
+        }
+    }
+
+    /// Looks `repository` up in the registry rather than in the listed catalog
+    pub fn repository_search(repository: Option<&str>) -> Markup {
+        html! {
+            form .d-flex .gap-2 method="get" action="/" {
+                input .form-control .form-control-sm style="max-width: 20rem" type="search" name="repository" value=[repository] placeholder="Repository name" aria-label="Repository name" required;
+                button .btn .btn-sm .btn-primary type="submit" { "Search" }
+            }
+        }
+    }
+
+    /// Shown instead of the empty catalog or above `KNOWN_REPOSITORIES` when the registry
+    /// refuses `_catalog`
+    pub fn catalog_unsupported() -> Markup {
         html! {
-            table .table .table-striped .table-bordered .table-hover .table-responsive .align-middle .text-center {
-                thead {
+            div .alert .alert-info .m-2 {
+                p {
+                    "This registry does not list its repositories. "
+                    @if env::known_repositories().is_empty() {
+                        "Set KNOWN_REPOSITORIES to list some here, or search a repository by name."
+                    } @else {
+                        "Showing the repositories of KNOWN_REPOSITORIES, search a repository by name to find the others."
+                    }
+                }
+                (repository_search(None))
+            }
+        }
+    }
+
+    pub fn empty_catalog() -> Markup {
+        let reference = common::service::image_reference("<image>");
+        html! {
+            div .alert .alert-info .m-2 {
+                p .fw-semibold { "No repositories yet \u{2014} push an image to get started" }
+                (image::view::command_list(&[
+                    format!("docker tag <image> {reference}:<tag>"),
+                    format!("docker push {reference}:<tag>"),
+                ]))
+            }
+        }
+    }
+
+    /// Home page URL with `params` as its query
+    fn home_href(params: &[(&str, &str)]) -> String {
+        if params.is_empty() {
+            "/".to_owned()
+        } else {
+            format!(
+                "/?{}",
+                serde_urlencoded::to_string(params).unwrap_or_default()
+            )
+        }
+    }
+
+    /// `next_cursor` is set when walking the catalog with the registry cursor,
+    /// `snapshot_taken_at` when the page comes from the background snapshot
+    pub fn image_table(
+        images: &Paginated<Image>,
+        next_cursor: Option<&str>,
+        snapshot_taken_at: Option<chrono::DateTime<chrono::Utc>>,
+        sort: ImageSort,
+        toggles: CatalogToggles,
+        density: TableDensity,
+        delete_action: DeleteAction,
+    ) -> Markup {
+        let show_pulls = env::registry_flavor().has_pull_counts();
+        let show_hidden = toggles.hidden == Some(true);
+        let show_empty = toggles.empty == Some(true);
+        let mut hidden_params = Vec::new();
+        if show_hidden {
+            hidden_params.push(("show_hidden", "true"));
+        }
+        if show_empty {
+            hidden_params.push(("show_empty", "true"));
+        }
+        let mut pagination_params = hidden_params.clone();
+        if sort != ImageSort::default() {
+            pagination_params.insert(0, ("sort", sort.as_str()));
+        }
+        let from = home_href(&pagination_params);
+        let toggled_hidden_href = home_href(
+            &pagination_params
+                .iter()
+                .copied()
+                .filter(|(name, _)| *name != "show_hidden")
+                .chain((!show_hidden).then_some(("show_hidden", "true")))
+                .collect::<Vec<_>>(),
+        );
+        let toggled_empty_href = home_href(
+            &pagination_params
+                .iter()
+                .copied()
+                .filter(|(name, _)| *name != "show_empty")
+                .chain((!show_empty).then_some(("show_empty", "true")))
+                .collect::<Vec<_>>(),
+        );
+        html! {
+            @if let Some(taken_at) = snapshot_taken_at {
+                form .d-flex .justify-content-end .align-items-center .gap-2 .m-2 method="post" action="/catalog/refresh" {
+                    span .text-muted title=(taken_at.to_rfc3339()) {
+                        (tf("updated", image::view::format_duration(chrono::Utc::now() - taken_at)))
+                    }
+                    button .btn .btn-sm .btn-outline-secondary type="submit" { (t("refresh")) }
+                }
+            }
+            .d-flex .justify-content-end .align-items-center .gap-2 .mx-2 .mb-2 {
+                @if toggles.hidden.is_some() {
+                    a .btn .btn-outline-secondary href=(toggled_hidden_href) {
+                        @if show_hidden { (t("hide_hidden_repositories")) } @else { (t("show_hidden_repositories")) }
+                    }
+                }
+                @if toggles.empty.is_some() {
+                    a .btn .btn-outline-secondary href=(toggled_empty_href) {
+                        @if show_empty { (t("hide_empty_repositories")) } @else { (t("show_empty_repositories")) }
+                    }
+                }
+                (common::view::density_toggle(density, &from))
+                @if images.need_pagination() {
+                    (common::view::pagination_fragment(images, "/", &pagination_params))
+                }
+            }
+            table .table .table-striped .table-bordered .table-hover .table-responsive .align-middle .text-center .table-sm[density == TableDensity::Compact] {
+                thead .sticky-header {
                     tr {
-                        th { "Image Name" }
-                        th { "Tag Count" }
-                        th { "Action" }
+                        th { (t("image_name")) }
+                        th { (t("tag_count")) }
+                        @if show_pulls {
+                            th {
+                                @if sort == ImageSort::Pulls {
+                                    a .text-decoration-none href=(home_href(&hidden_params)) { "Pulls \u{25BC}" }
+                                } @else {
+                                    a .text-decoration-none href=(home_href(&[&[("sort", "pulls")], hidden_params.as_slice()].concat())) { "Pulls" }
+                                }
+                            }
+                        }
+                        @if delete_action != DeleteAction::Hidden {
+                            th { (t("action")) }
+                        }
                     }
                 }
                 tbody {
-                    @for image in images {
-                        @if image.tag_count > 0 {
-                            tr {
-                                td { a href=(image.name) { (image.name) } }
-                                td { (image.tag_count) }
-                                td {
-                                    form action=(format!("{}/delete", image.name)) method="post" .m-0 {
-                                        button .btn .btn-danger type="submit" {
-                                            "Delete"
+                    @for image in images.iter() {
+                        tr {
+                            td { a href=(image.name) { (image.name) } }
+                            td { (image.tag_count) }
+                            @if show_pulls {
+                                td { (image.pull_count.map_or_else(|| "?".to_owned(), |pull_count| pull_count.to_string())) }
+                            }
+                            @match delete_action {
+                                DeleteAction::Allowed if image.tag_count == 0 => td {
+                                    @if env::registry_flavor().can_delete_repositories() {
+                                        a .btn .btn-outline-danger href=(format!("{}/repository/delete", image.name)) {
+                                            "Delete repository"
+                                        }
+                                    } @else {
+                                        span .d-inline-block tabindex="0" title="The distribution API cannot delete repositories, the registry garbage collector leaves them listed" {
+                                            button .btn .btn-outline-danger type="button" disabled { "Delete repository" }
                                         }
                                     }
-                                }
+                                },
+                                DeleteAction::Allowed => td {
+                                    a .btn .btn-danger href=(format!("{}/delete", image.name)) {
+                                        (t("delete"))
+                                    }
+                                },
+                                DeleteAction::Disabled => td {
+                                    (common::view::disabled_delete_button(t("delete")))
+                                },
+                                DeleteAction::Hidden => {},
                             }
                         }
                     }
                 }
             }
+            @if images.need_pagination() {
+                .d-flex .justify-content-end .mx-2 {
+                    (common::view::pagination_fragment(images, "/", &pagination_params))
+                }
+            }
+            @if let Some(next_cursor) = next_cursor {
+                @let size = images.size.to_string();
+                .d-flex .justify-content-end .gap-2 .mx-2 {
+                    a .btn .btn-outline-primary href=(home_href(&[&[("last", "")], hidden_params.as_slice()].concat())) { "First page" }
+                    a .btn .btn-primary href=(home_href(&[&[("last", next_cursor), ("size", &size)], hidden_params.as_slice()].concat())) { "Next page" }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,337 @@
+pub mod dto {
+    use serde::Deserialize;
+
+    /// Language of the rendered pages, from the `lang` cookie, `Accept-Language` or
+    /// `DEFAULT_LOCALE`
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub enum Locale {
+        #[default]
+        En,
+        Fr,
+    }
+
+    /// Accepts language tags, only their primary subtag is looked at
+    impl std::str::FromStr for Locale {
+        type Err = ();
+
+        fn from_str(value: &str) -> Result<Self, Self::Err> {
+            let language = value.trim().split(['-', '_']).next().unwrap_or_default();
+            match language.to_lowercase().as_str() {
+                "en" => Ok(Self::En),
+                "fr" => Ok(Self::Fr),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl Locale {
+        pub const ALL: [Self; 2] = [Self::En, Self::Fr];
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::En => "en",
+                Self::Fr => "fr",
+            }
+        }
+
+        /// Shown in the language selector, in the language itself
+        pub const fn name(self) -> &'static str {
+            match self {
+                Self::En => "English",
+                Self::Fr => "Fran\u{E7}ais",
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct LocaleForm {
+        pub lang: String,
+        /// Page to go back to, only local paths are followed
+        pub from: String,
+    }
+}
+
+pub mod handler {
+    use axum::{
+        Form,
+        extract::Request,
+        http::header,
+        middleware::Next,
+        response::{Redirect, Response},
+    };
+    use axum_extra::extract::CookieJar;
+
+    use crate::{
+        common::{self, service::env},
+        i18n::{
+            dto::{Locale, LocaleForm},
+            service::{self, CookieJarLocaleExt},
+        },
+    };
+
+    /// Unknown languages leave the current one in place
+    pub async fn select(
+        cookie_jar: CookieJar,
+        Form(LocaleForm { lang, from }): Form<LocaleForm>,
+    ) -> (CookieJar, Redirect) {
+        let from = common::service::local_redirect_target(&from).unwrap_or("/");
+        let cookie_jar = match lang.parse::<Locale>() {
+            Ok(locale) => cookie_jar.set_locale(locale),
+            Err(()) => cookie_jar,
+        };
+        (cookie_jar, Redirect::to(from))
+    }
+
+    /// Renders the request in the language of the `lang` cookie, else the preferred supported one
+    /// of `Accept-Language`
+    pub async fn localize(cookie_jar: CookieJar, request: Request, next: Next) -> Response {
+        let locale = cookie_jar
+            .locale()
+            .or_else(|| {
+                request
+                    .headers()
+                    .get(header::ACCEPT_LANGUAGE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(service::negotiate)
+            })
+            .unwrap_or_else(env::default_locale);
+        service::scope(locale, next.run(request)).await
+    }
+}
+
+pub mod service {
+    use axum_extra::extract::{
+        CookieJar,
+        cookie::{Cookie, SameSite},
+    };
+
+    use crate::{common::service::env, i18n::dto::Locale};
+
+    pub const LOCALE_COOKIE_NAME: &str = "lang";
+
+    /// `{}` is replaced by the argument of `tf`
+    const EN: &[(&str, &str)] = &[
+        ("action", "Action"),
+        ("ago", "{} ago"),
+        ("all_architectures", "All architectures"),
+        ("apply", "Apply"),
+        ("architecture", "Architecture"),
+        ("back_to_images", "Back to images"),
+        ("columns", "Columns"),
+        ("comfortable_rows", "Comfortable rows"),
+        ("compact_rows", "Compact rows"),
+        ("copy_all_digests", "Copy all digests"),
+        ("copy_all_tags", "Copy all tags"),
+        ("created", "Creation Date"),
+        ("delete", "Delete"),
+        ("digest", "Digest"),
+        ("duration.days", "{} day(s)"),
+        ("duration.hours", "{} hour(s)"),
+        ("duration.minutes", "{} minute(s)"),
+        ("duration.seconds", "{} second(s)"),
+        ("go", "Go"),
+        ("go_to_page", "Go to page"),
+        ("hide_empty_repositories", "Hide empty repositories"),
+        ("hide_hidden_repositories", "Hide hidden repositories"),
+        ("image_name", "Image Name"),
+        ("image_tags", "{} image tags"),
+        ("invalid_credentials", "Invalid username or password"),
+        ("last_checked", "Last checked {} ago"),
+        ("logged_out", "You have been logged out"),
+        ("login", "Login"),
+        ("logout", "Logout"),
+        ("manifest", "Manifest"),
+        ("next", "Next"),
+        ("no_tags", "No tags found."),
+        ("page", "Page"),
+        ("password", "Password"),
+        ("previous", "Previous"),
+        ("pull", "Pull"),
+        ("refresh", "Refresh"),
+        (
+            "registry_down.after",
+            "does not answer. The explorer is back as soon as it does.",
+        ),
+        ("registry_down.before", "The registry at"),
+        ("registry_unavailable", "Registry unavailable"),
+        ("retry", "Retry"),
+        ("scan", "Scan"),
+        ("show_empty_repositories", "Show empty repositories"),
+        ("show_hidden_repositories", "Show hidden repositories"),
+        ("signed", "Signed"),
+        ("size", "Size"),
+        ("tag", "Tag"),
+        ("tag_count", "Tag Count"),
+        ("type", "Type"),
+        ("updated", "Updated {} ago"),
+        ("username", "Username"),
+    ];
+
+    const FR: &[(&str, &str)] = &[
+        ("action", "Action"),
+        ("ago", "il y a {}"),
+        ("all_architectures", "Toutes les architectures"),
+        ("apply", "Appliquer"),
+        ("architecture", "Architecture"),
+        ("back_to_images", "Retour aux images"),
+        ("columns", "Colonnes"),
+        ("comfortable_rows", "Lignes espac\u{E9}es"),
+        ("compact_rows", "Lignes compactes"),
+        ("copy_all_digests", "Copier toutes les empreintes"),
+        ("copy_all_tags", "Copier tous les tags"),
+        ("created", "Date de cr\u{E9}ation"),
+        ("delete", "Supprimer"),
+        ("digest", "Empreinte"),
+        ("duration.days", "{} jour(s)"),
+        ("duration.hours", "{} heure(s)"),
+        ("duration.minutes", "{} minute(s)"),
+        ("duration.seconds", "{} seconde(s)"),
+        ("go", "Aller"),
+        ("go_to_page", "Aller \u{E0} la page"),
+        (
+            "hide_empty_repositories",
+            "Masquer les d\u{E9}p\u{F4}ts vides",
+        ),
+        (
+            "hide_hidden_repositories",
+            "Masquer les d\u{E9}p\u{F4}ts cach\u{E9}s",
+        ),
+        ("image_name", "Nom de l'image"),
+        ("image_tags", "Tags de l'image {}"),
+        (
+            "invalid_credentials",
+            "Nom d'utilisateur ou mot de passe invalide",
+        ),
+        ("last_checked", "Derni\u{E8}re v\u{E9}rification il y a {}"),
+        ("logged_out", "Vous avez \u{E9}t\u{E9} d\u{E9}connect\u{E9}"),
+        ("login", "Connexion"),
+        ("logout", "D\u{E9}connexion"),
+        ("manifest", "Manifeste"),
+        ("next", "Suivant"),
+        ("no_tags", "Aucun tag trouv\u{E9}."),
+        ("page", "Page"),
+        ("password", "Mot de passe"),
+        ("previous", "Pr\u{E9}c\u{E9}dent"),
+        ("pull", "Pull"),
+        ("refresh", "Rafra\u{EE}chir"),
+        (
+            "registry_down.after",
+            "ne r\u{E9}pond pas. L'explorateur revient d\u{E8}s qu'il r\u{E9}pond.",
+        ),
+        ("registry_down.before", "Le registre"),
+        ("registry_unavailable", "Registre indisponible"),
+        ("retry", "R\u{E9}essayer"),
+        ("scan", "Analyse"),
+        (
+            "show_empty_repositories",
+            "Afficher les d\u{E9}p\u{F4}ts vides",
+        ),
+        (
+            "show_hidden_repositories",
+            "Afficher les d\u{E9}p\u{F4}ts cach\u{E9}s",
+        ),
+        ("signed", "Sign\u{E9}"),
+        ("size", "Taille"),
+        ("tag", "Tag"),
+        ("tag_count", "Nombre de tags"),
+        ("type", "Type"),
+        ("updated", "Mis \u{E0} jour il y a {}"),
+        ("username", "Nom d'utilisateur"),
+    ];
+
+    tokio::task_local! {
+        static LOCALE: Locale;
+    }
+
+    /// Runs `render` with `locale` as the language of `t`
+    pub async fn scope<F: Future>(locale: Locale, render: F) -> F::Output {
+        LOCALE.scope(locale, render).await
+    }
+
+    /// Language of the current request, `DEFAULT_LOCALE` outside of one
+    pub fn locale() -> Locale {
+        LOCALE
+            .try_with(|locale| *locale)
+            .unwrap_or_else(|_| env::default_locale())
+    }
+
+    /// Supported language with the highest quality, the first listed among equals
+    pub fn negotiate(accept_language: &str) -> Option<Locale> {
+        accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let locale = parts.next()?.parse::<Locale>().ok()?;
+                let quality = match parts.find_map(|part| part.trim().strip_prefix("q=")) {
+                    Some(quality) => quality.parse::<f32>().ok()?,
+                    None => 1.0,
+                };
+                Some((locale, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .min_by(|(_, a), (_, b)| b.total_cmp(a))
+            .map(|(locale, _)| locale)
+    }
+
+    /// Text of `key` in the current language, in English when it is not translated
+    pub fn t(key: &'static str) -> &'static str {
+        let catalog = match locale() {
+            Locale::En => EN,
+            Locale::Fr => FR,
+        };
+        [catalog, EN]
+            .into_iter()
+            .find_map(|catalog| {
+                catalog
+                    .iter()
+                    .find(|(candidate, _)| *candidate == key)
+                    .map(|(_, text)| *text)
+            })
+            .unwrap_or(key)
+    }
+
+    /// `t` with its `{}` replaced by `argument`
+    pub fn tf(key: &'static str, argument: impl std::fmt::Display) -> String {
+        t(key).replacen("{}", &argument.to_string(), 1)
+    }
+
+    #[easy_ext::ext(CookieJarLocaleExt)]
+    impl CookieJar {
+        pub fn set_locale(self, locale: Locale) -> Self {
+            self.add(
+                Cookie::build((LOCALE_COOKIE_NAME, locale.as_str()))
+                    .http_only(true)
+                    .secure(env::cookie_secure())
+                    .path("/")
+                    .same_site(SameSite::Strict)
+                    .permanent(),
+            )
+        }
+
+        pub fn locale(&self) -> Option<Locale> {
+            self.get(LOCALE_COOKIE_NAME)
+                .and_then(|cookie| cookie.value().parse().ok())
+        }
+    }
+}
+
+pub mod view {
+    use maud::{Markup, html};
+
+    use crate::i18n::{dto::Locale, service};
+
+    /// Switches the language of the pages, `from` is filled with the current page on submit
+    pub fn selector() -> Markup {
+        let current = service::locale();
+        html! {
+            form .m-0 .ms-3 method="post" action="/lang" {
+                input type="hidden" name="from" value="/";
+                select .form-select .form-select-sm name="lang" aria-label="Language" onchange="this.form.from.value = location.pathname + location.search; this.form.submit()" {
+                    @for locale in Locale::ALL {
+                        option value=(locale.as_str()) selected[locale == current] { (locale.name()) }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,222 +1,2602 @@
 pub mod dto {
+    use std::collections::{BTreeMap, HashSet};
+
     use chrono::Utc;
+    use serde::{Deserialize, Serialize};
 
-    use crate::common::service::Paginated;
+    use crate::{
+        common::{
+            self,
+            dto::DeleteAction,
+            service::{Paginated, env},
+        },
+        i18n,
+        registry::dto::{ArtifactType, Layer, MediaType},
+    };
 
+    #[derive(Serialize)]
     pub struct ImageInfo {
         pub tags: Paginated<Tag>,
+        /// Every tag of the image, regardless of the page and architecture filter
+        pub tag_count: usize,
+        /// Distinct architectures across all the tags of the image
+        pub architectures: Vec<String>,
+        pub size: ImageSize,
+        /// Creation date of the most recent tag
+        pub newest: Option<chrono::DateTime<Utc>>,
+        /// Creation date of the oldest tag
+        pub oldest: Option<chrono::DateTime<Utc>>,
+        /// More tags than `MAX_ENRICHED_TAGS`: sorted by name, only part of the page is
+        /// enriched and the image-wide figures are unknown
+        pub partial: bool,
+        /// Only the manifests of the page were fetched, the image-wide figures are unknown and
+        /// `architectures` are those of the page
+        pub page_only: bool,
+    }
+
+    /// Storage used by all the tags of an image
+    #[derive(Serialize)]
+    pub struct ImageSize {
+        /// Each layer counted once, however many tags share it
+        pub unique: u64,
+        /// Sum of the tag sizes, shared layers are counted once per tag
+        pub sum: u64,
     }
 
+    #[derive(Clone, Serialize)]
     pub struct Tag {
         pub name: String,
         pub digest: String,
         pub error: bool,
         pub architecture: Option<String>,
+        /// Bare architecture names (`amd64`, `arm64`, ...), one per platform for multi-arch tags
+        pub architectures: Vec<String>,
+        /// `None` when the manifest could not be resolved
+        pub artifact_type: Option<ArtifactType>,
+        /// Manifest format, `None` when the manifest could not be resolved
+        pub media_type: Option<MediaType>,
         pub created: Option<chrono::DateTime<Utc>>,
+        /// In seconds once serialized
+        #[serde(serialize_with = "common::dto::serialize_duration_secs")]
         pub created_since: Option<chrono::Duration>,
+        pub layers: Vec<Layer>,
+        /// The manifest is shared with a tag matching `PROTECTED_TAGS`
+        pub protected: bool,
+        /// Other tags of the same manifest, only filled in the grouped view
+        pub aliases: Vec<String>,
+        /// Platforms (`os/architecture[/variant]`) of a multi-platform tag
+        pub platforms: Vec<String>,
+        /// Size of each platform of an index, empty otherwise. `None` for a platform whose
+        /// manifest could not be fetched
+        pub platform_sizes: Vec<(String, Option<u64>)>,
+        /// Approximate Dockerfile rebuilt from the image history, empty for indexes
+        pub dockerfile: Vec<String>,
+        /// Labels of the image config, empty for indexes
+        pub labels: BTreeMap<String, String>,
+        /// Annotations of the image manifest, empty for indexes
+        pub annotations: BTreeMap<String, String>,
+        /// `KEY=value` environment of the image config, empty for indexes
+        pub env: Vec<String>,
+        /// Only the digest is known, the details are on the tag page
+        pub deferred: bool,
+        /// The manifest or its config does not hash to its digest, see `VERIFY_DIGESTS`
+        pub digest_mismatch: bool,
+        /// Only classified in the tag list
+        pub version: TagVersion,
+        /// Highest release of the image, this tag or one of its aliases
+        pub latest_release: bool,
+        /// Only looked up for the tag list with the `Signed` column, `None` when the referrers
+        /// API is unavailable
+        #[serde(skip)]
+        pub signing: Option<SigningStatus>,
+    }
+
+    /// Whether a cosign signature refers to the manifest of a tag
+    #[derive(Clone)]
+    pub enum SigningStatus {
+        /// Subject and issuer of the certificate of a keyless signature
+        Signed {
+            subject: Option<String>,
+            issuer: Option<String>,
+        },
+        Unsigned,
+    }
+
+    /// How a tag name reads as a semantic version
+    #[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum TagVersion {
+        Release,
+        Prerelease,
+        /// Not a version, e.g. `latest` or a branch name
+        Other,
+    }
+
+    impl Tag {
+        /// Tag beyond `MAX_ENRICHED_TAGS`, its manifest and config are not fetched
+        pub fn deferred(name: String, digest: String) -> Self {
+            Self {
+                name,
+                digest,
+                error: false,
+                architecture: None,
+                architectures: Vec::new(),
+                artifact_type: None,
+                media_type: None,
+                created: None,
+                created_since: None,
+                layers: Vec::new(),
+                protected: false,
+                aliases: Vec::new(),
+                platforms: Vec::new(),
+                platform_sizes: Vec::new(),
+                dockerfile: Vec::new(),
+                labels: BTreeMap::new(),
+                annotations: BTreeMap::new(),
+                env: Vec::new(),
+                deferred: true,
+                digest_mismatch: false,
+                version: TagVersion::Other,
+                latest_release: false,
+                signing: None,
+            }
+        }
+
+        /// Tag whose manifest or digest could not be fetched, shown as an error row instead of
+        /// failing the whole page
+        pub fn unresolved(name: String) -> Self {
+            Self {
+                error: true,
+                deferred: false,
+                ..Self::deferred(name, String::new())
+            }
+        }
+
+        /// `None` when the manifest could not be resolved or was not fetched, and for schema 1
+        /// manifests which do not list layer sizes
+        pub fn size(&self) -> Option<u64> {
+            let schema1 = self.media_type.as_ref().is_some_and(MediaType::is_schema1);
+            (!self.error && !self.deferred && !schema1)
+                .then(|| self.layers.iter().map(|layer| layer.size).sum())
+        }
+    }
+
+    /// Outcome of the deletion of a single manifest
+    #[derive(Serialize)]
+    pub struct DeletionResult {
+        pub digest: String,
+        pub deleted: bool,
+    }
+
+    #[derive(Deserialize)]
+    pub struct RetentionQuery {
+        /// Tags created more than this many days ago are deleted
+        pub days: u32,
+    }
+
+    /// Confirmation of a `RetentionPlan`
+    #[derive(Deserialize)]
+    pub struct RetentionDeletionForm {
+        pub days: u32,
+        /// Comma-separated manifests of the confirmed plan, only these are deleted
+        pub digests: String,
+    }
+
+    impl RetentionDeletionForm {
+        pub fn digests(&self) -> HashSet<String> {
+            self.digests
+                .split(',')
+                .filter(|digest| !digest.is_empty())
+                .map(str::to_owned)
+                .collect()
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct CompareQuery {
+        pub a: Option<String>,
+        pub b: Option<String>,
+        /// `os/architecture[/variant]` compared when the tags are multi-platform
+        pub platform: Option<String>,
+    }
+
+    /// Layers of two compared tags split by the tag holding them
+    pub struct LayerDiff<'a> {
+        pub shared: Vec<&'a Layer>,
+        /// Only in the first tag
+        pub removed: Vec<&'a Layer>,
+        /// Only in the second tag
+        pub added: Vec<&'a Layer>,
+    }
+
+    /// What deleting the tags older than `days` involves, shown before confirming
+    pub struct RetentionPlan {
+        pub days: u32,
+        /// Manifests to delete with their tags, every tag of such a manifest is old
+        pub manifests: Vec<(String, Vec<String>)>,
+        /// Old tags kept because a newer, undated or protected tag shares their manifest
+        pub kept_tags: Vec<String>,
+    }
+
+    impl RetentionPlan {
+        /// Manifests of the plan that were also `confirmed`
+        pub fn confirmed(self, confirmed: &HashSet<String>) -> Self {
+            Self {
+                manifests: self
+                    .manifests
+                    .into_iter()
+                    .filter(|(digest, _)| confirmed.contains(digest))
+                    .collect(),
+                ..self
+            }
+        }
+    }
+
+    /// Outcome of the deletion of a tag by name
+    pub struct TagDeletion {
+        pub digest: String,
+        /// Every tag that pointed to the manifest, including the deleted one
+        pub tags: Vec<String>,
+        /// Tags whose digest could not be resolved, they may have pointed to the manifest too
+        pub unresolved_tags: Vec<String>,
+        pub deleted: bool,
+    }
+
+    /// Optional columns of the tag table
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum TagColumn {
+        Created,
+        Tag,
+        Digest,
+        Type,
+        MediaType,
+        Architecture,
+        Size,
+        Pull,
+        Scan,
+        Signed,
+        Action,
+    }
+
+    impl TagColumn {
+        pub const ALL: [Self; 11] = [
+            Self::Created,
+            Self::Tag,
+            Self::Digest,
+            Self::Type,
+            Self::MediaType,
+            Self::Architecture,
+            Self::Size,
+            Self::Pull,
+            Self::Scan,
+            Self::Signed,
+            Self::Action,
+        ];
+
+        pub const fn key(self) -> &'static str {
+            match self {
+                Self::Created => "created",
+                Self::Tag => "tag",
+                Self::Digest => "digest",
+                Self::Type => "type",
+                Self::MediaType => "media_type",
+                Self::Architecture => "architecture",
+                Self::Size => "size",
+                Self::Pull => "pull",
+                Self::Scan => "scan",
+                Self::Signed => "signed",
+                Self::Action => "action",
+            }
+        }
+
+        pub fn label(self) -> &'static str {
+            i18n::service::t(match self {
+                Self::Created => "created",
+                Self::Tag => "tag",
+                Self::Digest => "digest",
+                Self::Type => "type",
+                Self::MediaType => "manifest",
+                Self::Architecture => "architecture",
+                Self::Size => "size",
+                Self::Pull => "pull",
+                Self::Scan => "scan",
+                Self::Signed => "signed",
+                Self::Action => "action",
+            })
+        }
+
+        /// Every column but the delete action when it is hidden and the scan link when
+        /// `VULN_SCAN_URL_TEMPLATE` is not set
+        pub fn available(delete_action: DeleteAction) -> Vec<Self> {
+            Self::ALL
+                .into_iter()
+                .filter(|column| *column != Self::Action || delete_action != DeleteAction::Hidden)
+                .filter(|column| *column != Self::Scan || env::vuln_scan_url_template().is_some())
+                .collect()
+        }
+
+        /// Debugging columns, and `Signed` with its referrers lookup per row, are left out until
+        /// picked
+        pub const fn shown_by_default(self) -> bool {
+            !matches!(self, Self::MediaType | Self::Signed)
+        }
+
+        /// Columns with a manifest detail, filled in later for deferred rows
+        pub const fn needs_manifest(self) -> bool {
+            matches!(
+                self,
+                Self::Created | Self::Type | Self::MediaType | Self::Architecture | Self::Size
+            )
+        }
+
+        /// Sort applied when clicking the column header
+        pub const fn sort(self) -> Option<TagSort> {
+            match self {
+                Self::Created => Some(TagSort::Created),
+                Self::Tag => Some(TagSort::Name),
+                _ => None,
+            }
+        }
+
+        pub fn from_key(key: &str) -> Option<Self> {
+            Self::ALL.into_iter().find(|column| column.key() == key)
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct TagPlatformQuery {
+        /// `os/architecture[/variant]` of a multi-platform tag, its index is shown when absent
+        pub platform: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct RetagQuery {
+        /// Tag the copy commands of the tag page point at, a placeholder when absent
+        pub new_tag: Option<String>,
+    }
+
+    #[derive(Clone, Deserialize)]
+    pub struct TagFilterQuery {
+        pub arch: Option<String>,
+        /// One row per manifest instead of one per tag
+        #[serde(default)]
+        pub grouped: bool,
+        #[serde(default)]
+        pub sort: TagSort,
+        /// Defaults to the natural order of `sort`
+        pub order: Option<SortOrder>,
+        /// Every tag when absent, including those without a creation date
+        pub created_within: Option<CreatedWithin>,
+    }
+
+    impl TagFilterQuery {
+        pub fn order(&self) -> SortOrder {
+            self.order.unwrap_or(self.sort.default_order())
+        }
+
+        /// Query parameters reproducing this filter, defaults are left out
+        pub fn params(&self) -> Vec<(&'static str, &str)> {
+            let mut params = Vec::new();
+            if let Some(arch) = &self.arch {
+                params.push(("arch", arch.as_str()));
+            }
+            if self.grouped {
+                params.push(("grouped", "true"));
+            }
+            if self.sort != TagSort::default() {
+                params.push(("sort", self.sort.as_str()));
+            }
+            if self.order() != self.sort.default_order() {
+                params.push(("order", self.order().as_str()));
+            }
+            if let Some(created_within) = self.created_within {
+                params.push(("created_within", created_within.as_str()));
+            }
+            params
+        }
+
+        /// Same filter sorted by `sort`, reversing the order when it is already the current sort
+        pub fn sorted_by(&self, sort: TagSort) -> Self {
+            let order = if self.sort == sort {
+                self.order().reversed()
+            } else {
+                sort.default_order()
+            };
+            Self {
+                sort,
+                order: Some(order),
+                ..self.clone()
+            }
+        }
+    }
+
+    /// Quick filter on the age of the tags
+    #[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+    pub enum CreatedWithin {
+        #[serde(rename = "24h")]
+        Day,
+        #[serde(rename = "7d")]
+        Week,
+        #[serde(rename = "30d")]
+        Month,
+    }
+
+    impl CreatedWithin {
+        pub const ALL: [Self; 3] = [Self::Day, Self::Week, Self::Month];
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Day => "24h",
+                Self::Week => "7d",
+                Self::Month => "30d",
+            }
+        }
+
+        pub fn duration(self) -> chrono::Duration {
+            match self {
+                Self::Day => chrono::Duration::days(1),
+                Self::Week => chrono::Duration::days(7),
+                Self::Month => chrono::Duration::days(30),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum TagSort {
+        /// Tags whose manifest could not be resolved come last, by name
+        #[default]
+        Created,
+        Name,
+        /// Releases, then prereleases, by version, then the other tags by name
+        Semver,
+    }
+
+    impl TagSort {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Created => "created",
+                Self::Name => "name",
+                Self::Semver => "semver",
+            }
+        }
+
+        /// Newest first for dates and versions, A to Z for names
+        pub const fn default_order(self) -> SortOrder {
+            match self {
+                Self::Created | Self::Semver => SortOrder::Desc,
+                Self::Name => SortOrder::Asc,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum SortOrder {
+        Asc,
+        Desc,
+    }
+
+    impl SortOrder {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Asc => "asc",
+                Self::Desc => "desc",
+            }
+        }
+
+        pub const fn reversed(self) -> Self {
+            match self {
+                Self::Asc => Self::Desc,
+                Self::Desc => Self::Asc,
+            }
+        }
+
+        pub fn apply(self, ordering: std::cmp::Ordering) -> std::cmp::Ordering {
+            match self {
+                Self::Asc => ordering,
+                Self::Desc => ordering.reverse(),
+            }
+        }
     }
 }
 
 pub mod handler {
+    use std::collections::HashMap;
+
     use axum::{
+        Form,
+        body::Body,
         extract::{Path, Query, State},
-        response::Redirect,
+        http::{HeaderValue, StatusCode, header},
+        response::{IntoResponse, Redirect, Response},
     };
+    use axum_extra::extract::CookieJar;
     use joy_error::log::ResultLogExt;
     use maud::Markup;
 
     use crate::{
         AppState,
-        auth::middleware::Authenticated,
-        common::handler::PaginationQuery,
-        image::{service, view},
+        audit::{self, dto::Deletion},
+        auth::{
+            dto::Role,
+            middleware::{Authenticated, RequireAdmin},
+        },
+        common::{
+            self,
+            dto::{DeleteAction, DryRunQuery, Flash},
+            handler::PaginationQuery,
+            service::{density::CookieJarDensityExt, flash::CookieJarFlashExt},
+        },
+        error::service::{Error, Operation},
+        home,
+        image::{
+            dto::{
+                CompareQuery, RetagQuery, RetentionDeletionForm, RetentionQuery, TagColumn,
+                TagFilterQuery, TagPlatformQuery,
+            },
+            service::{self, CookieJarColumnsExt},
+            view,
+        },
     };
 
     pub async fn index(
         Path(image_name): Path<String>,
         Query(pagination): Query<PaginationQuery>,
+        Query(mut filter): Query<TagFilterQuery>,
+        State(AppState {
+            registry_api_client,
+            deletions_enabled,
+            ..
+        }): State<AppState>,
+        Authenticated(role): Authenticated,
+        cookie_jar: CookieJar,
+    ) -> Result<(CookieJar, Markup), Response> {
+        filter.arch = filter.arch.filter(|arch| !arch.is_empty());
+        let delete_action = DeleteAction::new(deletions_enabled, role);
+        let columns = cookie_jar.get_tag_columns(&TagColumn::available(delete_action));
+        let density = cookie_jar.table_density();
+        let (cookie_jar, flash) = cookie_jar.take_flash();
+        match service::get_image_info(
+            registry_api_client.clone(),
+            &image_name,
+            pagination,
+            &filter,
+        )
+        .await
+        .error()
+        .log_err()
+        {
+            Ok(mut info) => {
+                if columns.contains(&TagColumn::Signed) {
+                    service::look_up_signatures(
+                        &registry_api_client,
+                        &image_name,
+                        &mut info.tags.data,
+                    )
+                    .await;
+                }
+                Ok((
+                    cookie_jar,
+                    view::index(
+                        role,
+                        &image_name,
+                        &info,
+                        &filter,
+                        &columns,
+                        density,
+                        delete_action,
+                        flash,
+                    ),
+                ))
+            }
+            Err(err) => Err(common::handler::service_error(
+                role,
+                &err,
+                &format!("Could not load the tags of {image_name}."),
+            )),
+        }
+    }
+
+    /// Stores the visible columns of the tag table, the form only contains the checked ones
+    pub async fn set_tag_columns(
+        Path(image_name): Path<String>,
+        State(AppState {
+            deletions_enabled, ..
+        }): State<AppState>,
+        Authenticated(role): Authenticated,
+        cookie_jar: CookieJar,
+        Form(columns): Form<HashMap<String, String>>,
+    ) -> (CookieJar, Redirect) {
+        let columns = TagColumn::available(DeleteAction::new(deletions_enabled, role))
+            .into_iter()
+            .filter(|column| columns.contains_key(column.key()))
+            .collect::<Vec<_>>();
+        (
+            cookie_jar.set_tag_columns(&columns),
+            Redirect::to(&format!("/{image_name}")),
+        )
+    }
+
+    pub async fn tag_detail(
+        Path((image_name, tag_name)): Path<(String, String)>,
+        Query(TagPlatformQuery { platform }): Query<TagPlatformQuery>,
+        Query(RetagQuery { new_tag }): Query<RetagQuery>,
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        Authenticated(role): Authenticated,
+        cookie_jar: CookieJar,
+    ) -> Response {
+        let platform = platform.filter(|platform| !platform.is_empty());
+        let tag = match &platform {
+            Some(platform) => {
+                service::get_platform_tag(&registry_api_client, &image_name, tag_name, platform)
+                    .await
+            }
+            None => service::get_tag(&registry_api_client, &image_name, tag_name).await,
+        };
+        match tag.error().log_err() {
+            Ok(tag) => view::tag_detail(
+                role,
+                &image_name,
+                &tag,
+                platform.as_deref(),
+                new_tag.as_deref().filter(|new_tag| !new_tag.is_empty()),
+            )
+            .into_response(),
+            Err(err) => {
+                let redirect = Redirect::to(&format!("/{image_name}"));
+                match Error::registry(Operation::TagDetails, &err) {
+                    Error::Timeout(operation) => (
+                        cookie_jar
+                            .set_flash(&Flash::danger(format!("{} timed out", operation.label()))),
+                        redirect,
+                    )
+                        .into_response(),
+                    _ => redirect.into_response(),
+                }
+            }
+        }
+    }
+
+    /// Layers, sizes, environment and labels of two tags side by side
+    pub async fn compare(
+        Path(image_name): Path<String>,
+        Query(CompareQuery { a, b, platform }): Query<CompareQuery>,
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        Authenticated(role): Authenticated,
+    ) -> Response {
+        let tags = match service::get_image_tags(&registry_api_client, &image_name).await {
+            Ok(tags) => tags,
+            Err(err) => {
+                return common::handler::service_error(
+                    role,
+                    &err,
+                    &format!("Could not load the tags of {image_name}."),
+                );
+            }
+        };
+        let [a, b, platform] =
+            [a, b, platform].map(|value| value.filter(|value| !value.is_empty()));
+        let comparison = match (&a, &b) {
+            (Some(a), Some(b)) => match service::compare_tags(
+                &registry_api_client,
+                &image_name,
+                a.clone(),
+                b.clone(),
+                platform.as_deref(),
+            )
+            .await
+            .error()
+            .log_err()
+            {
+                Ok(comparison) => Some(comparison),
+                Err(err) => {
+                    return common::handler::service_error(
+                        role,
+                        &Error::registry(Operation::TagDetails, &err),
+                        &format!("Could not compare {a} with {b}."),
+                    );
+                }
+            },
+            _ => None,
+        };
+        view::compare(
+            role,
+            &image_name,
+            &tags,
+            a.as_deref(),
+            b.as_deref(),
+            platform.as_deref(),
+            comparison.as_ref(),
+        )
+        .into_response()
+    }
+
+    /// Tar of the manifest and config of a tag, sent as they are fetched
+    pub async fn bundle(
+        Path((image_name, tag_name)): Path<(String, String)>,
         State(AppState {
             registry_api_client,
             ..
         }): State<AppState>,
         _: Authenticated,
-    ) -> Result<Markup, Redirect> {
-        service::get_image_info(registry_api_client, &image_name, pagination)
+    ) -> Result<Response, StatusCode> {
+        let (filename, entries) = service::tag_bundle(registry_api_client, image_name, &tag_name)
             .await
             .error()
             .log_err()
-            .map_or_else(
-                |_| Err(Redirect::to("/")),
-                |info| Ok(view::index(&image_name, &info)),
-            )
+            .map_err(|_| StatusCode::BAD_GATEWAY)?;
+        Ok((
+            [
+                (header::CONTENT_TYPE, "application/x-tar".to_owned()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{filename}\""),
+                ),
+            ],
+            Body::from_stream(entries),
+        )
+            .into_response())
     }
 
-    pub async fn delete_tag(
-        Path((image_name, digest)): Path<(String, String)>,
+    /// Every tag name of the image, one per line, for the copy button of the image page
+    pub async fn tag_names(
+        Path(image_name): Path<String>,
         State(AppState {
             registry_api_client,
             ..
         }): State<AppState>,
-    ) -> Redirect {
-        if service::delete_tag(&registry_api_client, &image_name, &digest)
+        _: Authenticated,
+    ) -> Result<Response, StatusCode> {
+        let tags = service::get_image_tags(&registry_api_client, &image_name)
             .await
-            .is_err()
+            .map_err(|_| StatusCode::BAD_GATEWAY)?;
+        Ok(plain_text_lines(&tags))
+    }
+
+    /// Every manifest digest tagged in the image, one per line, resolved without the tag details.
+    /// Tags that could not be resolved are left out and named in `X-Unresolved-Tags`
+    pub async fn tag_digests(
+        Path(image_name): Path<String>,
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        _: Authenticated,
+    ) -> Result<Response, StatusCode> {
+        let (digests, unresolved_tags) =
+            service::get_image_digests(&registry_api_client, &image_name)
+                .await
+                .map_err(|_| StatusCode::BAD_GATEWAY)?;
+        let mut response = plain_text_lines(&digests);
+        if !unresolved_tags.is_empty()
+            && let Ok(value) = HeaderValue::from_str(&unresolved_tags.join(", "))
         {
-            tracing::error!("Could not delete image tag {image_name}:{digest}");
+            response.headers_mut().insert("x-unresolved-tags", value);
         }
-        Redirect::to(&format!("/{image_name}"))
+        Ok(response)
     }
-}
 
-pub mod service {
-
-    use joy_error::log::ResultLogExt;
-
-    use crate::{
-        common::handler::PaginationQuery,
-        error::service::ServiceResult,
-        image::dto::{ImageInfo, Tag},
-        registry,
-    };
+    fn plain_text_lines(lines: &[String]) -> Response {
+        (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            lines
+                .iter()
+                .map(|line| format!("{line}\n"))
+                .collect::<String>(),
+        )
+            .into_response()
+    }
 
-    pub async fn delete_tag(
-        registry_api_client: &registry::api::Client,
-        image_name: &str,
-        digest: &str,
-    ) -> ServiceResult<()> {
-        registry_api_client
-            .delete_tag(image_name, digest)
+    /// Detail cells of a row rendered without them because of `MAX_ENRICHED_TAGS`
+    pub async fn row_details(
+        Path((image_name, tag_name)): Path<(String, String)>,
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        _: Authenticated,
+    ) -> Result<Markup, StatusCode> {
+        service::get_tag(&registry_api_client, &image_name, tag_name)
             .await
             .error()
-            .log_err()?;
-        Ok(())
+            .log_err()
+            .map(|tag| view::row_details(&tag))
+            .map_err(|err| match Error::registry(Operation::TagDetails, &err) {
+                Error::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+                _ => StatusCode::BAD_GATEWAY,
+            })
     }
 
-    pub async fn get_image_tags(
-        registry_api_client: &registry::api::Client,
-        image_name: &str,
-    ) -> ServiceResult<Vec<String>> {
-        Ok(registry_api_client
-            .tags(image_name)
+    pub async fn dangling_manifests(
+        Path(image_name): Path<String>,
+        State(AppState {
+            registry_api_client,
+            deletions_enabled,
+            ..
+        }): State<AppState>,
+        Authenticated(role): Authenticated,
+    ) -> Response {
+        service::find_dangling_manifests(&registry_api_client, &image_name)
             .await
-            .error()
-            .log_err()?
-            .tags
-            .unwrap_or_default())
+            .map_or_else(
+                |err| {
+                    common::handler::service_error(
+                        role,
+                        &err,
+                        &format!("Could not list the dangling manifests of {image_name}."),
+                    )
+                },
+                |digests| {
+                    view::dangling_manifests(
+                        role,
+                        &image_name,
+                        &digests,
+                        DeleteAction::new(deletions_enabled, role),
+                    )
+                    .into_response()
+                },
+            )
     }
 
-    pub async fn get_image_info(
-        registry_api_client: registry::api::Client,
-        image_name: &str,
-        pagination: PaginationQuery,
-    ) -> ServiceResult<ImageInfo> {
-        let tags = get_image_tags(&registry_api_client, image_name).await?;
-        let tags = pagination.into_paginated(15, &tags)?;
-        let mut tags = tags
-            .map(|tag| async {
-                let digest_response = registry_api_client.manifest(image_name, &tag).await?;
-                let tag = match digest_response {
-                    registry::dto::TagManifest::Nominal {
-                        digest,
-                        created,
-                        architecture,
-                    } => Tag {
-                        digest,
-                        created: Some(created),
-                        created_since: Some(chrono::Utc::now() - created),
-                        architecture: Some(architecture),
-                        error: false,
-                        name: tag,
-                    },
-                    registry::dto::TagManifest::MultiArch {
-                        digest,
-                        architectures,
-                        created,
-                    } => Tag {
-                        digest,
-                        created,
-                        created_since: created.map(|c| chrono::Utc::now() - c),
-                        architecture: Some(architectures.join(", ")),
-                        error: false,
-                        name: tag,
+    pub async fn delete_dangling_manifests(
+        Path(image_name): Path<String>,
+        State(AppState {
+            registry_api_client,
+            ..
+        }): State<AppState>,
+        RequireAdmin(username): RequireAdmin,
+        Query(DryRunQuery { dry_run }): Query<DryRunQuery>,
+    ) -> Response {
+        if dry_run {
+            return service::find_dangling_manifests(&registry_api_client, &image_name)
+                .await
+                .map_or_else(
+                    |err| {
+                        common::handler::service_error(
+                            Role::Admin,
+                            &err,
+                            &format!("Could not list the dangling manifests of {image_name}."),
+                        )
                     },
-                    registry::dto::TagManifest::Error { digest } => Tag {
-                        digest,
-                        created: None,
-                        created_since: None,
-                        architecture: None,
-                        error: true,
-                        name: tag,
+                    |digests| {
+                        let manifests = digests
+                            .into_iter()
+                            .map(|digest| (digest, Vec::new()))
+                            .collect::<Vec<_>>();
+                        common::view::dry_run(
+                            Role::Admin,
+                            &format!("{image_name} dangling manifests"),
+                            &format!("/{image_name}"),
+                            &manifests,
+                        )
+                        .into_response()
                     },
-                };
-                anyhow::Ok(tag)
-            })
-            .into_future()
-            .await
-            .into_result()?;
-        tags.data.sort_by(|a, b| b.created.cmp(&a.created));
+                );
+        }
+        let results =
+            match service::delete_dangling_manifests(&registry_api_client, &image_name).await {
+                Ok(results) => results,
+                Err(err) => {
+                    return common::handler::service_error(
+                        Role::Admin,
+                        &err,
+                        &format!("Could not delete the dangling manifests of {image_name}."),
+                    );
+                }
+            };
+        for result in &results {
+            audit::service::record(
+                username,
+                Deletion::DanglingManifest,
+                &image_name,
+                Some(&result.digest),
+                Vec::new(),
+                Vec::new(),
+                result.deleted,
+            )
+            .await;
+        }
+        view::deletion_results(&image_name, &results).into_response()
+    }
 
-        Ok(ImageInfo { tags })
+    pub async fn retention(
+        Path(image_name): Path<String>,
+        Query(RetentionQuery { days }): Query<RetentionQuery>,
+        State(AppState {
+            registry_api_client,
+            deletions_enabled,
+            ..
+        }): State<AppState>,
+        Authenticated(role): Authenticated,
+    ) -> Response {
+        if days == 0 {
+            return retention_without_days(role);
+        }
+        service::plan_retention(&registry_api_client, &image_name, days)
+            .await
+            .map_or_else(
+                |err| {
+                    common::handler::service_error(
+                        role,
+                        &err,
+                        &format!("Could not plan the retention of {image_name}."),
+                    )
+                },
+                |plan| {
+                    view::retention(
+                        role,
+                        &image_name,
+                        &plan,
+                        DeleteAction::new(deletions_enabled, role),
+                    )
+                    .into_response()
+                },
+            )
     }
-}
 
-pub mod view {
-    use maud::{Markup, html};
+    fn retention_without_days(role: Role) -> Response {
+        (
+            StatusCode::BAD_REQUEST,
+            common::view::error_page(
+                Some(role),
+                StatusCode::BAD_REQUEST,
+                "Retention needs at least 1 day.",
+            ),
+        )
+            .into_response()
+    }
 
-    use crate::{
-        common::{self, service::Paginated},
-        image::dto::ImageInfo,
-    };
+    /// Only the confirmed manifests are deleted, and only those the plan computed again still
+    /// selects: tags pushed since the confirmation are kept
+    pub async fn delete_old_tags(
+        Path(image_name): Path<String>,
+        State(AppState {
+            registry_api_client,
+            catalog_snapshot,
+            ..
+        }): State<AppState>,
+        RequireAdmin(username): RequireAdmin,
+        Query(DryRunQuery { dry_run }): Query<DryRunQuery>,
+        Form(form): Form<RetentionDeletionForm>,
+    ) -> Response {
+        let days = form.days;
+        if days == 0 {
+            return retention_without_days(Role::Admin);
+        }
+        let confirmed = form.digests();
+        if dry_run {
+            return service::plan_retention(&registry_api_client, &image_name, days)
+                .await
+                .map_or_else(
+                    |err| {
+                        common::handler::service_error(
+                            Role::Admin,
+                            &err,
+                            &format!("Could not plan the retention of {image_name}."),
+                        )
+                    },
+                    |plan| {
+                        common::view::dry_run(
+                            Role::Admin,
+                            &format!("{image_name} tags older than {days} days"),
+                            &format!("/{image_name}"),
+                            &plan.confirmed(&confirmed).manifests,
+                        )
+                        .into_response()
+                    },
+                );
+        }
+        let (plan, results) =
+            match service::delete_old_tags(&registry_api_client, &image_name, days, &confirmed)
+                .await
+            {
+                Ok(deletion) => deletion,
+                Err(err) => {
+                    return common::handler::service_error(
+                        Role::Admin,
+                        &err,
+                        &format!("Could not delete the old tags of {image_name}."),
+                    );
+                }
+            };
+        let mut deleted_tags = 0;
+        for ((_, tags), result) in plan.manifests.into_iter().zip(&results) {
+            if result.deleted {
+                deleted_tags += tags.len();
+            }
+            audit::service::record(
+                username,
+                Deletion::OldTags,
+                &image_name,
+                Some(&result.digest),
+                tags,
+                Vec::new(),
+                result.deleted,
+            )
+            .await;
+        }
+        home::service::patch_snapshot_tag_count(&catalog_snapshot, &image_name, deleted_tags);
+        view::deletion_results(&image_name, &results).into_response()
+    }
 
-    pub fn index(image_name: &str, info: &ImageInfo) -> Markup {
-        const LEFT_ARROW: &str = "\u{1F850}";
-        const CLIPBOARD: &str = "\u{1F4CB}";
+    pub async fn delete_tag(
+        Path((image_name, digest)): Path<(String, String)>,
+        State(AppState {
+            registry_api_client,
+            catalog_snapshot,
+            ..
+        }): State<AppState>,
+        RequireAdmin(username): RequireAdmin,
+        cookie_jar: CookieJar,
+    ) -> (CookieJar, Redirect) {
+        let redirect = Redirect::to(&format!("/{image_name}"));
+        let protected_digests =
+            service::resolve_protected_digests(&registry_api_client, &image_name).await;
+        // Every tag pointing to the manifest goes away with it, a refused deletion needs none
+        let (tags, unresolved_tags) = match &protected_digests {
+            Ok(protected_digests) if !protected_digests.contains(&digest) => {
+                service::get_digest_tags(&registry_api_client, &image_name, &digest)
+                    .await
+                    .unwrap_or_default()
+            }
+            _ => Default::default(),
+        };
+        let result = match protected_digests {
+            Ok(protected_digests) => {
+                service::delete_unprotected_tag(
+                    &registry_api_client,
+                    &image_name,
+                    &digest,
+                    &protected_digests,
+                )
+                .await
+            }
+            Err(err) => Err(err),
+        };
+        if matches!(result, Ok(true)) {
+            home::service::patch_snapshot_tag_count(&catalog_snapshot, &image_name, tags.len());
+        }
+        audit::service::record(
+            username,
+            Deletion::Tag,
+            &image_name,
+            Some(&digest),
+            tags,
+            unresolved_tags,
+            matches!(result, Ok(true)),
+        )
+        .await;
+        match result {
+            Ok(true) => (cookie_jar, redirect),
+            Ok(false) => (
+                cookie_jar.set_flash(&Flash::warning("Tag no longer exists")),
+                redirect,
+            ),
+            Err(Error::ReadOnly) => (
+                cookie_jar.set_flash(&Flash::danger("The explorer is in read-only mode")),
+                redirect,
+            ),
+            Err(Error::Protected) => (
+                cookie_jar.set_flash(&Flash::danger("Tag is protected.")),
+                redirect,
+            ),
+            Err(Error::Unreachable(message) | Error::Unauthorized(message)) => (
+                cookie_jar.set_flash(&Flash::danger(format!(
+                    "Nothing was deleted, the registry failed: {message}"
+                ))),
+                redirect,
+            ),
+            Err(Error::Timeout(operation)) => (
+                cookie_jar.set_flash(&Flash::danger(format!(
+                    "Nothing was deleted, {} timed out",
+                    operation.label()
+                ))),
+                redirect,
+            ),
+            Err(Error::Unknown) => {
+                tracing::error!("Could not delete image tag {image_name}:{digest}");
+                (cookie_jar, redirect)
+            }
+        }
+    }
+}
+
+pub mod service {
+    use std::{
+        cmp::Ordering,
+        collections::{BTreeMap, HashMap, HashSet},
+    };
 
-        let registry_host = common::service::env::registry_host();
+    use anyhow::Context;
+    use axum_extra::extract::{
+        CookieJar,
+        cookie::{Cookie, SameSite},
+    };
+    use futures::{Stream, StreamExt};
+    use itertools::Itertools;
+    use joy_error::log::ResultLogExt;
+    use x509_parser::{asn1_rs::FromDer, der_parser::Oid, extensions::GeneralName};
 
-        common::view::page().js("clipboard").content(html! {
+    use crate::{
+        audit::{self, dto::Deletion},
+        common::{self, handler::PaginationQuery},
+        error::service::{Error, Operation, ServiceResult},
+        image::dto::{
+            DeletionResult, ImageInfo, ImageSize, LayerDiff, RetentionPlan, SigningStatus,
+            SortOrder, Tag, TagColumn, TagDeletion, TagFilterQuery, TagSort, TagVersion,
+        },
+        metrics::service::RegistryCallExt,
+        registry::{
+            self,
+            dto::{ArtifactType, Layer},
+        },
+    };
+
+    pub const TAG_COLUMNS_COOKIE_NAME: &str = "tag_columns";
+
+    /// Cosign signatures stored as OCI 1.1 artifacts, and sigstore bundles
+    const SIGNATURE_ARTIFACT_TYPES: [&str; 2] = [
+        "application/vnd.dev.cosign.artifact.sig.v1+json",
+        "application/vnd.dev.sigstore.bundle.v0.3+json",
+    ];
+
+    /// Fulcio issuer extension, a UTF8String
+    const FULCIO_ISSUER_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 8];
+
+    /// Deprecated Fulcio issuer extension, the bare URL
+    const FULCIO_LEGACY_ISSUER_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 1];
+
+    #[easy_ext::ext(CookieJarColumnsExt)]
+    impl CookieJar {
+        pub fn set_tag_columns(self, columns: &[TagColumn]) -> Self {
+            self.add(
+                Cookie::build((
+                    TAG_COLUMNS_COOKIE_NAME,
+                    columns.iter().map(|column| column.key()).join("."),
+                ))
+                .http_only(true)
+                .secure(common::service::env::cookie_secure())
+                .path("/")
+                .same_site(SameSite::Strict)
+                .permanent(),
+            )
+        }
+
+        /// Columns shown by default when no preference was saved
+        pub fn get_tag_columns(&self, available: &[TagColumn]) -> Vec<TagColumn> {
+            self.get(TAG_COLUMNS_COOKIE_NAME).map_or_else(
+                || {
+                    available
+                        .iter()
+                        .copied()
+                        .filter(|column| column.shown_by_default())
+                        .collect()
+                },
+                |cookie| {
+                    cookie
+                        .value()
+                        .split('.')
+                        .filter_map(TagColumn::from_key)
+                        .filter(|column| available.contains(column))
+                        .collect()
+                },
+            )
+        }
+    }
+
+    /// Digests the tags matching `PROTECTED_TAGS` point to, resolved once per request. A protected
+    /// tag that cannot be resolved may point to any manifest, the error names it and nothing must
+    /// be deleted
+    pub async fn resolve_protected_digests(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<HashSet<String>> {
+        if common::service::env::protected_tags().is_empty() {
+            return Ok(HashSet::new());
+        }
+        let tags = get_image_tags(registry_api_client, image_name).await?;
+        futures::future::join_all(
+            tags.iter()
+                .filter(|tag| common::service::is_protected_tag(tag))
+                .map(|tag| async move {
+                    registry_api_client
+                        .find_manifest_digest(image_name, tag)
+                        .counted()
+                        .await
+                        .with_context(|| {
+                            format!("could not resolve the protected tag {image_name}:{tag}")
+                        })
+                }),
+        )
+        .await
+        .into_iter()
+        .flatten_ok()
+        .collect::<anyhow::Result<HashSet<_>>>()
+        .error()
+        .log_err()
+        .map_err(|err| Error::registry(Operation::TagDetails, &err))
+    }
+
+    /// Refuses a digest of `protected_digests`. Returns `false` without deleting anything when
+    /// the manifest is already gone from the registry, this check can be disabled with
+    /// `REGISTRY_REVALIDATE_BEFORE_DELETE`
+    pub async fn delete_unprotected_tag(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        digest: &str,
+        protected_digests: &HashSet<String>,
+    ) -> ServiceResult<bool> {
+        if common::service::env::read_only() {
+            return Err(Error::ReadOnly);
+        }
+        if protected_digests.contains(digest) {
+            return Err(Error::Protected);
+        }
+        if common::service::env::registry_revalidate_before_delete()
+            && !registry_api_client
+                .manifest_exists(image_name, digest)
+                .counted()
+                .await
+                .with_context(|| format!("could not check {image_name}@{digest}"))
+                .error()
+                .log_err()?
+        {
+            return Ok(false);
+        }
+        registry_api_client
+            .delete_tag(image_name, digest)
+            .counted()
+            .await
+            .with_context(|| format!("could not delete {image_name}@{digest}"))
+            .error()
+            .log_err()?;
+        Ok(true)
+    }
+
+    /// Resolves `tag_name` to its digest with a `HEAD` and deletes the manifest, which removes
+    /// every tag sharing it. The other tags are resolved with `HEAD`s too, once the manifest is
+    /// known to be unprotected. `None` when the tag does not exist
+    pub async fn delete_tag_by_name(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        tag_name: &str,
+    ) -> ServiceResult<Option<TagDeletion>> {
+        let Some(digest) = registry_api_client
+            .find_manifest_digest(image_name, tag_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not resolve the digest of {image_name}:{tag_name}"))
+            .error()
+            .log_err()
+            .map_err(|err| Error::registry(Operation::TagDetails, &err))?
+        else {
+            return Ok(None);
+        };
+        if common::service::env::read_only() {
+            return Err(Error::ReadOnly);
+        }
+        let protected_digests = resolve_protected_digests(registry_api_client, image_name).await?;
+        if protected_digests.contains(&digest) {
+            return Err(Error::Protected);
+        }
+        let (tags, unresolved_tags) = get_digest_tags(registry_api_client, image_name, &digest)
+            .await
+            .unwrap_or_else(|_| (vec![tag_name.to_owned()], Vec::new()));
+        let deleted =
+            delete_unprotected_tag(registry_api_client, image_name, &digest, &protected_digests)
+                .await?;
+        Ok(Some(TagDeletion {
+            digest,
+            tags,
+            unresolved_tags,
+            deleted,
+        }))
+    }
+
+    /// Audit entry of a `delete_tag_by_name` call, a tag that does not exist is not recorded
+    pub async fn record_tag_deletion(
+        username: &str,
+        image_name: &str,
+        tag_name: &str,
+        result: &ServiceResult<Option<TagDeletion>>,
+    ) {
+        match result {
+            Ok(Some(deletion)) => {
+                audit::service::record(
+                    username,
+                    Deletion::Tag,
+                    image_name,
+                    Some(&deletion.digest),
+                    deletion.tags.clone(),
+                    deletion.unresolved_tags.clone(),
+                    deletion.deleted,
+                )
+                .await
+            }
+            Ok(None) => {}
+            Err(_) => {
+                audit::service::record(
+                    username,
+                    Deletion::Tag,
+                    image_name,
+                    None,
+                    vec![tag_name.to_owned()],
+                    Vec::new(),
+                    false,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Manifests of `image_name` once tagged that no tag references anymore and that still exist
+    /// on the registry
+    ///
+    /// Every tag is resolved, with the platform manifests of indexes and the referrers of each
+    /// manifest, so nothing reachable from a tag is reported. The registry API cannot list
+    /// manifests, so the candidates are the tag digests resolved by this instance since startup
+    pub async fn find_dangling_manifests(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<Vec<String>> {
+        let tags = get_image_tags(registry_api_client, image_name).await?;
+        let referenced_digests = futures::future::join_all(tags.iter().map(|tag| async move {
+            let manifest = registry_api_client
+                .manifest(image_name, tag)
+                .counted()
+                .await?;
+            let mut digests = vec![manifest.digest().to_owned()];
+            if let registry::dto::TagManifest::MultiArch {
+                platform_digests, ..
+            } = &manifest
+            {
+                digests.extend(platform_digests.iter().map(|(_, digest)| digest.clone()));
+            }
+            let referrers = registry_api_client
+                .referrers(image_name, manifest.digest())
+                .counted()
+                .await?
+                .unwrap_or_default();
+            digests.extend(referrers.into_iter().map(|referrer| referrer.digest));
+            anyhow::Ok(digests)
+        }))
+        .await
+        .into_iter()
+        .flatten_ok()
+        .collect::<anyhow::Result<HashSet<_>>>()
+        .with_context(|| format!("could not resolve the tags of {image_name}"))
+        .error()
+        .log_err()
+        .map_err(|err| Error::registry(Operation::TagDetails, &err))?;
+
+        let candidates = registry_api_client
+            .seen_digests(image_name)
+            .into_iter()
+            .filter(|digest| !referenced_digests.contains(digest))
+            .sorted()
+            .collect_vec();
+
+        let still_exist = futures::future::join_all(candidates.iter().map(|digest| {
+            registry_api_client
+                .manifest_exists(image_name, digest)
+                .counted()
+        }))
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("could not check the dangling manifests of {image_name}"))
+        .error()
+        .log_err()?;
+
+        Ok(candidates
+            .into_iter()
+            .zip(still_exist)
+            .filter_map(|(digest, exists)| exists.then_some(digest))
+            .collect())
+    }
+
+    /// Deletes every dangling manifest of `image_name`, a failed deletion does not stop the others
+    pub async fn delete_dangling_manifests(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<Vec<DeletionResult>> {
+        if common::service::env::read_only() {
+            return Err(Error::ReadOnly);
+        }
+        let digests = find_dangling_manifests(registry_api_client, image_name).await?;
+        let protected_digests = resolve_protected_digests(registry_api_client, image_name).await?;
+        let mut results = Vec::with_capacity(digests.len());
+        for digest in digests {
+            let deleted = delete_unprotected_tag(
+                registry_api_client,
+                image_name,
+                &digest,
+                &protected_digests,
+            )
+            .await
+            .is_ok_and(|deleted| deleted);
+            results.push(DeletionResult { digest, deleted });
+        }
+        Ok(results)
+    }
+
+    /// Resolves the creation date of every tag of `image_name` and selects the manifests whose
+    /// tags are all older than `days`. A manifest shared with a newer, undated or protected tag
+    /// is kept, the plan fails when a tag cannot be resolved as its manifest may be shared
+    pub async fn plan_retention(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        days: u32,
+    ) -> ServiceResult<RetentionPlan> {
+        let threshold = chrono::Duration::days(days.into());
+        let tags = get_image_tags(registry_api_client, image_name).await?;
+        let tags = futures::future::join_all(
+            tags.into_iter()
+                .map(|tag| get_tag(registry_api_client, image_name, tag)),
+        )
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("could not resolve every tag of {image_name}"))
+        .error()
+        .log_err()?;
+
+        let is_old = |tag: &Tag| {
+            tag.created_since
+                .is_some_and(|created_since| created_since > threshold)
+                && !common::service::is_protected_tag(&tag.name)
+        };
+        let kept_digests = tags
+            .iter()
+            .filter(|tag| !is_old(tag))
+            .map(|tag| tag.digest.as_str())
+            .collect::<HashSet<_>>();
+        let mut manifests = BTreeMap::<String, Vec<String>>::new();
+        let mut kept_tags = Vec::new();
+        for tag in tags.iter().filter(|tag| is_old(tag)) {
+            if kept_digests.contains(tag.digest.as_str()) {
+                kept_tags.push(tag.name.clone());
+            } else {
+                manifests
+                    .entry(tag.digest.clone())
+                    .or_default()
+                    .push(tag.name.clone());
+            }
+        }
+        kept_tags.sort();
+        Ok(RetentionPlan {
+            days,
+            manifests: manifests.into_iter().collect(),
+            kept_tags,
+        })
+    }
+
+    /// Deletes each `confirmed` manifest the retention plan still selects once, a failed
+    /// deletion does not stop the others. The results follow the manifests of the returned plan
+    pub async fn delete_old_tags(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        days: u32,
+        confirmed: &HashSet<String>,
+    ) -> ServiceResult<(RetentionPlan, Vec<DeletionResult>)> {
+        if common::service::env::read_only() {
+            return Err(Error::ReadOnly);
+        }
+        let plan = plan_retention(registry_api_client, image_name, days)
+            .await?
+            .confirmed(confirmed);
+        let protected_digests = resolve_protected_digests(registry_api_client, image_name).await?;
+        let mut results = Vec::with_capacity(plan.manifests.len());
+        for (digest, _) in &plan.manifests {
+            let deleted =
+                delete_unprotected_tag(registry_api_client, image_name, digest, &protected_digests)
+                    .await
+                    .is_ok_and(|deleted| deleted);
+            results.push(DeletionResult {
+                digest: digest.clone(),
+                deleted,
+            });
+        }
+        Ok((plan, results))
+    }
+
+    pub async fn get_image_tags(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<Vec<String>> {
+        Ok(registry_api_client
+            .tags(image_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not list the tags of {image_name}"))
+            .error()
+            .log_err()
+            .map_err(|err| Error::registry(Operation::TagDetails, &err))?
+            .tags
+            .unwrap_or_default())
+    }
+
+    /// Distinct digests of the tags, in tag order, each resolved with a `HEAD`. Tags that
+    /// cannot be resolved are returned apart
+    pub async fn get_image_digests(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<(Vec<String>, Vec<String>)> {
+        let (tag_digests, unresolved_tags) =
+            head_tag_digests(registry_api_client, image_name).await?;
+        let digests = tag_digests
+            .into_iter()
+            .map(|(_, digest)| digest)
+            .unique()
+            .collect();
+        Ok((digests, unresolved_tags))
+    }
+
+    /// Tags pointing to `digest`, each resolved with a `HEAD`. Tags that cannot be resolved are
+    /// returned apart, they may point to it too
+    pub async fn get_digest_tags(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        digest: &str,
+    ) -> ServiceResult<(Vec<String>, Vec<String>)> {
+        let (tag_digests, unresolved_tags) =
+            head_tag_digests(registry_api_client, image_name).await?;
+        let tags = tag_digests
+            .into_iter()
+            .filter(|(_, tag_digest)| tag_digest == digest)
+            .map(|(tag, _)| tag)
+            .collect();
+        Ok((tags, unresolved_tags))
+    }
+
+    async fn head_tag_digests(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+    ) -> ServiceResult<(Vec<(String, String)>, Vec<String>)> {
+        let tags = get_image_tags(registry_api_client, image_name).await?;
+        let digests = futures::future::join_all(tags.iter().map(|tag| {
+            registry_api_client
+                .manifest_digest(image_name, tag)
+                .counted()
+        }))
+        .await;
+
+        let mut unresolved_tags = Vec::new();
+        let tag_digests = tags
+            .into_iter()
+            .zip(digests)
+            .filter_map(|(tag, digest)| {
+                match digest
+                    .with_context(|| format!("could not resolve the digest of {image_name}:{tag}"))
+                    .warn()
+                    .log_err()
+                {
+                    Ok(digest) => Some((tag, digest)),
+                    Err(_) => {
+                        unresolved_tags.push(tag);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Ok((tag_digests, unresolved_tags))
+    }
+
+    pub async fn get_image_info(
+        registry_api_client: registry::api::Client,
+        image_name: &str,
+        pagination: PaginationQuery,
+        filter: &TagFilterQuery,
+    ) -> ServiceResult<ImageInfo> {
+        let tags = get_image_tags(&registry_api_client, image_name).await?;
+        if let Some(max_enriched_tags) = common::service::env::max_enriched_tags()
+            && tags.len() > max_enriched_tags
+        {
+            return get_page_image_info(
+                &registry_api_client,
+                image_name,
+                tags,
+                pagination,
+                filter,
+                Some(max_enriched_tags),
+            )
+            .await;
+        }
+        // Sorted by name without filters, the manifests of the other pages are not needed
+        let needs_every_manifest = filter.sort == TagSort::Created
+            || filter.arch.is_some()
+            || filter.created_within.is_some()
+            || filter.grouped;
+        if !needs_every_manifest {
+            return get_page_image_info(
+                &registry_api_client,
+                image_name,
+                tags,
+                pagination,
+                filter,
+                None,
+            )
+            .await;
+        }
+        let mut tags = futures::future::join_all(
+            tags.into_iter()
+                .map(|tag| get_tag(&registry_api_client, image_name, tag)),
+        )
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|err| Error::registry(Operation::TagDetails, &err))?;
+        sort_tags(&mut tags, filter.sort, filter.order());
+
+        let protected_digests = tags
+            .iter()
+            .filter(|tag| common::service::is_protected_tag(&tag.name))
+            .map(|tag| tag.digest.clone())
+            .collect::<HashSet<_>>();
+        for tag in &mut tags {
+            tag.protected = protected_digests.contains(&tag.digest);
+        }
+
+        let size = image_size(&tags);
+        let tag_count = tags.len();
+        let newest = tags.iter().filter_map(|tag| tag.created).max();
+        let oldest = tags.iter().filter_map(|tag| tag.created).min();
+
+        let architectures = tags
+            .iter()
+            .flat_map(|tag| tag.architectures.iter().cloned())
+            .sorted()
+            .dedup()
+            .collect();
+
+        let latest_release = latest_release(tags.iter().map(|tag| tag.name.as_str()));
+        if let Some(arch) = &filter.arch {
+            tags.retain(|tag| tag.architectures.iter().any(|a| a == arch));
+        }
+        // Tags without a creation date only show without this filter
+        if let Some(created_within) = filter.created_within {
+            let threshold = created_within.duration();
+            tags.retain(|tag| {
+                tag.created_since
+                    .is_some_and(|created_since| created_since <= threshold)
+            });
+        }
+        if filter.grouped {
+            tags = group_by_digest(tags);
+        }
+        classify_versions(&mut tags, latest_release.as_deref());
+
+        let tags = pagination.into_paginated(common::service::env::default_page_size(), &tags)?;
+
+        Ok(ImageInfo {
+            tags,
+            tag_count,
+            architectures,
+            size,
+            newest,
+            oldest,
+            partial: false,
+            page_only: false,
+        })
+    }
+
+    /// Tag names sorted by name or version and paginated before any manifest is fetched. With
+    /// `max_enriched_tags`, sorting by date, filtering by architecture and grouping are not
+    /// available, the first `max_enriched_tags` of the page are enriched, the others only get
+    /// their digest from a `HEAD`
+    async fn get_page_image_info(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        mut names: Vec<String>,
+        pagination: PaginationQuery,
+        filter: &TagFilterQuery,
+        max_enriched_tags: Option<usize>,
+    ) -> ServiceResult<ImageInfo> {
+        let tag_count = names.len();
+        let latest_release = latest_release(names.iter().map(String::as_str));
+        if filter.sort == TagSort::Semver {
+            names.sort_by(|a, b| compare_versions(a, b, filter.order()));
+        } else {
+            names.sort();
+            if filter.sort == TagSort::Name && filter.order() == SortOrder::Desc {
+                names.reverse();
+            }
+        }
+        let names = pagination.into_paginated(common::service::env::default_page_size(), &names)?;
+
+        let mut position = 0;
+        let mut tags = names
+            .map(|name| {
+                let enriched = max_enriched_tags.is_none_or(|max| position < max);
+                position += 1;
+                async move {
+                    let tag = if enriched {
+                        get_tag(registry_api_client, image_name, name.clone()).await
+                    } else {
+                        registry_api_client
+                            .manifest_digest(image_name, &name)
+                            .counted()
+                            .await
+                            .with_context(|| {
+                                format!("could not resolve the digest of {image_name}:{name}")
+                            })
+                            .map(|digest| Tag::deferred(name.clone(), digest))
+                    };
+                    tag.warn()
+                        .log_err()
+                        .unwrap_or_else(|_| Tag::unresolved(name))
+                }
+            })
+            .into_future()
+            .await;
+        // Protection of the other tags sharing these manifests is enforced on deletion
+        for tag in tags.data.iter_mut() {
+            tag.protected = common::service::is_protected_tag(&tag.name);
+        }
+        classify_versions(&mut tags.data, latest_release.as_deref());
+        // Choosing one fetches every manifest, the list is then complete
+        let architectures = if max_enriched_tags.is_none() {
+            tags.data
+                .iter()
+                .flat_map(|tag| tag.architectures.iter().cloned())
+                .sorted()
+                .dedup()
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(ImageInfo {
+            tags,
+            tag_count,
+            architectures,
+            size: ImageSize { unique: 0, sum: 0 },
+            newest: None,
+            oldest: None,
+            partial: max_enriched_tags.is_some(),
+            page_only: true,
+        })
+    }
+
+    /// Applied before pagination, tags without a creation date always come last, by name
+    fn sort_tags(tags: &mut [Tag], sort: TagSort, order: SortOrder) {
+        match sort {
+            TagSort::Created => tags.sort_by(|a, b| match (a.created, b.created) {
+                (Some(a_created), Some(b_created)) => order
+                    .apply(a_created.cmp(&b_created))
+                    .then_with(|| a.name.cmp(&b.name)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.name.cmp(&b.name),
+            }),
+            TagSort::Name => tags.sort_by(|a, b| order.apply(a.name.cmp(&b.name))),
+            TagSort::Semver => tags.sort_by(|a, b| compare_versions(&a.name, &b.name, order)),
+        }
+    }
+
+    /// Semantic version of a tag name, with an optional `v` prefix. The `1` and `1.2`
+    /// shorthands read as `1.0.0` and `1.2.0`
+    pub fn tag_version(name: &str) -> Option<semver::Version> {
+        let version = name.strip_prefix('v').unwrap_or(name);
+        if let Ok(version) = semver::Version::parse(version) {
+            return Some(version);
+        }
+        let mut numbers = version.split('.').map(|number| {
+            number
+                .parse::<u64>()
+                .ok()
+                .filter(|_| !number.starts_with('+'))
+        });
+        match (numbers.next(), numbers.next(), numbers.next()) {
+            (Some(Some(major)), None, None) => Some(semver::Version::new(major, 0, 0)),
+            (Some(Some(major)), Some(Some(minor)), None) => {
+                Some(semver::Version::new(major, minor, 0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Releases before prereleases, each ordered by version, other tags last by name whatever
+    /// the order
+    fn compare_versions(a: &str, b: &str, order: SortOrder) -> Ordering {
+        let rank = |version: &Option<semver::Version>| match version {
+            Some(version) if version.pre.is_empty() => 0,
+            Some(_) => 1,
+            None => 2,
+        };
+        let (a_version, b_version) = (tag_version(a), tag_version(b));
+        rank(&a_version)
+            .cmp(&rank(&b_version))
+            .then_with(|| order.apply(a_version.cmp(&b_version)))
+            .then_with(|| a.cmp(b))
+    }
+
+    /// Name of the highest version without prerelease among `names`
+    fn latest_release<'a>(names: impl Iterator<Item = &'a str>) -> Option<String> {
+        names
+            .filter_map(|name| tag_version(name).map(|version| (version, name)))
+            .filter(|(version, _)| version.pre.is_empty())
+            .max()
+            .map(|(_, name)| name.to_owned())
+    }
+
+    /// Fills `version` and `latest_release` of the listed tags
+    fn classify_versions(tags: &mut [Tag], latest_release: Option<&str>) {
+        for tag in tags {
+            tag.version = match tag_version(&tag.name) {
+                Some(version) if version.pre.is_empty() => TagVersion::Release,
+                Some(_) => TagVersion::Prerelease,
+                None => TagVersion::Other,
+            };
+            tag.latest_release = latest_release.is_some_and(|latest| {
+                tag.name == latest || tag.aliases.iter().any(|alias| alias == latest)
+            });
+        }
+    }
+
+    /// Keeps the first tag of each manifest, the following ones become its aliases
+    fn group_by_digest(tags: Vec<Tag>) -> Vec<Tag> {
+        let mut groups: Vec<Tag> = Vec::new();
+        let mut positions = HashMap::<String, usize>::new();
+        for tag in tags {
+            match positions.get(&tag.digest) {
+                Some(&position) => groups[position].aliases.push(tag.name),
+                None => {
+                    positions.insert(tag.digest.clone(), groups.len());
+                    groups.push(tag);
+                }
+            }
+        }
+        groups
+    }
+
+    /// Layer sizes come from the manifests, which the registry client caches, so this does not
+    /// cost additional registry calls
+    pub fn image_size(tags: &[Tag]) -> ImageSize {
+        let unique = tags
+            .iter()
+            .flat_map(|tag| &tag.layers)
+            .unique_by(|layer| &layer.digest)
+            .map(|layer| layer.size)
+            .sum();
+        let sum = tags.iter().filter_map(Tag::size).sum();
+        ImageSize { unique, sum }
+    }
+
+    /// `tag` as seen on one platform of its index, the platform list is kept to switch between
+    /// them
+    pub async fn get_platform_tag(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        tag: String,
+        platform: &str,
+    ) -> anyhow::Result<Tag> {
+        let registry::dto::TagManifest::MultiArch {
+            platform_digests, ..
+        } = registry_api_client
+            .manifest(image_name, &tag)
+            .counted()
+            .await
+            .with_context(|| format!("could not fetch the manifest of {image_name}:{tag}"))?
+        else {
+            return get_tag(registry_api_client, image_name, tag).await;
+        };
+        let digest = platform_digests
+            .iter()
+            .find(|(candidate, _)| candidate == platform)
+            .map(|(_, digest)| digest.as_str())
+            .with_context(|| format!("{image_name}:{tag} has no {platform} manifest"))?;
+        let manifest = registry_api_client
+            .platform_manifest(image_name, digest)
+            .counted()
+            .await
+            .with_context(|| {
+                format!("could not fetch the {platform} manifest of {image_name}:{tag}")
+            })?;
+        let mut platform_tag = tag_from_manifest(tag, manifest);
+        platform_tag.platforms = platform_digests
+            .into_iter()
+            .map(|(platform, _)| platform)
+            .collect();
+        Ok(platform_tag)
+    }
+
+    pub async fn get_tag(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        tag: String,
+    ) -> anyhow::Result<Tag> {
+        let manifest = registry_api_client
+            .manifest(image_name, &tag)
+            .counted()
+            .await
+            .with_context(|| format!("could not fetch the manifest of {image_name}:{tag}"))?;
+        Ok(tag_from_manifest(tag, manifest))
+    }
+
+    /// Both tags are fetched at once, each fetch still waits for one of the
+    /// `REGISTRY_MAX_CONCURRENT_REQUESTS` slots
+    pub async fn compare_tags(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        a: String,
+        b: String,
+        platform: Option<&str>,
+    ) -> anyhow::Result<(Tag, Tag)> {
+        let fetch = |tag: String| async move {
+            match platform {
+                Some(platform) => {
+                    get_platform_tag(registry_api_client, image_name, tag, platform).await
+                }
+                None => get_tag(registry_api_client, image_name, tag).await,
+            }
+        };
+        futures::try_join!(fetch(a), fetch(b))
+    }
+
+    pub fn layer_diff<'a>(a: &'a [Layer], b: &'a [Layer]) -> LayerDiff<'a> {
+        let a_digests = a.iter().map(|layer| &layer.digest).collect::<HashSet<_>>();
+        let b_digests = b.iter().map(|layer| &layer.digest).collect::<HashSet<_>>();
+        let (shared, removed) = a
+            .iter()
+            .partition(|layer| b_digests.contains(&layer.digest));
+        LayerDiff {
+            shared,
+            removed,
+            added: b
+                .iter()
+                .filter(|layer| !a_digests.contains(&layer.digest))
+                .collect(),
+        }
+    }
+
+    /// `KEY=value` entries by key, a key without `=` has an empty value
+    pub fn env_map(env: &[String]) -> BTreeMap<String, String> {
+        env.iter()
+            .map(|entry| {
+                let (key, value) = entry.split_once('=').unwrap_or((entry, ""));
+                (key.to_owned(), value.to_owned())
+            })
+            .collect()
+    }
+
+    /// Every key of `a` and `b` with its value on each side
+    pub fn entry_diff<'a>(
+        a: &'a BTreeMap<String, String>,
+        b: &'a BTreeMap<String, String>,
+    ) -> Vec<(&'a str, Option<&'a str>, Option<&'a str>)> {
+        a.keys()
+            .chain(b.keys())
+            .unique()
+            .sorted()
+            .map(|key| {
+                (
+                    key.as_str(),
+                    a.get(key).map(String::as_str),
+                    b.get(key).map(String::as_str),
+                )
+            })
+            .collect()
+    }
+
+    /// Fills the signing status of `tags`, each manifest is looked up once. Left to `None` when
+    /// the referrers API is unavailable
+    pub async fn look_up_signatures(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        tags: &mut [Tag],
+    ) {
+        let digests = tags
+            .iter()
+            .filter(|tag| !tag.error)
+            .map(|tag| tag.digest.clone())
+            .unique()
+            .collect::<Vec<_>>();
+        let statuses = futures::future::join_all(digests.into_iter().map(|digest| async move {
+            let status = signing_status(registry_api_client, image_name, &digest).await;
+            (digest, status)
+        }))
+        .await
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        for tag in tags {
+            tag.signing = statuses.get(&tag.digest).cloned().flatten();
+        }
+    }
+
+    async fn signing_status(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        digest: &str,
+    ) -> Option<SigningStatus> {
+        let referrers = registry_api_client
+            .referrers(image_name, digest)
+            .counted()
+            .await
+            .warn()
+            .log_err()
+            .ok()??;
+        let Some(signature) = referrers.into_iter().find(|referrer| {
+            referrer
+                .artifact_type
+                .as_deref()
+                .is_some_and(|artifact_type| SIGNATURE_ARTIFACT_TYPES.contains(&artifact_type))
+        }) else {
+            return Some(SigningStatus::Unsigned);
+        };
+        let (subject, issuer) =
+            signing_certificate(registry_api_client, image_name, &signature.digest)
+                .await
+                .map_or((None, None), |pem| certificate_identity(&pem));
+        Some(SigningStatus::Signed { subject, issuer })
+    }
+
+    /// PEM of the Fulcio certificate cosign stores as an annotation of the signature layer,
+    /// `None` for signatures made with a key
+    async fn signing_certificate(
+        registry_api_client: &registry::api::Client,
+        image_name: &str,
+        digest: &str,
+    ) -> Option<String> {
+        let (_, manifest) = registry_api_client
+            .raw_manifest(image_name, digest)
+            .counted()
+            .await
+            .ok()?;
+        let manifest = serde_json::from_slice::<serde_json::Value>(&manifest).ok()?;
+        manifest
+            .get("layers")?
+            .as_array()?
+            .iter()
+            .find_map(|layer| {
+                layer
+                    .get("annotations")?
+                    .get("dev.sigstore.cosign/certificate")?
+                    .as_str()
+                    .map(ToOwned::to_owned)
+            })
+    }
+
+    /// Subject and issuer of a keyless signature certificate, the subject is the first email
+    /// or URI of its alternative names
+    fn certificate_identity(pem: &str) -> (Option<String>, Option<String>) {
+        let Ok((_, pem)) = x509_parser::pem::parse_x509_pem(pem.as_bytes()) else {
+            return (None, None);
+        };
+        let Ok(certificate) = pem.parse_x509() else {
+            return (None, None);
+        };
+        let subject = certificate
+            .subject_alternative_name()
+            .ok()
+            .flatten()
+            .and_then(|names| {
+                names
+                    .value
+                    .general_names
+                    .iter()
+                    .find_map(|name| match name {
+                        GeneralName::RFC822Name(name) | GeneralName::URI(name) => {
+                            Some((*name).to_owned())
+                        }
+                        _ => None,
+                    })
+            });
+        let extension = |oid: &[u64]| {
+            let oid = Oid::from(oid).ok()?;
+            certificate
+                .get_extension_unique(&oid)
+                .ok()
+                .flatten()
+                .map(|extension| extension.value)
+        };
+        let issuer = extension(FULCIO_ISSUER_OID)
+            .and_then(|value| String::from_der(value).ok())
+            .map(|(_, issuer)| issuer)
+            .or_else(|| {
+                extension(FULCIO_LEGACY_ISSUER_OID)
+                    .and_then(|value| String::from_utf8(value.to_vec()).ok())
+            });
+        (subject, issuer)
+    }
+
+    fn tag_from_manifest(tag: String, manifest: registry::dto::TagManifest) -> Tag {
+        let layers = manifest.layers().to_vec();
+        let digest_mismatch = manifest.digest_mismatch();
+        let media_type = manifest.media_type().cloned();
+        match manifest {
+            registry::dto::TagManifest::Nominal {
+                digest,
+                created,
+                architecture,
+                history,
+                labels,
+                annotations,
+                env,
+                ..
+            } => Tag {
+                digest,
+                created,
+                created_since: created.map(|c| chrono::Utc::now() - c),
+                architectures: architecture.iter().cloned().collect(),
+                architecture,
+                artifact_type: Some(ArtifactType::Image),
+                media_type: media_type.clone(),
+                error: false,
+                name: tag,
+                layers,
+                protected: false,
+                aliases: Vec::new(),
+                platforms: Vec::new(),
+                platform_sizes: Vec::new(),
+                dockerfile: history
+                    .iter()
+                    .map(|command| dockerfile_line(command))
+                    .collect(),
+                labels,
+                annotations,
+                env,
+                deferred: false,
+                digest_mismatch,
+                version: TagVersion::Other,
+                latest_release: false,
+                signing: None,
+            },
+            registry::dto::TagManifest::MultiArch {
+                digest,
+                architectures,
+                platform_sizes,
+                created,
+                ..
+            } => Tag {
+                digest,
+                created,
+                created_since: created.map(|c| chrono::Utc::now() - c),
+                // Platforms are formatted as `os/architecture[/variant]`
+                architectures: architectures
+                    .iter()
+                    .filter_map(|platform| platform.split('/').nth(1))
+                    .map(ToOwned::to_owned)
+                    .unique()
+                    .collect(),
+                architecture: Some(architectures.join(", ")),
+                artifact_type: Some(ArtifactType::Image),
+                media_type: media_type.clone(),
+                error: false,
+                name: tag,
+                layers,
+                protected: false,
+                aliases: Vec::new(),
+                platforms: architectures,
+                platform_sizes,
+                dockerfile: Vec::new(),
+                labels: BTreeMap::new(),
+                annotations: BTreeMap::new(),
+                env: Vec::new(),
+                deferred: false,
+                digest_mismatch,
+                version: TagVersion::Other,
+                latest_release: false,
+                signing: None,
+            },
+            registry::dto::TagManifest::Artifact {
+                digest,
+                artifact_type,
+                ..
+            } => Tag {
+                digest,
+                created: None,
+                created_since: None,
+                architecture: None,
+                architectures: Vec::new(),
+                artifact_type: Some(artifact_type),
+                media_type: media_type.clone(),
+                error: false,
+                name: tag,
+                layers,
+                protected: false,
+                aliases: Vec::new(),
+                platforms: Vec::new(),
+                platform_sizes: Vec::new(),
+                dockerfile: Vec::new(),
+                labels: BTreeMap::new(),
+                annotations: BTreeMap::new(),
+                env: Vec::new(),
+                deferred: false,
+                digest_mismatch,
+                version: TagVersion::Other,
+                latest_release: false,
+                signing: None,
+            },
+            registry::dto::TagManifest::Error { digest } => Tag {
+                digest,
+                created: None,
+                created_since: None,
+                architecture: None,
+                architectures: Vec::new(),
+                artifact_type: None,
+                media_type,
+                error: true,
+                name: tag,
+                layers: Vec::new(),
+                protected: false,
+                aliases: Vec::new(),
+                platforms: Vec::new(),
+                platform_sizes: Vec::new(),
+                dockerfile: Vec::new(),
+                labels: BTreeMap::new(),
+                annotations: BTreeMap::new(),
+                env: Vec::new(),
+                deferred: false,
+                digest_mismatch,
+                version: TagVersion::Other,
+                latest_release: false,
+                signing: None,
+            },
+        }
+    }
+
+    /// `manifest.json`, `config.json` and `layers.txt` as a tar, with a file name made of the
+    /// image, the tag and the short digest. The manifest is fetched before answering so that a
+    /// missing tag is an error, the config is fetched once the manifest entry is sent. Indexes
+    /// have neither config nor layers
+    pub async fn tag_bundle(
+        registry_api_client: registry::api::Client,
+        image_name: String,
+        tag_name: &str,
+    ) -> anyhow::Result<(String, impl Stream<Item = anyhow::Result<Vec<u8>>> + use<>)> {
+        let (digest, manifest) = registry_api_client
+            .raw_manifest(&image_name, tag_name)
+            .counted()
+            .await
+            .with_context(|| format!("could not fetch the manifest of {image_name}:{tag_name}"))?;
+        let json = serde_json::from_slice::<serde_json::Value>(&manifest)?;
+        let config_digest = json
+            .get("config")
+            .and_then(|config| config.get("digest"))
+            .and_then(serde_json::Value::as_str)
+            .map(ToOwned::to_owned);
+        let layers = registry::dto::Layer::from_manifest(&json)
+            .into_iter()
+            .map(|layer| format!("{}\n", layer.digest))
+            .collect::<String>();
+
+        let short_digest = digest
+            .split_once(':')
+            .map_or(digest.as_str(), |(_, hex)| hex)
+            .chars()
+            .take(12)
+            .collect::<String>();
+        let filename = format!(
+            "{}_{tag_name}_{short_digest}.tar",
+            image_name.replace('/', "_")
+        );
+
+        let manifest_entry =
+            futures::stream::once(async move { tar_entry("manifest.json", &manifest) });
+        let config_entry = futures::stream::iter(config_digest).then(move |config_digest| {
+            let registry_api_client = registry_api_client.clone();
+            let image_name = image_name.clone();
+            async move {
+                let config = registry_api_client
+                    .blob(&image_name, &config_digest)
+                    .counted()
+                    .await
+                    .with_context(|| format!("could not fetch the config of {image_name}"))?;
+                tar_entry("config.json", &config)
+            }
+        });
+        let layers_entry = futures::stream::iter(
+            (!layers.is_empty()).then(|| tar_entry("layers.txt", layers.as_bytes())),
+        );
+        // A tar ends with two empty blocks
+        let end = futures::stream::once(async { Ok(vec![0; 2 * TAR_BLOCK]) });
+        Ok((
+            filename,
+            manifest_entry
+                .chain(config_entry)
+                .chain(layers_entry)
+                .chain(end),
+        ))
+    }
+
+    const TAR_BLOCK: usize = 512;
+
+    /// Header and content of a regular file, padded to whole blocks
+    fn tar_entry(path: &str, content: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut header = tar::Header::new_ustar();
+        header.set_path(path)?;
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(
+            chrono::Utc::now()
+                .timestamp()
+                .try_into()
+                .unwrap_or_default(),
+        );
+        header.set_cksum();
+        let mut entry = header.as_bytes().to_vec();
+        entry.extend_from_slice(content);
+        entry.resize(entry.len().next_multiple_of(TAR_BLOCK), 0);
+        Ok(entry)
+    }
+
+    /// Turns a history `created_by` into a Dockerfile instruction, classic builder commands are
+    /// `/bin/sh -c [#(nop)] ...` while BuildKit ones already start with the instruction
+    fn dockerfile_line(created_by: &str) -> String {
+        let command = created_by.trim();
+        let command = command
+            .strip_suffix("# buildkit")
+            .unwrap_or(command)
+            .trim_end();
+        // Classic builds with build args prefix the shell with `|<count> KEY=value ...`
+        let command = if command.starts_with('|') {
+            command
+                .find("/bin/sh -c ")
+                .map_or(command, |position| &command[position..])
+        } else {
+            command
+        };
+        match command.strip_prefix("/bin/sh -c ") {
+            Some(command) => match command.trim_start().strip_prefix("#(nop)") {
+                Some(instruction) => instruction.trim().to_owned(),
+                None => format!("RUN {}", command.trim()),
+            },
+            None => command.to_owned(),
+        }
+    }
+
+    /// Labels shown first on the tag page
+    pub const HIGHLIGHTED_LABELS: [&str; 4] = [
+        "org.opencontainers.image.source",
+        "org.opencontainers.image.revision",
+        "org.opencontainers.image.version",
+        "org.opencontainers.image.created",
+    ];
+
+    /// Highlighted labels in `HIGHLIGHTED_LABELS` order followed by the others, sorted by key
+    pub fn sorted_labels(labels: &BTreeMap<String, String>) -> Vec<(&str, &str, bool)> {
+        let highlighted = HIGHLIGHTED_LABELS.iter().filter_map(|key| {
+            labels
+                .get_key_value(*key)
+                .map(|(key, value)| (key.as_str(), value.as_str(), true))
+        });
+        let others = labels
+            .iter()
+            .filter(|(key, _)| !HIGHLIGHTED_LABELS.contains(&key.as_str()))
+            .map(|(key, value)| (key.as_str(), value.as_str(), false));
+        highlighted.chain(others).collect()
+    }
+
+    /// Browsable URL of the source and revision labels when the source points to a known forge
+    pub fn label_link(labels: &BTreeMap<String, String>, key: &str) -> Option<String> {
+        const FORGES: [&str; 4] = ["github.com", "gitlab.com", "bitbucket.org", "codeberg.org"];
+
+        let source = labels.get("org.opencontainers.image.source")?;
+        let source = source
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .replacen("git@", "https://", 1)
+            .replacen(".com:", ".com/", 1)
+            .replacen(".org:", ".org/", 1);
+        let forge = FORGES
+            .into_iter()
+            .find(|forge| source.starts_with(&format!("https://{forge}/")))?;
+        match key {
+            "org.opencontainers.image.source" => Some(source),
+            "org.opencontainers.image.revision" => {
+                let revision = labels.get(key)?;
+                let commit_path = match forge {
+                    "gitlab.com" => "-/commit",
+                    "bitbucket.org" => "commits",
+                    _ => "commit",
+                };
+                Some(format!("{source}/{commit_path}/{revision}"))
+            }
+            _ => None,
+        }
+    }
+
+    /// `VULN_SCAN_URL_TEMPLATE` with its `{image}`, `{tag}` and `{digest}` placeholders filled in
+    pub fn vuln_scan_url(image: &str, tag: &str, digest: &str) -> Option<String> {
+        common::service::env::vuln_scan_url_template().map(|template| {
+            template
+                .replace("{image}", image)
+                .replace("{tag}", tag)
+                .replace("{digest}", digest)
+        })
+    }
+}
+
+pub mod view {
+    use std::collections::BTreeMap;
+
+    use itertools::Itertools;
+    use maud::{Markup, html};
+
+    use crate::{
+        auth::dto::Role,
+        common::{
+            self,
+            dto::{DeleteAction, Flash, TableDensity},
+        },
+        i18n::service::{t, tf},
+        image::{
+            dto::{
+                CreatedWithin, DeletionResult, ImageInfo, RetentionPlan, SigningStatus, SortOrder,
+                Tag, TagColumn, TagFilterQuery, TagSort, TagVersion,
+            },
+            service,
+        },
+        registry::dto::{ArtifactType, Layer, MediaType},
+    };
+
+    const CLIPBOARD: &str = "\u{1F4CB}";
+    const LOCK: &str = "\u{1F512}";
+    const WARNING: &str = "\u{26A0}";
+    const CHECK: &str = "\u{2714}";
+    const DELETE_MANIFEST: &str =
+        "Deletes the manifest of this tag, every tag pointing to the same digest goes away too";
+    const SCHEMA1: &str =
+        "Deprecated manifest format, push the image again with a recent client to convert it";
+    const DIGEST_MISMATCH: &str = "The manifest or config received does not match its digest, a proxy or the registry storage may be corrupting content";
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn index(
+        role: Role,
+        image_name: &str,
+        info: &ImageInfo,
+        filter: &TagFilterQuery,
+        columns: &[TagColumn],
+        density: TableDensity,
+        delete_action: DeleteAction,
+        flash: Option<Flash>,
+    ) -> Markup {
+        const LEFT_ARROW: &str = "\u{1F850}";
+
+        let image_reference = common::service::image_reference(image_name);
+
+        let arch = filter.arch.as_deref();
+        let grouped = filter.grouped;
+        let pagination_params = filter.params();
+        let toggle_grouping = filter_href(
+            image_name,
+            &TagFilterQuery {
+                grouped: !grouped,
+                ..filter.clone()
+            },
+        );
+
+        common::view::page().title(&format!("{image_name} \u{2013} tags")).role(role).js("clipboard").js("row-details").maybe_flash(flash).content(html! {
             .d-flex .justify-content-between .m-2 {
                 .d-flex .align-items-center .gap-3 {
                     a .text-decoration-none href="/" { .fs-1 { (LEFT_ARROW) } }
-                    h1 { (image_name) " image tags" }
+                    h1 { (tf("image_tags", image_name)) }
+                    @if delete_action == DeleteAction::Allowed {
+                        a .btn .btn-outline-danger href=(format!("/{image_name}/dangling")) { "Dangling manifests" }
+                        form .input-group .w-auto .m-0 method="get" action=(format!("/{image_name}/retention")) {
+                            button .btn .btn-outline-danger type="submit" { "Delete tags older than" }
+                            input .form-control type="number" name="days" min="1" value="30" style="width: 5rem";
+                            span .input-group-text { "days" }
+                        }
+                    }
+                    a .btn .btn-outline-secondary href=(toggle_grouping) {
+                        @if grouped { "One row per tag" } @else { "One row per manifest" }
+                    }
+                    a .btn .btn-outline-secondary .active[filter.sort == TagSort::Semver] href=(filter_href(image_name, &filter.sorted_by(TagSort::Semver))) {
+                        "Sort by version"
+                        @if filter.sort == TagSort::Semver {
+                            (sort_arrow(filter.order()))
+                        }
+                    }
+                    @if !info.partial {
+                        .btn-group {
+                            @for created_within in CreatedWithin::ALL.map(Some).into_iter().chain([None]) {
+                                a .btn .btn-outline-secondary .active[filter.created_within == created_within] href=(filter_href(image_name, &TagFilterQuery { created_within, ..filter.clone() })) {
+                                    (created_within.map_or("all", CreatedWithin::as_str))
+                                }
+                            }
+                        }
+                    }
+                    (common::view::density_toggle(density, &filter_href(image_name, filter)))
+                    .btn-group {
+                        button .btn .btn-outline-secondary type="button" title="Copy every tag name, one per line" data-clipboard-url=(format!("/{image_name}/tags.txt")) onclick="copyFromUrl(this)" { (t("copy_all_tags")) }
+                        button .btn .btn-outline-secondary type="button" title="Copy every tagged digest, one per line" data-clipboard-url=(format!("/{image_name}/digests.txt")) onclick="copyFromUrl(this)" { (t("copy_all_digests")) }
+                    }
+                }
+                details .align-self-center {
+                    summary { (t("columns")) }
+                    form .d-flex .flex-column .m-0 method="post" action=(format!("/{image_name}/columns")) {
+                        @for column in TagColumn::available(delete_action) {
+                            label .d-flex .gap-2 {
+                                input type="checkbox" name=(column.key()) checked[columns.contains(&column)];
+                                (column.label())
+                            }
+                        }
+                        button .btn .btn-sm .btn-primary type="submit" { (t("apply")) }
+                    }
+                }
+                @if !info.architectures.is_empty() {
+                    form .d-flex .align-items-center .gap-2 .m-0 method="get" action=(format!("/{image_name}")) {
+                        input type="hidden" name="size" value=(info.tags.size);
+                        @for (name, value) in filter.params() {
+                            @if name != "arch" {
+                                input type="hidden" name=(name) value=(value);
+                            }
+                        }
+                        select .form-select name="arch" onchange="this.form.submit()" {
+                            option value="" selected[arch.is_none()] { (t("all_architectures")) }
+                            @for architecture in &info.architectures {
+                                option value=(architecture) selected[arch == Some(architecture.as_str())] { (architecture) }
+                            }
+                        }
+                    }
+                }
+                @if !info.tags.is_empty() && info.tags.need_pagination() {
+                    .d-flex .justify-content-end {
+                        (common::view::pagination_fragment(&info.tags, &format!("/{image_name}"), &pagination_params))
+                    }
+                }
+            }
+
+            @if info.partial {
+                .alert .alert-info .mx-2 {
+                    "This image has " (info.tag_count) " tags, more than MAX_ENRICHED_TAGS: tags are sorted by name or version, "
+                    "the architecture and creation filters and grouping are unavailable and only the first tags of the page show their details."
                 }
-                @if !info.tags.is_empty() && info.tags.need_pagination() {
-                    .d-flex .justify-content-end {
-                        (pagination_fragment(&info.tags, &format!("/{image_name}")))
+            }
+            .card .mx-2 .mb-2 {
+                .card-body .d-flex .flex-wrap .gap-4 {
+                    div { .text-muted { "Tags" } .fs-5 { (info.tag_count) } }
+                    @if !info.page_only {
+                        div { .text-muted { "Unique size" } .fs-5 { (format_size(info.size.unique)) } }
+                        div { .text-muted { "Sum of tags" } .fs-5 { (format_size(info.size.sum)) } }
+                        div { .text-muted { "Last push" } .fs-5 { (format_date(info.newest)) } }
+                        div { .text-muted { "Oldest tag" } .fs-5 { (format_date(info.oldest)) } }
+                        div { .text-muted { "Architectures" } .fs-5 { @if info.architectures.is_empty() { "?" } @else { (info.architectures.join(", ")) } } }
                     }
                 }
             }
 
             @if info.tags.is_empty() {
-                p { "No tags found." }
+                p { (t("no_tags")) }
             } @else {
 
-                table .table .table-striped .table-bordered .table-hover .table-responsive .m-0 .align-middle .text-center {
-                    thead {
+                table .table .table-striped .table-bordered .table-hover .table-responsive .m-0 .align-middle .text-center .table-sm[density == TableDensity::Compact] {
+                    thead .sticky-header {
                         tr {
-                            th { "Creation Date" }
-                            th { "Tag" }
-                            th { "Digest" }
-                            th { "Architecture" }
-                            th { "Action" }
+                            @for column in columns {
+                                @match column.sort() {
+                                    Some(sort) => th {
+                                        a .text-decoration-none href=(filter_href(image_name, &filter.sorted_by(sort))) {
+                                            (column.label())
+                                            @if filter.sort == sort {
+                                                (sort_arrow(filter.order()))
+                                            }
+                                        }
+                                    },
+                                    None => th { (column.label()) },
+                                }
+                            }
                         }
                     }
                     tbody {
-                        @for tag in info.tags.iter() {
-                            tr {
-                                td { (tag.created.map(|date| format!("{}", date.format("%Y-%m-%d %H:%M:%S"))).as_deref().unwrap_or("?")) " (" (tag.created_since.map(format_duration).as_deref().unwrap_or("?")) " ago)"}
-                                td {
-                                    .d-flex .align-items-center .justify-content-between {
-                                        (tag.name)
-                                        .copy-button role="button" onclick="copyToClipboard(this)" data-image=(format!("{registry_host}/{image_name}:{}", tag.name)) { (CLIPBOARD) }
-                                    }
+                        @for (index, tag) in info.tags.iter().enumerate() {
+                            @if let Some(section) = version_section(filter, index.checked_sub(1).and_then(|previous| info.tags.data.get(previous)), tag) {
+                                tr {
+                                    td .text-muted .text-start colspan=(columns.len()) { (section) }
                                 }
-                                td .text-danger[tag.error] { (tag.digest) }
-                                td { (tag.architecture.as_deref().unwrap_or("?")) }
-                                td {
-                                    form .m-0 method="post" action=(format!("/{image_name}/delete/{}", tag.digest)) {
-                                        button .btn .btn-danger type="submit" { "Delete" }
-                                    }
+                            }
+                            tr data-row-details=[tag.deferred.then(|| format!("/{image_name}/tag/{}/row-details", tag.name))] {
+                                @for column in columns {
+                                    (tag_cell(*column, tag, image_name, &image_reference, delete_action))
                                 }
                             }
                         }
@@ -224,40 +2604,783 @@ pub mod view {
                 }
                 @if info.tags.need_pagination() {
                     .d-flex .justify-content-end .mx-2 {
-                        (pagination_fragment(&info.tags, &format!("/{image_name}")))
+                        (common::view::pagination_fragment(&info.tags, &format!("/{image_name}"), &pagination_params))
+                    }
+                }
+            }
+        }).call()
+    }
+
+    fn sort_arrow(order: SortOrder) -> &'static str {
+        match order {
+            SortOrder::Asc => " \u{25B2}",
+            SortOrder::Desc => " \u{25BC}",
+        }
+    }
+
+    /// Heading of the group `tag` opens when sorting by version, releases need none
+    fn version_section(
+        filter: &TagFilterQuery,
+        previous: Option<&Tag>,
+        tag: &Tag,
+    ) -> Option<&'static str> {
+        if filter.sort != TagSort::Semver
+            || previous.is_some_and(|previous| previous.version == tag.version)
+        {
+            return None;
+        }
+        match tag.version {
+            TagVersion::Release => None,
+            TagVersion::Prerelease => Some("Pre-releases"),
+            TagVersion::Other => Some("Other tags"),
+        }
+    }
+
+    fn tag_badges(tag: &Tag) -> Markup {
+        html! {
+            @if tag.media_type.as_ref().is_some_and(MediaType::is_schema1) {
+                span .badge .text-bg-danger title=(SCHEMA1) { "legacy schema1" }
+            }
+            @if tag.latest_release {
+                span .badge .text-bg-success { "latest release" }
+            }
+            @if tag.version == TagVersion::Prerelease {
+                span .badge .text-bg-warning { "pre-release" }
+            }
+        }
+    }
+
+    fn filter_href(image_name: &str, filter: &TagFilterQuery) -> String {
+        let params = filter.params();
+        if params.is_empty() {
+            format!("/{image_name}")
+        } else {
+            format!(
+                "/{image_name}?{}",
+                serde_urlencoded::to_string(params).unwrap_or_default()
+            )
+        }
+    }
+
+    /// Hover text of a signed tag, the identity is only known for keyless signatures
+    fn signer(subject: Option<&str>, issuer: Option<&str>) -> String {
+        match (subject, issuer) {
+            (Some(subject), Some(issuer)) => format!("Signed by {subject}, issued by {issuer}"),
+            (Some(subject), None) => format!("Signed by {subject}"),
+            (None, Some(issuer)) => format!("Signed, issued by {issuer}"),
+            (None, None) => "Signed".to_owned(),
+        }
+    }
+
+    fn tag_cell(
+        column: TagColumn,
+        tag: &Tag,
+        image_name: &str,
+        image_reference: &str,
+        delete_action: DeleteAction,
+    ) -> Markup {
+        match column {
+            column if column.needs_manifest() && tag.deferred => {
+                html! {
+                    td data-column=(column.key()) { a .text-muted href=(format!("/{image_name}/tag/{}", tag.name)) { "Load details" } }
+                }
+            }
+            TagColumn::Created
+            | TagColumn::Type
+            | TagColumn::MediaType
+            | TagColumn::Architecture
+            | TagColumn::Size => detail_cell(column, tag),
+            TagColumn::Tag if !tag.aliases.is_empty() => html! {
+                td {
+                    .d-flex .flex-wrap .gap-1 .justify-content-center {
+                        @for name in std::iter::once(&tag.name).chain(&tag.aliases) {
+                            a .badge .text-bg-secondary .text-decoration-none href=(format!("/{image_name}/tag/{name}")) { (name) }
+                        }
+                        (tag_badges(tag))
+                    }
+                }
+            },
+            TagColumn::Tag => html! {
+                td {
+                    .d-flex .align-items-center .justify-content-between {
+                        .d-flex .align-items-center .gap-1 {
+                            a href=(format!("/{image_name}/tag/{}", tag.name)) { (tag.name) }
+                            (tag_badges(tag))
+                        }
+                        .copy-button role="button" onclick="copyToClipboard(this)" data-clipboard=(format!("{image_reference}:{}", tag.name)) { (CLIPBOARD) }
+                    }
+                }
+            },
+            TagColumn::Digest => html! {
+                td .text-danger[tag.error || tag.digest_mismatch] {
+                    @if tag.digest_mismatch {
+                        span title=(DIGEST_MISMATCH) { (WARNING) " " }
+                    }
+                    @if tag.digest.is_empty() {
+                        span title="The manifest of this tag could not be fetched" { "?" }
+                    } @else {
+                        (tag.digest)
+                    }
+                }
+            },
+            TagColumn::Pull => html! {
+                td .text-start {
+                    @for command in pull_commands(image_reference, &tag.name, &tag.digest).into_iter().take(if tag.digest.is_empty() { 1 } else { 2 }) {
+                        .d-flex .align-items-center .justify-content-between .gap-2 {
+                            code { (command) }
+                            .copy-button role="button" onclick="copyToClipboard(this)" data-clipboard=(command) { (CLIPBOARD) }
+                        }
+                    }
+                }
+            },
+            TagColumn::Signed => html! {
+                td {
+                    @match &tag.signing {
+                        Some(SigningStatus::Signed { subject, issuer }) => span .text-success title=(signer(subject.as_deref(), issuer.as_deref())) { (CHECK) },
+                        Some(SigningStatus::Unsigned) => span .text-secondary title="No cosign signature" { "-" },
+                        None => {},
+                    }
+                }
+            },
+            TagColumn::Scan => html! {
+                td {
+                    @if let Some(url) = service::vuln_scan_url(image_name, &tag.name, &tag.digest) {
+                        a .btn .btn-sm .btn-outline-secondary href=(url) target="_blank" rel="noopener" { "Scan" }
+                    }
+                }
+            },
+            // Nothing to delete without a digest
+            TagColumn::Action if tag.digest.is_empty() => html! { td {} },
+            TagColumn::Action => html! {
+                td {
+                    @match delete_action {
+                        DeleteAction::Allowed | DeleteAction::Disabled if tag.protected => {
+                            span .d-inline-block tabindex="0" title="Tag is protected" {
+                                button .btn .btn-danger type="button" disabled { (LOCK) " " (t("delete")) }
+                            }
+                        },
+                        DeleteAction::Allowed if !tag.aliases.is_empty() => form .m-0 method="post" action=(format!("/{image_name}/delete/{}", tag.digest)) onsubmit=(shared_manifest_confirmation(tag)) {
+                            button .btn .btn-danger type="submit" title=(DELETE_MANIFEST) { (t("delete")) }
+                        },
+                        DeleteAction::Allowed => form .m-0 method="post" action=(format!("/{image_name}/delete/{}", tag.digest)) {
+                            button .btn .btn-danger type="submit" title=(DELETE_MANIFEST) { (t("delete")) }
+                        },
+                        DeleteAction::Disabled => (common::view::disabled_delete_button(t("delete"))),
+                        DeleteAction::Hidden => {},
+                    }
+                }
+            },
+        }
+    }
+
+    /// Cells built from the manifest and config, `data-column` lets the row details script put
+    /// them in place of the placeholders of a deferred row
+    fn detail_cell(column: TagColumn, tag: &Tag) -> Markup {
+        // Artifacts other than images have no architecture
+        let architecture = tag
+            .artifact_type
+            .is_none_or(|artifact_type| artifact_type == ArtifactType::Image)
+            .then(|| tag.architecture.as_deref().unwrap_or("?"));
+        html! {
+            td data-column=(column.key()) {
+                @match column {
+                    TagColumn::Created => {
+                        (format_date(tag.created)) " (" (tag.created_since.map_or_else(|| "?".to_owned(), |since| tf("ago", format_duration(since)))) ")"
+                    },
+                    TagColumn::Type => (tag.artifact_type.map_or("?", ArtifactType::as_str)),
+                    TagColumn::MediaType => (media_type_label(tag)),
+                    TagColumn::Architecture => (architecture.unwrap_or_default()),
+                    TagColumn::Size => (tag.size().map(format_size).as_deref().unwrap_or("?")),
+                    _ => {},
+                }
+            }
+        }
+    }
+
+    /// Schema 1 manifests are flagged, Docker stopped pulling them
+    fn media_type_label(tag: &Tag) -> Markup {
+        html! {
+            @match &tag.media_type {
+                Some(media_type) if media_type.is_schema1() => span .badge .text-bg-danger title=(SCHEMA1) { (WARNING) " " (media_type.label()) },
+                Some(media_type) => (media_type.label()),
+                None => "?",
+            }
+        }
+    }
+
+    /// Detail cells of a deferred row, fetched by `row-details.js`
+    pub fn row_details(tag: &Tag) -> Markup {
+        html! {
+            @for column in TagColumn::ALL.into_iter().filter(|column| column.needs_manifest()) {
+                (detail_cell(column, tag))
+            }
+        }
+    }
+
+    /// `platform` is set when `tag` describes one platform of a multi-platform tag
+    pub fn tag_detail(
+        role: Role,
+        image_name: &str,
+        tag: &Tag,
+        platform: Option<&str>,
+        new_tag: Option<&str>,
+    ) -> Markup {
+        const LEFT_ARROW: &str = "\u{1F850}";
+
+        let image_reference = common::service::image_reference(image_name);
+
+        common::view::page().role(role).js("clipboard").content(html! {
+            .d-flex .align-items-center .gap-3 .m-2 {
+                a .text-decoration-none href=(format!("/{image_name}")) { .fs-1 { (LEFT_ARROW) } }
+                h1 { (image_name) ":" (tag.name) }
+                a .btn .btn-sm .btn-outline-secondary .ms-auto href=(format!("/{image_name}/compare?{}", serde_urlencoded::to_string([("a", &tag.name)]).unwrap_or_default())) { "Compare with another tag" }
+            }
+            .mx-2 {
+                @if !tag.platforms.is_empty() {
+                    .d-flex .flex-wrap .align-items-center .gap-2 .mb-2 {
+                        "Platform:"
+                        a .btn .btn-sm .btn-primary[platform.is_none()] .btn-outline-primary[platform.is_some()] href=(format!("/{image_name}/tag/{}", tag.name)) { "Index" }
+                        @for candidate in &tag.platforms {
+                            @let selected = platform == Some(candidate.as_str());
+                            a .btn .btn-sm .btn-primary[selected] .btn-outline-primary[!selected] href=(format!("/{image_name}/tag/{}?{}", tag.name, serde_urlencoded::to_string([("platform", candidate)]).unwrap_or_default())) { (candidate) }
+                        }
+                    }
+                }
+                table .table .table-bordered .align-middle {
+                    tbody {
+                        tr {
+                            th { "Digest" }
+                            td .text-danger[tag.error || tag.digest_mismatch] {
+                                code { (tag.digest) }
+                                @if tag.digest_mismatch {
+                                    div { (WARNING) " " (DIGEST_MISMATCH) }
+                                }
+                            }
+                        }
+                        tr { th { "Type" } td { (tag.artifact_type.map_or("?", ArtifactType::as_str)) } }
+                        tr {
+                            th { "Manifest" }
+                            td {
+                                (media_type_label(tag))
+                                @if let Some(media_type) = &tag.media_type {
+                                    " " code .text-muted { (media_type.media_type) }
+                                }
+                            }
+                        }
+                        tr { th { "Architecture" } td { (tag.architecture.as_deref().unwrap_or("?")) } }
+                        tr { th { "Creation Date" } td { (format_date(tag.created)) } }
+                        tr {
+                            th { "Size" }
+                            td {
+                                (tag.size().map(format_size).as_deref().unwrap_or("?"))
+                                @if !tag.platform_sizes.is_empty() {
+                                    span .text-muted {
+                                        " ("
+                                        (platform_size_breakdown(tag))
+                                        ")"
+                                    }
+                                }
+                            }
+                        }
+                        tr { th { "Layers" } td { (tag.layers.len()) } }
+                        @if let Some(url) = service::vuln_scan_url(image_name, &tag.name, &tag.digest) {
+                            tr { th { "Vulnerabilities" } td { a href=(url) target="_blank" rel="noopener" { "Scan report" } } }
+                        }
+                    }
+                }
+                @if !tag.error {
+                    a .btn .btn-sm .btn-outline-secondary .mb-2 href=(format!("/{image_name}/tag/{}/bundle.tar", tag.name)) download {
+                        "Download manifest + config"
+                    }
+                }
+                h2 .fs-4 { "Pull" }
+                (command_list(&pull_commands(&image_reference, &tag.name, &tag.digest)))
+                @if !tag.error {
+                    h2 .fs-4 { "Signing" }
+                    (command_list(&cosign_commands(&image_reference, &tag.digest)))
+                    h2 .fs-4 { "Kubernetes" }
+                    p .text-muted {
+                        "Pods pulling from a private registry reference a secret in "
+                        code { "imagePullSecrets" } ", created once per namespace."
+                    }
+                    (command_list(&kubernetes_snippets(&image_reference, &tag.name, &tag.digest)))
+                    h2 .fs-4 { "Copy to a new tag" }
+                    p .text-muted {
+                        "Registries cannot rename or retag server-side, these commands push a new tag pointing at the same manifest."
+                    }
+                    form .d-flex .align-items-center .gap-2 .mb-2 method="get" action=(format!("/{image_name}/tag/{}", tag.name)) {
+                        @if let Some(platform) = platform {
+                            input type="hidden" name="platform" value=(platform);
+                        }
+                        input .form-control .w-auto type="text" name="new_tag" placeholder="New tag" value=(new_tag.unwrap_or_default());
+                        button .btn .btn-sm .btn-outline-primary type="submit" { "Fill in" }
+                    }
+                    (command_list(&retag_commands(&image_reference, &tag.digest, new_tag.unwrap_or("<new-tag>"))))
+                }
+                @if !tag.labels.is_empty() {
+                    h2 .fs-4 { "Config labels" }
+                    p .text-muted { "Set at build time, stored in the image config." }
+                    (label_table(&tag.labels))
+                }
+                @if !tag.annotations.is_empty() {
+                    h2 .fs-4 { "Manifest annotations" }
+                    p .text-muted { "Stored in the manifest itself, often set when pushing." }
+                    (label_table(&tag.annotations))
+                }
+                @if tag.artifact_type == Some(ArtifactType::Image) {
+                    h2 .fs-4 { "Dockerfile" }
+                    @if !tag.platforms.is_empty() && platform.is_none() {
+                        p .text-muted { "Select a platform to see how it was built." }
+                    } @else if tag.dockerfile.is_empty() {
+                        p .text-muted { "The image config has no build history." }
+                    } @else {
+                        p .text-muted { "Approximation rebuilt from the image history, base image layers included." }
+                        pre .border .rounded .p-2 { code { (tag.dockerfile.join("\n")) } }
+                    }
+                }
+            }
+        }).call()
+    }
+
+    fn label_table(labels: &BTreeMap<String, String>) -> Markup {
+        html! {
+            table .table .table-bordered .table-sm .align-middle {
+                tbody {
+                    @for (key, value, highlighted) in service::sorted_labels(labels) {
+                        tr .table-primary[highlighted] {
+                            th .fw-normal[!highlighted] { code { (key) } }
+                            td {
+                                .d-flex .align-items-center .justify-content-between .gap-2 {
+                                    @match service::label_link(labels, key) {
+                                        Some(url) => a href=(url) target="_blank" rel="noopener" { (value) },
+                                        None => span { (value) },
+                                    }
+                                    .copy-button role="button" onclick="copyToClipboard(this)" data-clipboard=(value) { (CLIPBOARD) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn command_list(commands: &[String]) -> Markup {
+        html! {
+            @for command in commands {
+                .d-flex .align-items-center .gap-2 .mb-1 {
+                    code { (command) }
+                    .copy-button role="button" onclick="copyToClipboard(this)" data-clipboard=(command) { (CLIPBOARD) }
+                }
+            }
+        }
+    }
+
+    pub fn dangling_manifests(
+        role: Role,
+        image_name: &str,
+        digests: &[String],
+        delete_action: DeleteAction,
+    ) -> Markup {
+        const LEFT_ARROW: &str = "\u{1F850}";
+
+        common::view::page().role(role).content(html! {
+            .d-flex .align-items-center .gap-3 .m-2 {
+                a .text-decoration-none href=(format!("/{image_name}")) { .fs-1 { (LEFT_ARROW) } }
+                h1 { (image_name) " dangling manifests" }
+            }
+            .mx-2 {
+                @if digests.is_empty() {
+                    p { "No dangling manifests found." }
+                } @else {
+                    p { "The following manifests are no longer referenced by any tag and will be deleted:" }
+                    ul {
+                        @for digest in digests {
+                            li { code { (digest) } }
+                        }
+                    }
+                    .alert .alert-warning {
+                        "Storage is only reclaimed once the registry garbage collector has run."
+                    }
+                    form .d-flex .gap-2 method="post" action=(format!("/{image_name}/dangling/delete")) {
+                        a .btn .btn-secondary href=(format!("/{image_name}")) { "Cancel" }
+                        @if delete_action == DeleteAction::Allowed {
+                            button .btn .btn-outline-secondary type="submit" formaction=(format!("/{image_name}/dangling/delete?dry_run=1")) { "Dry run" }
+                        }
+                        @match delete_action {
+                            DeleteAction::Allowed => button .btn .btn-danger type="submit" { "Delete " (digests.len()) " manifest(s)" },
+                            DeleteAction::Disabled => (common::view::disabled_delete_button(&format!("Delete {} manifest(s)", digests.len()))),
+                            DeleteAction::Hidden => {},
+                        }
+                    }
+                }
+            }
+        }).call()
+    }
+
+    pub fn retention(
+        role: Role,
+        image_name: &str,
+        plan: &RetentionPlan,
+        delete_action: DeleteAction,
+    ) -> Markup {
+        const LEFT_ARROW: &str = "\u{1F850}";
+
+        let tag_count = plan
+            .manifests
+            .iter()
+            .map(|(_, tags)| tags.len())
+            .sum::<usize>();
+        common::view::page().role(role).content(html! {
+            .d-flex .align-items-center .gap-3 .m-2 {
+                a .text-decoration-none href=(format!("/{image_name}")) { .fs-1 { (LEFT_ARROW) } }
+                h1 { (image_name) " tags older than " (plan.days) " days" }
+            }
+            .mx-2 {
+                @if plan.manifests.is_empty() {
+                    p { "No tag is older than " (plan.days) " days." }
+                } @else {
+                    p { "The following manifests and their tags will be deleted:" }
+                    ul {
+                        @for (digest, tags) in &plan.manifests {
+                            li { code { (digest) } " " (tags.join(", ")) }
+                        }
+                    }
+                }
+                @if !plan.kept_tags.is_empty() {
+                    p .text-muted {
+                        "Kept because a newer, undated or protected tag shares their manifest: "
+                        (plan.kept_tags.join(", "))
+                    }
+                }
+                @if !plan.manifests.is_empty() {
+                    .alert .alert-warning {
+                        "Storage is only reclaimed once the registry garbage collector has run."
+                    }
+                    form .d-flex .gap-2 method="post" action=(format!("/{image_name}/retention/delete")) {
+                        input type="hidden" name="days" value=(plan.days);
+                        input type="hidden" name="digests" value=(plan.manifests.iter().map(|(digest, _)| digest.as_str()).join(","));
+                        a .btn .btn-secondary href=(format!("/{image_name}")) { "Cancel" }
+                        @if delete_action == DeleteAction::Allowed {
+                            button .btn .btn-outline-secondary type="submit" formaction=(format!("/{image_name}/retention/delete?dry_run=1")) { "Dry run" }
+                        }
+                        @let label = format!("Delete {tag_count} tag(s), {} manifest(s)", plan.manifests.len());
+                        @match delete_action {
+                            DeleteAction::Allowed => button .btn .btn-danger type="submit" { (label) },
+                            DeleteAction::Disabled => (common::view::disabled_delete_button(&label)),
+                            DeleteAction::Hidden => {},
+                        }
+                    }
+                }
+            }
+        }).call()
+    }
+
+    pub fn compare(
+        role: Role,
+        image_name: &str,
+        tags: &[String],
+        a: Option<&str>,
+        b: Option<&str>,
+        platform: Option<&str>,
+        comparison: Option<&(Tag, Tag)>,
+    ) -> Markup {
+        const LEFT_ARROW: &str = "\u{1F850}";
+
+        let compare_href = |platform: Option<&str>| {
+            let params = [("a", a), ("b", b), ("platform", platform)]
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value?)))
+                .collect::<Vec<_>>();
+            format!(
+                "/{image_name}/compare?{}",
+                serde_urlencoded::to_string(params).unwrap_or_default()
+            )
+        };
+        common::view::page().role(role).content(html! {
+            .d-flex .align-items-center .gap-3 .m-2 {
+                a .text-decoration-none href=(format!("/{image_name}")) { .fs-1 { (LEFT_ARROW) } }
+                h1 { "Compare " (image_name) " tags" }
+            }
+            .mx-2 {
+                form .d-flex .flex-wrap .align-items-center .gap-2 .mb-3 method="get" action=(format!("/{image_name}/compare")) {
+                    (tag_select("a", tags, a))
+                    "\u{2192}"
+                    (tag_select("b", tags, b))
+                    button .btn .btn-sm .btn-primary type="submit" { "Compare" }
+                }
+                @if let Some((a, b)) = comparison {
+                    @let platforms = a.platforms.iter().chain(&b.platforms).unique().collect::<Vec<_>>();
+                    @if !platforms.is_empty() {
+                        .d-flex .flex-wrap .align-items-center .gap-2 .mb-2 {
+                            "Platform:"
+                            a .btn .btn-sm .btn-primary[platform.is_none()] .btn-outline-primary[platform.is_some()] href=(compare_href(None)) { "Index" }
+                            @for candidate in platforms {
+                                @let selected = platform == Some(candidate.as_str());
+                                a .btn .btn-sm .btn-primary[selected] .btn-outline-primary[!selected] href=(compare_href(Some(candidate))) { (candidate) }
+                            }
+                        }
+                    }
+                    table .table .table-bordered .table-sm .align-middle {
+                        thead { tr { th {} th { (a.name) } th { (b.name) } } }
+                        tbody {
+                            tr { th { "Digest" } td { code { (a.digest) } } td { code { (b.digest) } } }
+                            tr { th { "Creation Date" } td { (format_date(a.created)) } td { (format_date(b.created)) } }
+                            tr {
+                                th { "Size" }
+                                td { (a.size().map(format_size).as_deref().unwrap_or("?")) }
+                                td {
+                                    (b.size().map(format_size).as_deref().unwrap_or("?"))
+                                    @if let (Some(a_size), Some(b_size)) = (a.size(), b.size()) {
+                                        span .text-muted { " (" (size_delta(a_size, b_size)) ")" }
+                                    }
+                                }
+                            }
+                            tr { th { "Layers" } td { (a.layers.len()) } td { (b.layers.len()) } }
+                        }
+                    }
+                    h2 .fs-4 { "Layers" }
+                    @let diff = service::layer_diff(&a.layers, &b.layers);
+                    p {
+                        (diff.shared.len()) " shared (" (format_size(diff.shared.iter().map(|layer| layer.size).sum())) "), "
+                        span .text-danger { (diff.removed.len()) " only in " (a.name) " (" (format_size(diff.removed.iter().map(|layer| layer.size).sum())) ")" } ", "
+                        span .text-success { (diff.added.len()) " only in " (b.name) " (" (format_size(diff.added.iter().map(|layer| layer.size).sum())) ")" }
+                    }
+                    table .table .table-bordered .table-sm .align-middle {
+                        thead { tr { th { (a.name) } th { (b.name) } } }
+                        tbody {
+                            @for index in 0..a.layers.len().max(b.layers.len()) {
+                                tr {
+                                    (layer_cell(a.layers.get(index), &b.layers, "table-danger"))
+                                    (layer_cell(b.layers.get(index), &a.layers, "table-success"))
+                                }
+                            }
+                        }
                     }
+                    @let (a_env, b_env) = (service::env_map(&a.env), service::env_map(&b.env));
+                    (entry_diff_section("Environment", &a.name, &b.name, &service::entry_diff(&a_env, &b_env)))
+                    (entry_diff_section("Config labels", &a.name, &b.name, &service::entry_diff(&a.labels, &b.labels)))
                 }
             }
         }).call()
     }
 
-    fn pagination_fragment<T>(pagination: &Paginated<T>, prefix: &str) -> Markup {
-        let page = pagination.page;
-        let size = pagination.size;
-        let total_pages = pagination.total_pages();
+    fn tag_select(name: &str, tags: &[String], selected: Option<&str>) -> Markup {
+        html! {
+            select .form-select .form-select-sm .w-auto name=(name) aria-label="Tag" required {
+                option value="" selected[selected.is_none()] disabled { "Tag" }
+                @for tag in tags {
+                    option selected[selected == Some(tag.as_str())] { (tag) }
+                }
+            }
+        }
+    }
+
+    /// Shared layers are muted, the others are highlighted with `class`
+    fn layer_cell(layer: Option<&Layer>, other_layers: &[Layer], class: &str) -> Markup {
+        html! {
+            @match layer {
+                Some(layer) => {
+                    @let shared = other_layers.iter().any(|other| other.digest == layer.digest);
+                    td class=(if shared { "text-muted" } else { class }) title=(layer.digest) {
+                        code .text-reset { (layer.digest.get(..19).unwrap_or(&layer.digest)) }
+                        " " (format_size(layer.size))
+                    }
+                }
+                None => td {},
+            }
+        }
+    }
 
+    fn entry_diff_section(
+        title: &str,
+        a_name: &str,
+        b_name: &str,
+        entries: &[(&str, Option<&str>, Option<&str>)],
+    ) -> Markup {
         html! {
-            .d-flex .my-2 .gap-2 {
-                @if page > 0 {
-                    a .btn .btn-primary href=(format!("{prefix}?page={}&size={}", pagination.previous(), size)) { "Previous" }
+            h2 .fs-4 { (title) }
+            @if entries.is_empty() {
+                p .text-muted { "None on either tag." }
+            } @else {
+                table .table .table-bordered .table-sm .align-middle {
+                    thead { tr { th {} th { (a_name) } th { (b_name) } } }
+                    tbody {
+                        @for (key, a_value, b_value) in entries {
+                            @let class = match (a_value, b_value) {
+                                (Some(a_value), Some(b_value)) if a_value == b_value => "text-muted",
+                                (Some(_), Some(_)) => "table-warning",
+                                (Some(_), None) => "table-danger",
+                                (None, _) => "table-success",
+                            };
+                            tr class=(class) {
+                                th .fw-normal { code .text-reset { (key) } }
+                                td .text-break { (a_value.unwrap_or("")) }
+                                td .text-break { (b_value.unwrap_or("")) }
+                            }
+                        }
+                    }
                 }
-                span .align-self-center { (page + 1) " / " (total_pages + 1) }
-                @if page < total_pages {
-                    a .btn .btn-primary href=(format!("{prefix}?page={}&size={}", pagination.next(), size)) { "Next" }
+            }
+        }
+    }
+
+    fn size_delta(a_size: u64, b_size: u64) -> String {
+        if b_size >= a_size {
+            format!("+{}", format_size(b_size - a_size))
+        } else {
+            format!("\u{2212}{}", format_size(a_size - b_size))
+        }
+    }
+
+    pub fn deletion_results(image_name: &str, results: &[DeletionResult]) -> Markup {
+        const LEFT_ARROW: &str = "\u{1F850}";
+
+        common::view::page().role(Role::Admin).content(html! {
+            .d-flex .align-items-center .gap-3 .m-2 {
+                a .text-decoration-none href=(format!("/{image_name}")) { .fs-1 { (LEFT_ARROW) } }
+                h1 { (image_name) " deletion results" }
+            }
+            @if results.is_empty() {
+                p .mx-2 { "Nothing was deleted." }
+            } @else {
+                table .table .table-striped .table-bordered .align-middle .text-center {
+                    thead {
+                        tr {
+                            th { "Digest" }
+                            th { "Result" }
+                        }
+                    }
+                    tbody {
+                        @for result in results {
+                            tr {
+                                td { code { (result.digest) } }
+                                @if result.deleted {
+                                    td .text-success { "Deleted" }
+                                } @else {
+                                    td .text-danger { "Failed" }
+                                }
+                            }
+                        }
+                    }
                 }
             }
+        }).call()
+    }
+
+    fn pull_commands(image_reference: &str, tag: &str, digest: &str) -> [String; 2] {
+        [
+            format!("docker pull {image_reference}:{tag}"),
+            format!("docker pull {image_reference}@{digest}"),
+        ]
+    }
+
+    /// Signing by digest so that the signature cannot end up on another manifest if the tag moves
+    fn cosign_commands(image_reference: &str, digest: &str) -> [String; 2] {
+        [
+            format!("cosign sign {image_reference}@{digest}"),
+            format!("cosign verify {image_reference}@{digest}"),
+        ]
+    }
+
+    /// `image:` fields of a container spec, then the pull secret they need
+    fn kubernetes_snippets(image_reference: &str, tag: &str, digest: &str) -> [String; 4] {
+        let host = common::service::env::registry_image_host();
+        [
+            format!("image: {image_reference}:{tag}"),
+            format!("image: {image_reference}@{digest}"),
+            format!(
+                "kubectl create secret docker-registry regcred --docker-server={host} --docker-username=<username> --docker-password=<password>"
+            ),
+            "imagePullSecrets: [{ name: regcred }]".to_owned(),
+        ]
+    }
+
+    /// `--all` and `imagetools` keep every platform when `digest` is an index
+    fn retag_commands(image_reference: &str, digest: &str, new_tag: &str) -> [String; 2] {
+        [
+            format!(
+                "docker buildx imagetools create -t {image_reference}:{new_tag} {image_reference}@{digest}"
+            ),
+            format!(
+                "skopeo copy --all docker://{image_reference}@{digest} docker://{image_reference}:{new_tag}"
+            ),
+        ]
+    }
+
+    /// Deleting a manifest removes every tag pointing to it
+    fn shared_manifest_confirmation(tag: &Tag) -> String {
+        let names = std::iter::once(&tag.name)
+            .chain(&tag.aliases)
+            .map(|name| name.replace(['\'', '\\'], ""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "return confirm('Deleting a tag deletes its manifest, the following tags share it and will all be deleted: {names}')"
+        )
+    }
+
+    /// `linux/amd64: 120 MiB, linux/arm64: 118 MiB, attestations: 12 KiB`, `?` for a platform
+    /// that could not be fetched. The total counts every fetched entry of the index, what the
+    /// platforms leave of it belongs to the attestations so the parts add up to the total
+    fn platform_size_breakdown(tag: &Tag) -> String {
+        let platforms_size = tag
+            .platform_sizes
+            .iter()
+            .filter_map(|(_, size)| *size)
+            .sum::<u64>();
+        let attestations_size = tag
+            .size()
+            .unwrap_or_default()
+            .saturating_sub(platforms_size);
+        tag.platform_sizes
+            .iter()
+            .map(|(platform, size)| {
+                format!(
+                    "{platform}: {}",
+                    size.map_or_else(|| "?".to_owned(), format_size)
+                )
+            })
+            .chain(
+                (attestations_size > 0)
+                    .then(|| format!("attestations: {}", format_size(attestations_size))),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn format_date(date: Option<chrono::DateTime<chrono::Utc>>) -> String {
+        date.map_or_else(
+            || "?".to_owned(),
+            |date| date.format("%Y-%m-%d %H:%M:%S").to_string(),
+        )
+    }
+
+    pub fn format_size(bytes: u64) -> String {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        #[allow(clippy::cast_precision_loss)]
+        let mut size = bytes as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{bytes} {}", UNITS[unit])
+        } else {
+            format!("{size:.1} {}", UNITS[unit])
         }
     }
 
-    fn format_duration(duration: chrono::Duration) -> String {
+    pub fn format_duration(duration: chrono::Duration) -> String {
         if duration.num_hours() > 23 {
-            format!("{} day(s)", duration.num_days())
+            tf("duration.days", duration.num_days())
         } else if duration.num_minutes() > 59 {
-            format!("{} hour(s)", duration.num_hours())
+            tf("duration.hours", duration.num_hours())
         } else if duration.num_seconds() > 59 {
-            format!("{} minute(s)", duration.num_minutes())
+            tf("duration.minutes", duration.num_minutes())
         } else {
-            format!("{} second(s)", duration.num_seconds())
+            tf("duration.seconds", duration.num_seconds())
         }
     }
 }
@@ -1,20 +1,51 @@
+mod api;
+mod audit;
 mod auth;
 mod common;
+mod diagnostics;
 mod error;
 mod home;
+mod i18n;
 mod image;
+mod metrics;
+mod registries;
 mod registry;
 mod router;
 
-use std::env;
+use std::{
+    env,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
 use tracing::info;
 
-use crate::router::create_router;
+use crate::{
+    registries::dto::RegistryConfig,
+    router::{create_multi_registry_router, create_router},
+};
+
+/// Pages only need to know the catalog changed, a few pending events are enough
+const CATALOG_EVENTS_CAPACITY: usize = 4;
 
 #[derive(Clone)]
 pub struct AppState {
     registry_api_client: registry::api::Client,
+    started_at: Instant,
+    /// Time of the last catalog fetch that succeeded
+    last_catalog_fetch: home::service::LastCatalogFetch,
+    /// Filled when `HOME_REFRESH_SECS` is set, or once by `WARM_CACHE_ON_START`
+    catalog_snapshot: home::service::SharedCatalogSnapshot,
+    catalog_events: home::service::CatalogEvents,
+    /// Deletions of every tag of an image, kept for their result page
+    deletion_jobs: home::service::DeletionJobs,
+    /// Turns `true` once a shutdown signal is received, open event streams end on it
+    shutdown: tokio::sync::watch::Receiver<bool>,
+    /// Refreshed every `REGISTRY_PROBE_SECS`, pages show the maintenance page while unreachable
+    registry_probe: common::service::SharedRegistryProbe,
+    /// Probed once at startup with `PROBE_DELETIONS`, delete actions are disabled in the views
+    /// when `Some(false)`
+    deletions_enabled: Option<bool>,
 }
 
 #[tokio::main]
@@ -22,21 +53,100 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt().init();
     common::service::env::check();
 
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        return run_command(&args).await;
+    }
+
     info!("Registry Host: {}", common::service::env::registry_host());
     info!(
         "Registry Username: {}",
         common::service::env::registry_username()
     );
+    info!(
+        "Registry connection pool: max idle per host {:?}, idle timeout {:?}s, HTTP/1.1 only {}",
+        common::service::env::registry_pool_max_idle_per_host(),
+        common::service::env::registry_pool_idle_timeout_secs(),
+        common::service::env::registry_http1_only()
+    );
+    info!(
+        "Registry timeouts: catalog {:?}s, manifests {:?}s",
+        common::service::env::registry_catalog_timeout_secs(),
+        common::service::env::registry_manifest_timeout_secs()
+    );
 
-    let registry_api_client = registry::api::Client::new(
-        common::service::env::registry_host(),
-        common::service::env::registry_username(),
-        common::service::env::registry_password(),
-    )?;
+    if common::service::env::registry_proxy_disabled() {
+        info!("Registry proxy: disabled");
+    } else if let Some(proxy_url) = common::service::env::registry_http_proxy() {
+        // The URL may embed credentials, only its origin is logged
+        let proxy_origin = reqwest::Url::parse(proxy_url).map_or_else(
+            |_| proxy_url.to_owned(),
+            |url| url.origin().ascii_serialization(),
+        );
+        info!(
+            "Registry proxy: {proxy_origin}, authenticated {}, bypassed for {}",
+            common::service::env::registry_http_proxy_credentials().is_some()
+                || proxy_url.contains('@'),
+            common::service::env::registry_no_proxy().unwrap_or("no host")
+        );
+    } else if let Some(no_proxy) = common::service::env::registry_no_proxy() {
+        info!(
+            "Registry proxy: from the HTTP_PROXY and HTTPS_PROXY variables if set, bypassed for {no_proxy}"
+        );
+    } else {
+        info!("Registry proxy: from the HTTP_PROXY, HTTPS_PROXY and NO_PROXY variables if set");
+    }
 
-    let app_state = AppState {
-        registry_api_client,
-    };
+    info!(
+        "Registry flavor: {}",
+        common::service::env::registry_flavor().as_str()
+    );
+
+    let extra_headers = common::service::env::registry_extra_headers();
+    if !extra_headers.is_empty() {
+        // Values may be API keys, only the names are logged
+        info!(
+            "Registry extra headers: {}",
+            extra_headers
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if common::service::env::trusted_proxy() {
+        info!("Trusting X-Forwarded-Proto and X-Forwarded-Host for cookies and redirects");
+    }
+
+    if common::service::env::cookie_secure() {
+        info!(
+            "Cookies are marked Secure, the explorer must be reached over HTTPS (set COOKIE_SECURE=false for plain HTTP)"
+        );
+    } else {
+        tracing::warn!("COOKIE_SECURE is disabled, the session cookie can leak over plain HTTP");
+    }
+
+    info!(
+        "Auth cookie: SameSite={}, domain {}",
+        common::service::env::cookie_same_site(),
+        common::service::env::cookie_domain().unwrap_or("of the request host")
+    );
+
+    let (shutdown_sender, shutdown) = tokio::sync::watch::channel(false);
+    // One unreachable registry must not delay the start by a timeout per registry
+    let registries = common::service::env::registries();
+    let mut app_states = registries
+        .iter()
+        .zip(
+            futures::future::try_join_all(
+                registries
+                    .iter()
+                    .map(|registry| registry_state(registry, shutdown.clone())),
+            )
+            .await?,
+        )
+        .collect::<Vec<_>>();
 
     let listen_addr = env::var("LISTEN_ADDR").expect("LISTEN_ADDR");
     let listen_port = env::var("LISTEN_PORT").expect("LISTEN_PORT");
@@ -45,10 +155,256 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let listener = tokio::net::TcpListener::bind(&binding_addr).await?;
 
-    let router = create_router().with_state(app_state);
+    if common::service::env::warm_cache_on_start() {
+        info!("Warming the catalog cache in the background");
+        for (_, app_state) in &app_states {
+            home::service::spawn_cache_warming(
+                app_state.registry_api_client.clone(),
+                app_state.catalog_snapshot.clone(),
+                app_state.last_catalog_fetch.clone(),
+            );
+        }
+    }
+
+    let router = if app_states.len() == 1 {
+        let (_, app_state) = app_states.remove(0);
+        create_router(app_state)
+    } else {
+        info!(
+            "Registries: {}",
+            app_states
+                .iter()
+                .map(|(registry, _)| registry.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        create_multi_registry_router(Arc::new(
+            app_states
+                .into_iter()
+                .map(|(registry, app_state)| (registry, create_router(app_state)))
+                .collect(),
+        ))
+    };
 
     info!("Listening on {binding_addr}");
-    axum::serve(listener, router).await?;
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            info!("Shutting down");
+            let _ = shutdown_sender.send(true);
+        })
+        .await?;
 
     Ok(())
 }
+
+/// `delete-tag <image> <tag>` deletes the manifest of a tag, with every tag sharing it, from the
+/// first registry instead of serving
+async fn run_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [command, image_name, tag_name] = args else {
+        return Err("usage: docker-registry-explorer delete-tag <image> <tag>".into());
+    };
+    if command != "delete-tag" {
+        return Err(format!("unknown command {command}, the only one is delete-tag").into());
+    }
+    let registry = &common::service::env::registries()[0];
+    let registry_api_client =
+        registry::api::Client::new(&registry.url, &registry.username, &registry.password)?;
+    let result =
+        image::service::delete_tag_by_name(&registry_api_client, image_name, tag_name).await;
+    let username = env::var("USER").unwrap_or_else(|_| "cli".to_owned());
+    image::service::record_tag_deletion(&username, image_name, tag_name, &result).await;
+    match result {
+        Ok(Some(image::dto::TagDeletion {
+            digest,
+            tags,
+            deleted: true,
+            ..
+        })) => {
+            println!("Deleted {digest}, tags {}", tags.join(", "));
+            Ok(())
+        }
+        Ok(Some(deletion)) => {
+            Err(format!("the registry refused to delete {}", deletion.digest).into())
+        }
+        Ok(None) => Err(format!("{image_name}:{tag_name} does not exist").into()),
+        Err(err) => Err(format!("could not delete {image_name}:{tag_name}: {err}").into()),
+    }
+}
+
+/// Connects to `registry` and starts its background tasks
+async fn registry_state(
+    registry: &'static RegistryConfig,
+    shutdown: tokio::sync::watch::Receiver<bool>,
+) -> Result<AppState, Box<dyn std::error::Error>> {
+    let registry_api_client =
+        registry::api::Client::new(&registry.url, &registry.username, &registry.password)?;
+
+    // A wrong `REGISTRY_API_PREFIX` makes every page fail, better to say so right away
+    let startup_ping = registry_api_client.ping().await;
+    let registry_probe = Arc::new(RwLock::new(common::service::RegistryProbe {
+        reachable: startup_ping.is_ok(),
+        checked_at: chrono::Utc::now(),
+    }));
+    match startup_ping {
+        Ok(Some(api_version)) if api_version == "registry/2.0" => info!(
+            "Registry API found at {}{}",
+            registry.url,
+            common::service::env::registry_api_prefix()
+        ),
+        Ok(api_version) => tracing::warn!(
+            "{}{}/ answered without announcing Docker-Distribution-Api-Version: registry/2.0 ({}), it may not be a distribution registry",
+            registry.url,
+            common::service::env::registry_api_prefix(),
+            api_version.as_deref().unwrap_or("no header")
+        ),
+        Err(err) => tracing::error!(
+            "Registry API not reachable at {}{}/ ({err}), check REGISTRY_HOST and REGISTRY_API_PREFIX",
+            registry.url,
+            common::service::env::registry_api_prefix()
+        ),
+    }
+
+    let deletions_enabled = if common::service::env::read_only() {
+        info!("Read-only mode, deletions are disabled");
+        Some(false)
+    } else if common::service::env::probe_deletions() {
+        let deletions_enabled = registry_api_client
+            .deletions_enabled()
+            .await
+            .inspect_err(|err| {
+                tracing::warn!("Could not detect whether the registry allows deletions: {err}");
+            })
+            .ok();
+        info!("{} deletions enabled: {deletions_enabled:?}", registry.name);
+        deletions_enabled
+    } else {
+        None
+    };
+
+    let app_state = AppState {
+        deletions_enabled,
+        registry_api_client,
+        started_at: Instant::now(),
+        last_catalog_fetch: Arc::default(),
+        catalog_snapshot: Arc::default(),
+        catalog_events: tokio::sync::broadcast::channel(CATALOG_EVENTS_CAPACITY).0,
+        deletion_jobs: Arc::default(),
+        shutdown,
+        registry_probe,
+    };
+
+    common::service::spawn_registry_probe(
+        app_state.registry_api_client.clone(),
+        app_state.registry_probe.clone(),
+        Duration::from_secs(common::service::env::registry_probe_secs()),
+    );
+
+    if let Some(home_refresh_secs) = common::service::env::home_refresh_secs() {
+        info!("Home page catalog refreshed every {home_refresh_secs}s");
+        home::service::spawn_catalog_refresh(
+            app_state.registry_api_client.clone(),
+            app_state.catalog_snapshot.clone(),
+            app_state.last_catalog_fetch.clone(),
+            app_state.catalog_events.clone(),
+            Duration::from_secs(home_refresh_secs),
+        );
+    }
+
+    Ok(app_state)
+}
+
+/// Ctrl+C, or SIGTERM as sent by `docker stop`
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod test_support {
+    use std::{
+        sync::{Arc, Once, RwLock},
+        time::Instant,
+    };
+
+    use axum::Router;
+
+    use crate::{AppState, auth, common, registry, router::create_router};
+
+    pub const USERNAME: &str = "explorer";
+    pub const PASSWORD: &str = "test-password";
+
+    /// The configuration is read from the environment once per process, every test sees the
+    /// same values
+    pub fn init_env() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            // SAFETY: runs once, before any test reads the environment
+            unsafe {
+                std::env::set_var("REGISTRY_HOST", "registry.test");
+                std::env::set_var("REGISTRY_USERNAME", "user");
+                std::env::set_var("REGISTRY_PASSWORD", "pass");
+                std::env::set_var("LISTEN_ADDR", "127.0.0.1");
+                std::env::set_var("LISTEN_PORT", "0");
+                std::env::set_var("STATIC_DIR", "static");
+                std::env::set_var("EXPLORER_USERNAME", USERNAME);
+                std::env::set_var("EXPLORER_PASSWORD", PASSWORD);
+                std::env::set_var("HIDDEN_REPOSITORIES", "cache/*");
+                std::env::set_var("PROTECTED_TAGS", "release-*");
+                std::env::set_var("FAVICON_PATH", "static/missing-favicon.ico");
+            }
+        });
+    }
+
+    impl AppState {
+        /// Freshly started explorer talking to `registry_url`, deletions enabled
+        pub fn for_tests(registry_url: &str) -> Self {
+            init_env();
+            Self {
+                registry_api_client: registry::api::Client::for_tests(registry_url),
+                started_at: Instant::now(),
+                last_catalog_fetch: Arc::default(),
+                catalog_snapshot: Arc::default(),
+                catalog_events: tokio::sync::broadcast::channel(1).0,
+                deletion_jobs: Arc::default(),
+                shutdown: tokio::sync::watch::channel(false).1,
+                registry_probe: Arc::new(RwLock::new(common::service::RegistryProbe {
+                    reachable: true,
+                    checked_at: chrono::Utc::now(),
+                })),
+                deletions_enabled: Some(true),
+            }
+        }
+    }
+
+    pub fn app(registry_url: &str) -> Router {
+        init_env();
+        create_router(AppState::for_tests(registry_url))
+    }
+
+    /// `Cookie` header of a logged in admin
+    pub fn admin_cookie() -> String {
+        init_env();
+        format!(
+            "{}={}",
+            auth::service::AUTH_TOKEN_COOKIE_NAME,
+            auth::service::hash_credentials(USERNAME, PASSWORD)
+        )
+    }
+}
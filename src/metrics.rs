@@ -0,0 +1,152 @@
+pub mod dto {
+    /// Page whose rendering drives registry calls, the `page` label of the metrics
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum Page {
+        Home,
+        Image,
+        Tag,
+    }
+
+    impl Page {
+        pub const ALL: [Self; 3] = [Self::Home, Self::Image, Self::Tag];
+
+        pub const fn label(self) -> &'static str {
+            match self {
+                Self::Home => "home",
+                Self::Image => "image",
+                Self::Tag => "tag",
+            }
+        }
+    }
+}
+
+pub mod handler {
+    use axum::{
+        extract::{Request, State},
+        http::header,
+        middleware::Next,
+        response::{IntoResponse, Response},
+    };
+
+    use crate::metrics::{dto::Page, service};
+
+    /// Prometheus text exposition, unauthenticated like `/health`
+    pub async fn index() -> impl IntoResponse {
+        (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            service::render(),
+        )
+    }
+
+    /// Attributes the registry calls made while rendering the route to `page`
+    pub async fn track(State(page): State<Page>, request: Request, next: Next) -> Response {
+        service::track_render(page, next.run(request)).await
+    }
+}
+
+pub mod service {
+    use std::{
+        cell::Cell,
+        fmt::Write,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use crate::metrics::dto::Page;
+
+    struct PageCounters {
+        renders: AtomicU64,
+        registry_calls: AtomicU64,
+    }
+
+    impl PageCounters {
+        const fn new() -> Self {
+            Self {
+                renders: AtomicU64::new(0),
+                registry_calls: AtomicU64::new(0),
+            }
+        }
+    }
+
+    static PAGE_COUNTERS: [PageCounters; Page::ALL.len()] = [
+        PageCounters::new(),
+        PageCounters::new(),
+        PageCounters::new(),
+    ];
+
+    /// Background refreshes and API calls
+    static UNATTRIBUTED_REGISTRY_CALLS: AtomicU64 = AtomicU64::new(0);
+
+    tokio::task_local! {
+        static RENDER_REGISTRY_CALLS: Cell<u64>;
+    }
+
+    /// Runs `render` counting the registry calls it makes, calls made by spawned tasks are not
+    /// attributed to the page
+    pub async fn track_render<F: Future>(page: Page, render: F) -> F::Output {
+        RENDER_REGISTRY_CALLS
+            .scope(Cell::new(0), async {
+                let output = render.await;
+                let counters = &PAGE_COUNTERS[page as usize];
+                counters.renders.fetch_add(1, Ordering::Relaxed);
+                counters
+                    .registry_calls
+                    .fetch_add(RENDER_REGISTRY_CALLS.with(Cell::get), Ordering::Relaxed);
+                output
+            })
+            .await
+    }
+
+    fn count_registry_call() {
+        if RENDER_REGISTRY_CALLS
+            .try_with(|calls| calls.set(calls.get() + 1))
+            .is_err()
+        {
+            UNATTRIBUTED_REGISTRY_CALLS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[easy_ext::ext(RegistryCallExt)]
+    impl<F: Future> F {
+        /// Counts the registry client call of the services once awaited, for the page being
+        /// rendered if any
+        pub async fn counted(self) -> F::Output {
+            count_registry_call();
+            self.await
+        }
+    }
+
+    pub fn render() -> String {
+        let mut metrics = String::new();
+        let _ = writeln!(
+            metrics,
+            "# HELP explorer_registry_calls_total Registry client calls of the services by the page that drove them"
+        );
+        let _ = writeln!(metrics, "# TYPE explorer_registry_calls_total counter");
+        for page in Page::ALL {
+            let calls = PAGE_COUNTERS[page as usize]
+                .registry_calls
+                .load(Ordering::Relaxed);
+            let _ = writeln!(
+                metrics,
+                "explorer_registry_calls_total{{page=\"{}\"}} {calls}",
+                page.label()
+            );
+        }
+        let _ = writeln!(
+            metrics,
+            "explorer_registry_calls_total{{page=\"none\"}} {}",
+            UNATTRIBUTED_REGISTRY_CALLS.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(metrics, "# HELP explorer_page_renders_total Rendered pages");
+        let _ = writeln!(metrics, "# TYPE explorer_page_renders_total counter");
+        for page in Page::ALL {
+            let _ = writeln!(
+                metrics,
+                "explorer_page_renders_total{{page=\"{}\"}} {}",
+                page.label(),
+                PAGE_COUNTERS[page as usize].renders.load(Ordering::Relaxed)
+            );
+        }
+        metrics
+    }
+}
@@ -0,0 +1,138 @@
+pub mod dto {
+    use serde::Deserialize;
+
+    /// A browsed registry, `REGISTRY_HOST` or one of the numbered `REGISTRY_<n>_HOST`
+    pub struct RegistryConfig {
+        /// Shown in the selector and kept in the selection cookie
+        pub name: String,
+        /// With an explicit scheme and without trailing slash
+        pub url: String,
+        pub username: String,
+        pub password: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct SelectRegistryForm {
+        pub registry: String,
+    }
+}
+
+pub mod handler {
+    use axum::{
+        Form,
+        extract::{Request, State},
+        response::{Redirect, Response},
+    };
+    use axum_extra::extract::CookieJar;
+
+    use crate::{
+        auth::middleware::Authenticated,
+        registries::{
+            dto::SelectRegistryForm,
+            service::{self, CookieJarRegistryExt, RegistryRouters},
+        },
+    };
+
+    /// Pages of one registry make no sense on another, the selection starts over at home
+    pub async fn select(
+        _: Authenticated,
+        cookie_jar: CookieJar,
+        Form(SelectRegistryForm { registry }): Form<SelectRegistryForm>,
+    ) -> (CookieJar, Redirect) {
+        (cookie_jar.set_registry(&registry), Redirect::to("/"))
+    }
+
+    pub async fn dispatch(
+        State(routers): State<RegistryRouters>,
+        cookie_jar: CookieJar,
+        request: Request,
+    ) -> Response {
+        service::dispatch(&routers, cookie_jar.registry(), request).await
+    }
+}
+
+pub mod service {
+    use std::sync::Arc;
+
+    use axum::{Router, extract::Request, response::Response};
+    use axum_extra::extract::{
+        CookieJar,
+        cookie::{Cookie, SameSite},
+    };
+    use joy_error::ResultInfallibleExt;
+    use tower::ServiceExt;
+
+    use crate::{common::service::env, registries::dto::RegistryConfig};
+
+    pub const REGISTRY_COOKIE_NAME: &str = "registry";
+
+    /// One router per configured registry, in the `env::registries` order
+    pub type RegistryRouters = Arc<Vec<(&'static RegistryConfig, Router)>>;
+
+    tokio::task_local! {
+        static SELECTED_REGISTRY: &'static RegistryConfig;
+    }
+
+    /// Registry the current request browses, `None` when a single registry is configured
+    pub fn selected() -> Option<&'static RegistryConfig> {
+        SELECTED_REGISTRY.try_with(|registry| *registry).ok()
+    }
+
+    /// Serves `request` with the router of the registry named `name`, the first one when the
+    /// name is unknown, e.g. removed from the configuration
+    pub async fn dispatch(
+        routers: &RegistryRouters,
+        name: Option<&str>,
+        request: Request,
+    ) -> Response {
+        let (registry, router) = routers
+            .iter()
+            .find(|(registry, _)| Some(registry.name.as_str()) == name)
+            .unwrap_or(&routers[0]);
+        SELECTED_REGISTRY
+            .scope(registry, router.clone().oneshot(request))
+            .await
+            .unwrap_infallible()
+    }
+
+    #[easy_ext::ext(CookieJarRegistryExt)]
+    impl CookieJar {
+        pub fn set_registry(self, name: &str) -> Self {
+            self.add(
+                Cookie::build((REGISTRY_COOKIE_NAME, name.to_owned()))
+                    .http_only(true)
+                    .secure(env::cookie_secure())
+                    .path("/")
+                    .same_site(SameSite::Strict)
+                    .permanent(),
+            )
+        }
+
+        pub fn registry(&self) -> Option<&str> {
+            self.get(REGISTRY_COOKIE_NAME).map(Cookie::value)
+        }
+    }
+}
+
+pub mod view {
+    use maud::{Markup, html};
+
+    use crate::{common::service::env, registries::service};
+
+    /// Switches the browsed registry, only shown when several are configured
+    pub fn selector() -> Markup {
+        html! {
+            @if let Some(selected) = service::selected() {
+                form .m-0 method="post" action="/registry" {
+                    select .form-select name="registry" aria-label="Registry" onchange="this.form.submit()" {
+                        @for registry in env::registries() {
+                            option value=(registry.name) selected[registry.name == selected.name] {
+                                (registry.name)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,316 +1,1995 @@
 pub mod api {
-    use std::borrow::ToOwned;
+    use std::{
+        borrow::ToOwned,
+        collections::{BTreeMap, HashMap, HashSet},
+        sync::{
+            Arc, RwLock,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::{Duration, Instant},
+    };
 
-    use anyhow::anyhow;
+    use anyhow::{Context, anyhow, ensure};
     use serde::de::DeserializeOwned;
+    use sha2::{Digest, Sha256};
+    use tokio::sync::Semaphore;
     use tracing::{info, instrument};
 
     use crate::{
         common,
         registry::dto::{
-            CatalogResponse, ManifestBlob, ManifestListResponse, TagManifest, TagsResponse,
+            ArtifactType, AuthScheme, BearerChallenge, CacheStats, CatalogPage, CatalogResponse,
+            HarborQuota, HarborRepository, Layer, ManifestBlob, ManifestListResponse, MediaType,
+            Referrer, ReferrersResponse, RegistryFlavor, StorageUsage, TagManifest, TagsResponse,
+            TokenResponse,
         },
     };
 
+    /// Every manifest flavour, a multi-platform tag answers with its index
+    const MANIFEST_ACCEPT: &str = "application/vnd.docker.distribution.manifest.v2+json, application/vnd.oci.image.manifest.v1+json, application/vnd.oci.image.index.v1+json, application/vnd.docker.distribution.manifest.list.v2+json, application/vnd.docker.distribution.manifest.v1+prettyjws";
+
+    /// `n` of `tags/list`, a registry answering with that many tags and no `Link` header is asked
+    /// for the following page anyway
+    const TAGS_PAGE_SIZE: usize = 1000;
+
+    /// Tags listed per image at most, in case a registry keeps announcing pages
+    const MAX_TAGS: usize = 100_000;
+
+    /// Digest no manifest has, for the capability probes
+    const PROBE_DIGEST: &str =
+        "sha256:0000000000000000000000000000000000000000000000000000000000000000";
+
+    /// Single-platform manifests only, used for the digests listed in an index
+    const IMAGE_MANIFEST_ACCEPT: &str = "application/vnd.docker.distribution.manifest.v2+json, application/vnd.oci.image.manifest.v1+json";
+
+    /// SHA-256 digest of content served without its `Docker-Content-Digest`
+    fn content_digest(bytes: &[u8]) -> String {
+        format!("sha256:{:x}", Sha256::digest(bytes))
+    }
+
+    #[easy_ext::ext(RequestTimeoutExt)]
+    impl reqwest::RequestBuilder {
+        /// One of the `REGISTRY_*_TIMEOUT_SECS`, unset means no timeout
+        fn timeout_secs(self, timeout_secs: Option<u64>) -> Self {
+            match timeout_secs {
+                Some(timeout_secs) => self.timeout(Duration::from_secs(timeout_secs)),
+                None => self,
+            }
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct Client {
         inner: reqwest::Client,
         base_url: String,
         username: &'static str,
         password: &'static str,
+        manifest_cache: Arc<RwLock<HashMap<String, CachedManifest>>>,
+        /// Every manifest digest resolved per image since startup, tags that were overwritten
+        /// leave their previous digest here
+        seen_digests: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+        max_body_bytes: usize,
+        concurrency: Arc<Semaphore>,
+        /// `VERIFY_DIGESTS`
+        verify_digests: bool,
+        /// Registry URL without the API prefix, where extension APIs are mounted
+        root_url: String,
+        /// Scheme, host and port of `root_url`, the only origin `extra_headers` are sent to
+        registry_origin: String,
+        /// `REGISTRY_EXTRA_HEADERS`
+        extra_headers: reqwest::header::HeaderMap,
+        /// `REGISTRY_FLAVOR`
+        flavor: RegistryFlavor,
+        /// Bearer tokens per scope, for registries delegating authentication to a token server
+        tokens: Arc<RwLock<HashMap<String, CachedToken>>>,
+        /// `MAX_CATALOG_SIZE`
+        max_catalog_size: Option<usize>,
+        /// Whether the last catalog fetch stopped at `max_catalog_size`
+        catalog_truncated: Arc<AtomicBool>,
+        /// `REGISTRY_NAMESPACE`
+        namespace: Option<&'static str>,
+        /// `KNOWN_REPOSITORIES`
+        known_repositories: &'static [String],
+        /// Whether the registry refused the last catalog fetch
+        catalog_unsupported: Arc<AtomicBool>,
+        /// Whether the referrers API answered 404, it is not asked again
+        referrers_unsupported: Arc<AtomicBool>,
+        /// Last answer of `pull_counts` with its time, kept for `PULL_COUNTS_TTL`
+        pull_counts_cache: Arc<RwLock<Option<CachedPullCounts>>>,
+    }
+
+    /// The registry answered with a body bigger than `REGISTRY_MAX_BODY_BYTES`
+    #[derive(Debug)]
+    pub struct PayloadTooLarge {
+        pub limit: usize,
+    }
+
+    impl std::fmt::Display for PayloadTooLarge {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "registry response exceeds {} bytes", self.limit)
+        }
+    }
+
+    impl std::error::Error for PayloadTooLarge {}
+
+    /// Parsed manifest kept alongside the `ETag` the registry returned for it
+    #[derive(Debug)]
+    struct CachedManifest {
+        etag: String,
+        manifest: TagManifest,
+        cached_at: Instant,
+    }
+
+    /// Manifests kept for `If-None-Match`, the oldest one makes room past it
+    const MAX_CACHED_MANIFESTS: usize = 10_000;
+
+    #[derive(Debug)]
+    struct CachedPullCounts {
+        fetched_at: Instant,
+        pull_counts: HashMap<String, u64>,
     }
 
+    #[derive(Clone, Debug)]
+    struct CachedToken {
+        token: String,
+        expires_at: Instant,
+    }
+
+    /// Lifetime of a token whose server does not tell, the minimum of the token specification
+    const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(60);
+
+    /// Pull counts walk every repository of the registry, every page render must not
+    const PULL_COUNTS_TTL: Duration = Duration::from_secs(300);
+
     impl Client {
+        /// Without `REGISTRY_HTTP_PROXY` nor `REGISTRY_PROXY_DISABLED`, reqwest falls back to the
+        /// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` variables, `REGISTRY_NO_PROXY` replacing the
+        /// last one when set
         pub fn new(
-            registry_host: &str,
+            registry_url: &str,
             username: &'static str,
             password: &'static str,
         ) -> anyhow::Result<Self> {
-            let client = reqwest::Client::builder()
-                .user_agent(format!(
-                    "Docker Registry Explorer v{}",
-                    common::service::APP_VERSION
-                ))
-                .build()?;
+            let mut user_agent =
+                format!("Docker Registry Explorer v{}", common::service::APP_VERSION);
+            if let Some(suffix) = common::service::env::registry_user_agent_suffix() {
+                user_agent = format!("{user_agent} {suffix}");
+            }
+            // Added to every registry request, including the raw manifest and blob calls, but not
+            // to the token server which may be a third party
+            let mut extra_headers = reqwest::header::HeaderMap::new();
+            for (name, value) in common::service::env::registry_extra_headers() {
+                let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("invalid REGISTRY_EXTRA_HEADERS name: {name}"))?;
+                let mut value = reqwest::header::HeaderValue::from_str(value)
+                    .with_context(|| format!("invalid REGISTRY_EXTRA_HEADERS value for {name}"))?;
+                value.set_sensitive(true);
+                extra_headers.insert(name, value);
+            }
+            let mut builder = reqwest::Client::builder().user_agent(user_agent);
+            // The home page counts the tags of every repository concurrently, the pool settings
+            // control how many of those connections are kept open between page loads. Measured
+            // on 200 repositories against a local registry, three loads 3s apart: by default the
+            // first load opens 17 connections (the concurrency limit) and the next ones reuse
+            // them, ~110ms each. `REGISTRY_POOL_MAX_IDLE_PER_HOST=0` opens 200 per load, ~160ms,
+            // `=8` reopens 9 to 20 and `REGISTRY_POOL_IDLE_TIMEOUT_SECS=1` reopens all 17
+            if let Some(max_idle) = common::service::env::registry_pool_max_idle_per_host() {
+                builder = builder.pool_max_idle_per_host(max_idle);
+            }
+            if let Some(idle_timeout) = common::service::env::registry_pool_idle_timeout_secs() {
+                builder = builder.pool_idle_timeout(Duration::from_secs(idle_timeout));
+            }
+            if common::service::env::registry_http1_only() {
+                builder = builder.http1_only();
+            }
+            if common::service::env::registry_proxy_disabled() {
+                builder = builder.no_proxy();
+            } else if let Some(proxy_url) = common::service::env::registry_http_proxy() {
+                let mut proxy = reqwest::Proxy::all(proxy_url)
+                    .with_context(|| format!("invalid REGISTRY_HTTP_PROXY: {proxy_url}"))?
+                    .no_proxy(
+                        common::service::env::registry_no_proxy()
+                            .and_then(reqwest::NoProxy::from_string),
+                    );
+                if let Some((username, password)) =
+                    common::service::env::registry_http_proxy_credentials()
+                {
+                    proxy = proxy.basic_auth(username, password);
+                }
+                builder = builder.proxy(proxy);
+            } else if let Some(no_proxy) = common::service::env::registry_no_proxy() {
+                // Explicit proxies turn the system ones off, they are rebuilt from the same
+                // variables to bypass `REGISTRY_NO_PROXY` instead of `NO_PROXY`
+                for proxy in system_proxies(no_proxy)? {
+                    builder = builder.proxy(proxy);
+                }
+            }
+            let client = builder.build()?;
 
             Ok(Self {
                 inner: client,
-                base_url: format!("https://{registry_host}/v2"),
+                base_url: format!(
+                    "{registry_url}{}",
+                    common::service::env::registry_api_prefix()
+                ),
                 username,
                 password,
+                manifest_cache: Arc::default(),
+                seen_digests: Arc::default(),
+                max_body_bytes: common::service::env::registry_max_body_bytes(),
+                concurrency: Arc::new(Semaphore::new(
+                    common::service::env::registry_max_concurrent_requests(),
+                )),
+                verify_digests: common::service::env::verify_digests(),
+                root_url: registry_url.to_owned(),
+                registry_origin: reqwest::Url::parse(registry_url)
+                    .with_context(|| format!("invalid registry URL: {registry_url}"))?
+                    .origin()
+                    .ascii_serialization(),
+                extra_headers,
+                flavor: common::service::env::registry_flavor(),
+                tokens: Arc::default(),
+                max_catalog_size: common::service::env::max_catalog_size(),
+                catalog_truncated: Arc::default(),
+                namespace: common::service::env::registry_namespace(),
+                known_repositories: common::service::env::known_repositories(),
+                catalog_unsupported: Arc::default(),
+                referrers_unsupported: Arc::default(),
+                pull_counts_cache: Arc::default(),
             })
         }
 
-        async fn make_request<Response: DeserializeOwned>(
-            &self,
-            method: reqwest::Method,
-            path: &str,
-        ) -> anyhow::Result<Response> {
-            Ok(self
-                .inner
-                .request(method, format!("{}/{path}", self.base_url))
-                .header("accept", "application/vnd.docker.distribution.manifest.v2+json, application/vnd.oci.image.manifest.v1+json, application/vnd.oci.image.index.v1+json, application/vnd.docker.distribution.manifest.list.v2+json")
-                .basic_auth(self.username, Some(self.password))
-                .send()
-                .await?
-                .json()
-                .await?)
+        /// Client of a test registry, usually a wiremock server
+        #[cfg(test)]
+        pub fn for_tests(registry_url: &str) -> Self {
+            Self::new(registry_url, "user", "pass").expect("test client")
         }
 
-        pub async fn catalog(&self) -> anyhow::Result<CatalogResponse> {
-            self.make_request(reqwest::Method::GET, "_catalog").await
+        #[cfg(test)]
+        pub fn with_digest_verification(mut self) -> Self {
+            self.verify_digests = true;
+            self
         }
 
-        #[instrument(skip(self))]
-        pub async fn count_tags(&self, image: &str) -> anyhow::Result<usize> {
-            let tags = self.tags(image).await?;
-            Ok(tags.tags.map_or(0, |tags| tags.len()))
+        #[cfg(test)]
+        pub fn with_namespace(
+            mut self,
+            namespace: &'static str,
+            known_repositories: &'static [String],
+        ) -> Self {
+            self.namespace = Some(namespace);
+            self.known_repositories = known_repositories;
+            self
         }
 
-        #[instrument(skip(self))]
-        pub async fn tags(&self, image: &str) -> anyhow::Result<TagsResponse> {
-            self.make_request(reqwest::Method::GET, &format!("{image}/tags/list"))
-                .await
+        #[cfg(test)]
+        pub fn with_max_catalog_size(mut self, max_catalog_size: usize) -> Self {
+            self.max_catalog_size = Some(max_catalog_size);
+            self
         }
 
-        #[instrument(skip(self))]
-        pub async fn manifest(&self, image: &str, tag: &str) -> anyhow::Result<TagManifest> {
-            let response = self
-                .inner
-                .get(format!("{}/{image}/manifests/{tag}", self.base_url))
-                .basic_auth(self.username, Some(self.password))
-                .header("accept", "application/vnd.docker.distribution.manifest.v2+json, application/vnd.oci.image.manifest.v1+json, application/vnd.oci.image.index.v1+json, application/vnd.docker.distribution.manifest.list.v2+json")
-                .send()
-                .await?;
+        #[cfg(test)]
+        pub fn with_flavor(mut self, flavor: RegistryFlavor) -> Self {
+            self.flavor = flavor;
+            self
+        }
 
-            let content_type = response
-                .headers()
-                .get("content-type")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("")
-                .to_owned();
+        #[cfg(test)]
+        pub fn with_extra_header(mut self, name: &'static str, value: &'static str) -> Self {
+            self.extra_headers
+                .insert(name, reqwest::header::HeaderValue::from_static(value));
+            self
+        }
 
-            let header_digest = response
+        /// Sends `request` once one of the `REGISTRY_MAX_CONCURRENT_REQUESTS` slots is free
+        async fn send(
+            &self,
+            request: reqwest::RequestBuilder,
+        ) -> anyhow::Result<reqwest::Response> {
+            self.send_scoped(request, None).await
+        }
+
+        /// Sends `request` with the cached token of `scope`, or of the scope its URL needs when
+        /// `None`. Without a valid token, a bearer challenge is answered with a token of `scope`,
+        /// or of the scope the registry asks for, and `request` is sent again. Browsing is
+        /// therefore pull-scoped while deletions ask for more
+        async fn send_scoped(
+            &self,
+            request: reqwest::RequestBuilder,
+            scope: Option<String>,
+        ) -> anyhow::Result<reqwest::Response> {
+            let request = request.build()?;
+            let key = scope.clone().or_else(|| self.url_scope(request.url()));
+            let retry = request.try_clone();
+            let response = match key.as_deref().and_then(|key| self.cached_token(key)) {
+                Some(token) => self.execute(with_bearer(request, &token)?).await?,
+                None => self.execute(request).await?,
+            };
+            if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+                return Ok(response);
+            }
+            // An expired or insufficient token is fetched again
+            if let Some(key) = &key
+                && let Ok(mut tokens) = self.tokens.write()
+            {
+                tokens.remove(key);
+            }
+            let challenge = response
                 .headers()
-                .get("docker-content-digest")
-                .ok_or_else(|| anyhow!("docker-content-digest is missing from response"))
-                .and_then(|header| header.to_str().map_err(|err| anyhow!(err)))
-                .map(ToOwned::to_owned);
+                .get(reqwest::header::WWW_AUTHENTICATE)
+                .and_then(|header| header.to_str().ok())
+                .and_then(BearerChallenge::parse);
+            let (Some(retry), Some(challenge)) = (retry, challenge) else {
+                return Ok(response);
+            };
+            let scope = scope
+                .or_else(|| challenge.scope.clone())
+                .unwrap_or_default();
+            let token = self.fetch_token(&challenge, &scope).await?;
+            if let Ok(mut tokens) = self.tokens.write() {
+                tokens.insert(key.unwrap_or(scope), token.clone());
+            }
+            self.execute(with_bearer(retry, &token.token)?).await
+        }
 
-            let is_multi_arch = content_type.contains("manifest.list")
-                || content_type.contains("image.index");
+        /// Sends `request` as it is, a challenge is returned to the caller. The extra headers are
+        /// added when it goes to the registry origin
+        async fn execute(
+            &self,
+            mut request: reqwest::Request,
+        ) -> anyhow::Result<reqwest::Response> {
+            if request.url().origin().ascii_serialization() == self.registry_origin {
+                request.headers_mut().extend(self.extra_headers.clone());
+            }
+            let _permit = self.concurrency.acquire().await?;
+            Ok(self.inner.execute(request).await?)
+        }
 
-            if is_multi_arch {
-                self.handle_multi_arch_manifest(image, header_digest, response)
-                    .await
-            } else {
-                self.handle_single_manifest(image, header_digest, response)
-                    .await
+        fn cached_token(&self, scope: &str) -> Option<String> {
+            self.tokens
+                .read()
+                .ok()?
+                .get(scope)
+                .filter(|cached| cached.expires_at > Instant::now())
+                .map(|cached| cached.token.clone())
+        }
+
+        /// Pull scope of the repository, or catalog scope, a request to `url` needs
+        fn url_scope(&self, url: &reqwest::Url) -> Option<String> {
+            let path = url
+                .as_str()
+                .split('?')
+                .next()?
+                .strip_prefix(&self.base_url)?;
+            if path == "/_catalog" {
+                return Some("registry:catalog:*".to_owned());
             }
+            let repository = ["/tags/", "/manifests/", "/blobs/", "/referrers/"]
+                .into_iter()
+                .filter_map(|marker| path.find(marker))
+                .min()
+                .and_then(|end| path.get(1..end))?;
+            Some(format!("repository:{repository}:pull"))
         }
 
-        async fn handle_single_manifest(
+        /// Asks the token server of `challenge` for a token of `scope` with the registry
+        /// credentials
+        async fn fetch_token(
             &self,
-            image: &str,
-            header_digest: Result<String, anyhow::Error>,
-            response: reqwest::Response,
-        ) -> anyhow::Result<TagManifest> {
-            let json = response.json::<serde_json::Value>().await?;
-            if let Ok(digest) = header_digest {
-                let config_digest = json
-                    .get("config")
-                    .ok_or_else(|| anyhow!("config missing"))?
-                    .get("digest")
-                    .ok_or_else(|| anyhow!("digest missing"))?
-                    .as_str()
-                    .ok_or_else(|| anyhow!("not a string"))?
-                    .to_owned();
-                let blob = self
-                    .inner
-                    .get(format!("{}/{image}/blobs/{config_digest}", self.base_url))
-                    .basic_auth(self.username, Some(self.password))
-                    .send()
-                    .await?
-                    .json::<ManifestBlob>()
-                    .await?;
-                let created = chrono::DateTime::parse_from_rfc3339(&blob.created)?.to_utc();
-                Ok(TagManifest::Nominal {
-                    digest,
-                    created,
-                    architecture: blob.architecture,
-                })
-            } else {
-                Ok(TagManifest::Error {
-                    digest: json
-                        .get("errors")
-                        .ok_or_else(|| anyhow!("errors missing"))?
-                        .as_array()
-                        .ok_or_else(|| anyhow!("not an array"))?
-                        .first()
-                        .ok_or_else(|| anyhow!("empty array"))?
-                        .get("detail")
-                        .ok_or_else(|| anyhow!("detail missing"))?
-                        .get("Revision")
-                        .ok_or_else(|| anyhow!("revision missing"))?
-                        .as_str()
-                        .ok_or_else(|| anyhow!("not a string"))?
-                        .to_owned(),
-                })
+            challenge: &BearerChallenge,
+            scope: &str,
+        ) -> anyhow::Result<CachedToken> {
+            let mut query = Vec::new();
+            if let Some(service) = &challenge.service {
+                query.push(("service", service.as_str()));
+            }
+            if !scope.is_empty() {
+                query.push(("scope", scope));
             }
+            let request = self
+                .inner
+                .get(&challenge.realm)
+                .query(&query)
+                .basic_auth(self.username, Some(self.password));
+            let response = {
+                let _permit = self.concurrency.acquire().await?;
+                request.send().await?.error_for_status()?
+            };
+            let response = self.read_json::<TokenResponse>(response).await?;
+            let expires_at = Instant::now()
+                + response
+                    .expires_in
+                    .map_or(DEFAULT_TOKEN_LIFETIME, Duration::from_secs);
+            let token = response
+                .token
+                .or(response.access_token)
+                .with_context(|| format!("token server gave no token for {scope:?}"))?;
+            Ok(CachedToken { token, expires_at })
         }
 
-        async fn handle_multi_arch_manifest(
+        /// Deserializes the body of `response`, refusing to buffer more than `max_body_bytes`
+        async fn read_json<T: DeserializeOwned>(
             &self,
-            image: &str,
-            header_digest: Result<String, anyhow::Error>,
             response: reqwest::Response,
-        ) -> anyhow::Result<TagManifest> {
-            let digest = header_digest?;
-            let manifest_list = response.json::<ManifestListResponse>().await?;
+        ) -> anyhow::Result<T> {
+            ensure!(
+                !is_html(&response),
+                "expected JSON from {} but the registry answered an HTML page",
+                response.url().path()
+            );
+            Ok(serde_json::from_slice(&self.read_bytes(response).await?)?)
+        }
 
-            if manifest_list.manifests.is_empty() {
-                return Ok(TagManifest::Error { digest });
+        async fn read_bytes(&self, mut response: reqwest::Response) -> anyhow::Result<Vec<u8>> {
+            let limit = self.max_body_bytes;
+            if response
+                .content_length()
+                .is_some_and(|length| length > limit as u64)
+            {
+                return Err(PayloadTooLarge { limit }.into());
             }
+            let mut body = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > limit {
+                    return Err(PayloadTooLarge { limit }.into());
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(body)
+        }
 
-            let architectures: Vec<String> = manifest_list
-                .manifests
-                .iter()
-                .filter_map(|entry| {
-                    entry.platform.as_ref().and_then(|p| {
-                        if p.os == "unknown" && p.architecture == "unknown" {
-                            return None;
-                        }
-                        let base = format!("{}/{}", p.os, p.architecture);
-                        Some(match &p.variant {
-                            Some(v) => format!("{base}/{v}"),
-                            None => base,
-                        })
-                    })
-                })
-                .collect();
-
-            // Find linux/amd64 entry, or fall back to first entry with a platform
-            let preferred = manifest_list
-                .manifests
-                .iter()
-                .find(|e| {
-                    e.platform
-                        .as_ref()
-                        .is_some_and(|p| p.os == "linux" && p.architecture == "amd64")
-                })
-                .or_else(|| {
-                    manifest_list
-                        .manifests
-                        .iter()
-                        .find(|e| e.platform.is_some())
-                })
-                .unwrap_or(&manifest_list.manifests[0]);
+        /// Only SHA-256 digests are checked, and only with `VERIFY_DIGESTS`
+        fn digest_mismatch(&self, digest: &str, bytes: &[u8]) -> bool {
+            let mismatch = self.verify_digests
+                && digest
+                    .strip_prefix("sha256:")
+                    .is_some_and(|expected| format!("{:x}", Sha256::digest(bytes)) != expected);
+            if mismatch {
+                tracing::warn!("Content received for {digest} does not match its digest");
+            }
+            mismatch
+        }
 
-            let created = self
-                .fetch_created_date(image, &preferred.digest)
-                .await
-                .ok();
+        /// Succeeds when the API base answers, a 401 still proves the registry is up. Yields the
+        /// `Docker-Distribution-Api-Version` the registry announces, `registry/2.0` for a
+        /// distribution registry
+        #[instrument(skip(self))]
+        pub async fn ping(&self) -> anyhow::Result<Option<String>> {
+            let request = self
+                .inner
+                .get(format!("{}/", self.base_url))
+                .basic_auth(self.username, Some(self.password));
+            let response = self.send(request).await?;
+            let status = response.status();
+            ensure!(
+                status.is_success() || status == reqwest::StatusCode::UNAUTHORIZED,
+                "registry answered {status}"
+            );
+            ensure!(
+                !is_html(&response),
+                "the API base answered an HTML page, a proxy or a web server is answering instead of the registry"
+            );
+            Ok(response
+                .headers()
+                .get("docker-distribution-api-version")
+                .and_then(|version| version.to_str().ok())
+                .map(ToOwned::to_owned))
+        }
 
-            Ok(TagManifest::MultiArch {
-                digest,
-                architectures,
-                created,
+        /// Scheme the API base challenges anonymous clients with
+        #[instrument(skip(self))]
+        pub async fn auth_scheme(&self) -> anyhow::Result<AuthScheme> {
+            let response = self
+                .execute(self.inner.get(format!("{}/", self.base_url)).build()?)
+                .await?;
+            if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+                return Ok(AuthScheme::Anonymous);
+            }
+            let challenge = response
+                .headers()
+                .get(reqwest::header::WWW_AUTHENTICATE)
+                .and_then(|header| header.to_str().ok())
+                .unwrap_or_default();
+            Ok(match BearerChallenge::parse(challenge) {
+                Some(challenge) => AuthScheme::Bearer {
+                    realm: challenge.realm,
+                },
+                None => match challenge.split_whitespace().next() {
+                    Some(scheme) if scheme.eq_ignore_ascii_case("basic") => AuthScheme::Basic,
+                    scheme => AuthScheme::Other(scheme.unwrap_or_default().to_owned()),
+                },
             })
         }
 
-        async fn fetch_created_date(
-            &self,
-            image: &str,
-            manifest_digest: &str,
-        ) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
-            let manifest_response = self
+        /// OCI 1.1 referrers API, a registry supporting it never answers 404 to it
+        #[instrument(skip(self))]
+        pub async fn referrers_supported(&self, image: &str) -> anyhow::Result<bool> {
+            let request = self
                 .inner
                 .get(format!(
-                    "{}/{image}/manifests/{manifest_digest}",
-                    self.base_url
+                    "{}/referrers/{PROBE_DIGEST}",
+                    self.repository_url(image)
                 ))
-                .basic_auth(self.username, Some(self.password))
-                .header("accept", "application/vnd.docker.distribution.manifest.v2+json, application/vnd.oci.image.manifest.v1+json")
-                .send()
-                .await?;
-            let json = manifest_response.json::<serde_json::Value>().await?;
-            let config_digest = json
-                .get("config")
-                .ok_or_else(|| anyhow!("config missing"))?
-                .get("digest")
-                .ok_or_else(|| anyhow!("digest missing"))?
-                .as_str()
-                .ok_or_else(|| anyhow!("not a string"))?;
-            let blob = self
+                .basic_auth(self.username, Some(self.password));
+            let response = self.send(request).await?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(false);
+            }
+            response.error_for_status()?;
+            Ok(true)
+        }
+
+        /// Manifests referring to `digest`, `None` when the registry does not support the
+        /// referrers API
+        #[instrument(skip(self))]
+        pub async fn referrers(
+            &self,
+            image: &str,
+            digest: &str,
+        ) -> anyhow::Result<Option<Vec<Referrer>>> {
+            if self.referrers_unsupported.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            let request = self
                 .inner
-                .get(format!("{}/{image}/blobs/{config_digest}", self.base_url))
+                .get(format!("{}/referrers/{digest}", self.repository_url(image)))
                 .basic_auth(self.username, Some(self.password))
-                .send()
-                .await?
-                .json::<ManifestBlob>()
+                .header("accept", "application/vnd.oci.image.index.v1+json");
+            let response = self.send(request).await?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                self.referrers_unsupported.store(true, Ordering::Relaxed);
+                return Ok(None);
+            }
+            let referrers = self
+                .read_json::<ReferrersResponse>(response.error_for_status()?)
                 .await?;
-            let created = chrono::DateTime::parse_from_rfc3339(&blob.created)?.to_utc();
-            Ok(created)
+            Ok(Some(referrers.manifests))
+        }
+
+        pub fn cache_stats(&self) -> CacheStats {
+            CacheStats {
+                manifests: self
+                    .manifest_cache
+                    .read()
+                    .map_or(0, |manifest_cache| manifest_cache.len()),
+                seen_digests: self.seen_digests.read().map_or(0, |seen_digests| {
+                    seen_digests.values().map(HashSet::len).sum()
+                }),
+                tokens: self.tokens.read().map_or(0, |tokens| tokens.len()),
+            }
         }
 
+        /// Deleting a manifest that cannot exist answers 405 only when the registry was started
+        /// without `REGISTRY_STORAGE_DELETE_ENABLED`
         #[instrument(skip(self))]
-        pub async fn delete_tag(&self, image: &str, digest: &str) -> anyhow::Result<()> {
-            info!("Calling delete tag request");
-            self.inner
-                .delete(format!("{}/{image}/manifests/{digest}", self.base_url))
-                .basic_auth(self.username, Some(self.password))
-                .send()
+        pub async fn deletions_enabled(&self) -> anyhow::Result<bool> {
+            let request = self
+                .inner
+                .delete(format!(
+                    "{}/manifests/{PROBE_DIGEST}",
+                    self.repository_url("docker-registry-explorer-probe")
+                ))
+                .basic_auth(self.username, Some(self.password));
+            let status = self.send(request).await?.status();
+            ensure!(
+                !matches!(
+                    status,
+                    reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+                ),
+                "registry answered {status}"
+            );
+            Ok(status != reqwest::StatusCode::METHOD_NOT_ALLOWED)
+        }
+
+        /// Pull count of every repository from the extension API of the registry flavor, empty
+        /// for a plain distribution registry. Answers are reused for `PULL_COUNTS_TTL`
+        #[instrument(skip(self))]
+        pub async fn pull_counts(&self) -> anyhow::Result<HashMap<String, u64>> {
+            const HARBOR_PAGE_SIZE: usize = 100;
+
+            let mut pull_counts = HashMap::new();
+            if self.flavor != RegistryFlavor::Harbor {
+                return Ok(pull_counts);
+            }
+            if let Some(cached) = self
+                .pull_counts_cache
+                .read()
+                .map_err(|_| anyhow!("pull counts cache poisoned"))?
+                .as_ref()
+                .filter(|cached| cached.fetched_at.elapsed() < PULL_COUNTS_TTL)
+            {
+                return Ok(cached.pull_counts.clone());
+            }
+            for page in 1.. {
+                let request = self
+                    .inner
+                    .get(format!("{}/api/v2.0/repositories", self.root_url))
+                    .query(&[
+                        ("page", page.to_string()),
+                        ("page_size", HARBOR_PAGE_SIZE.to_string()),
+                    ])
+                    .basic_auth(self.username, Some(self.password));
+                let response = self.send(request).await?.error_for_status()?;
+                let repositories = self.read_json::<Vec<HarborRepository>>(response).await?;
+                let last_page = repositories.len() < HARBOR_PAGE_SIZE;
+                pull_counts.extend(
+                    repositories
+                        .into_iter()
+                        .map(|repository| (repository.name, repository.pull_count)),
+                );
+                if last_page {
+                    break;
+                }
+            }
+            *self
+                .pull_counts_cache
+                .write()
+                .map_err(|_| anyhow!("pull counts cache poisoned"))? = Some(CachedPullCounts {
+                fetched_at: Instant::now(),
+                pull_counts: pull_counts.clone(),
+            });
+            Ok(pull_counts)
+        }
+
+        /// Storage used and quota summed over the Harbor projects, `None` for a plain distribution
+        /// registry
+        #[instrument(skip(self))]
+        pub async fn storage_usage(&self) -> anyhow::Result<Option<StorageUsage>> {
+            const HARBOR_PAGE_SIZE: usize = 100;
+
+            if !self.flavor.has_storage_usage() {
+                return Ok(None);
+            }
+            let mut usage = StorageUsage {
+                used: 0,
+                quota: Some(0),
+            };
+            for page in 1.. {
+                let request = self
+                    .inner
+                    .get(format!("{}/api/v2.0/quotas", self.root_url))
+                    .query(&[
+                        ("page", page.to_string()),
+                        ("page_size", HARBOR_PAGE_SIZE.to_string()),
+                    ])
+                    .basic_auth(self.username, Some(self.password));
+                let response = self.send(request).await?.error_for_status()?;
+                let quotas = self.read_json::<Vec<HarborQuota>>(response).await?;
+                let last_page = quotas.len() < HARBOR_PAGE_SIZE;
+                for quota in quotas {
+                    usage.used += u64::try_from(quota.used.storage).unwrap_or_default();
+                    usage.quota = usage
+                        .quota
+                        .zip(u64::try_from(quota.hard.storage).ok())
+                        .map(|(total, hard)| total + hard);
+                }
+                if last_page {
+                    break;
+                }
+            }
+            Ok(Some(usage))
+        }
+
+        /// Artifacts of the Harbor repository `image`, tagged or not. Harbor deletes them all with
+        /// the repository
+        #[instrument(skip(self))]
+        pub async fn artifact_count(&self, image: &str) -> anyhow::Result<u64> {
+            let name = self.repository_name(image);
+            let (project, repository) = name
+                .split_once('/')
+                .with_context(|| format!("{name} is not in a Harbor project"))?;
+            let request = self
+                .inner
+                .get(format!(
+                    "{}/api/v2.0/projects/{project}/repositories/{}",
+                    self.root_url,
+                    repository.replace('/', "%252F")
+                ))
+                .basic_auth(self.username, Some(self.password));
+            let response = self.send(request).await?.error_for_status()?;
+            Ok(self
+                .read_json::<HarborRepository>(response)
                 .await?
-                .error_for_status()?;
+                .artifact_count)
+        }
 
+        /// Deletes the repository `image` with the extension API of the registry flavor, Harbor
+        /// names it `project/repository` with the slashes of the repository encoded twice
+        #[instrument(skip(self))]
+        pub async fn delete_repository(&self, image: &str) -> anyhow::Result<()> {
+            ensure!(
+                self.flavor.can_delete_repositories(),
+                "{} registries cannot delete repositories",
+                self.flavor.as_str()
+            );
+            let name = self.repository_name(image);
+            let (project, repository) = name
+                .split_once('/')
+                .with_context(|| format!("{name} is not in a Harbor project"))?;
+            info!("Calling delete repository request");
+            let request = self
+                .inner
+                .delete(format!(
+                    "{}/api/v2.0/projects/{project}/repositories/{}",
+                    self.root_url,
+                    repository.replace('/', "%252F")
+                ))
+                .basic_auth(self.username, Some(self.password));
+            self.send(request).await?.error_for_status()?;
             Ok(())
         }
-    }
-}
 
-pub mod dto {
-    use serde::Deserialize;
+        /// The whole catalog, or its first `MAX_CATALOG_SIZE` repositories. Registries ignoring
+        /// `n` have their answer cut here
+        pub async fn catalog(&self) -> anyhow::Result<CatalogResponse> {
+            let mut request = self
+                .inner
+                .get(format!("{}/_catalog", self.base_url))
+                .timeout_secs(common::service::env::registry_catalog_timeout_secs())
+                .basic_auth(self.username, Some(self.password));
+            if let Some(max_catalog_size) = self.max_catalog_size {
+                request = request.query(&[("n", max_catalog_size)]);
+            }
+            let response = self.send(request).await?;
+            let unsupported = self.catalog_unsupported_by(response.status());
+            self.catalog_unsupported
+                .store(unsupported, Ordering::Relaxed);
+            if unsupported {
+                self.catalog_truncated.store(false, Ordering::Relaxed);
+                return Ok(CatalogResponse {
+                    repositories: self.known_repositories.to_vec(),
+                });
+            }
+            let response = response.error_for_status()?;
+            let has_next = response.headers().contains_key("link");
+            let mut catalog = self.read_json::<CatalogResponse>(response).await?;
+            catalog.repositories = self.relative_names(catalog.repositories);
+            let truncated = self.max_catalog_size.is_some_and(|max_catalog_size| {
+                has_next || catalog.repositories.len() > max_catalog_size
+            });
+            if let Some(max_catalog_size) = self.max_catalog_size {
+                catalog.repositories.truncate(max_catalog_size);
+            }
+            self.catalog_truncated.store(truncated, Ordering::Relaxed);
+            Ok(catalog)
+        }
 
-    #[derive(Deserialize)]
-    pub struct CatalogResponse {
-        pub repositories: Vec<String>,
-    }
+        /// The size the last catalog fetch stopped at, when it left repositories out because of
+        /// `MAX_CATALOG_SIZE`
+        pub fn catalog_truncated(&self) -> Option<usize> {
+            self.max_catalog_size
+                .filter(|_| self.catalog_truncated.load(Ordering::Relaxed))
+        }
 
-    #[derive(Deserialize)]
-    pub struct TagsResponse {
-        pub tags: Option<Vec<String>>,
-    }
+        /// Whether the registry refused the last catalog fetch, `KNOWN_REPOSITORIES` was listed
+        /// instead
+        pub fn catalog_unsupported(&self) -> bool {
+            self.catalog_unsupported.load(Ordering::Relaxed)
+        }
 
-    pub enum TagManifest {
-        Nominal {
-            digest: String,
-            created: chrono::DateTime<chrono::Utc>,
-            architecture: String,
-        },
-        MultiArch {
-            digest: String,
-            architectures: Vec<String>,
-            created: Option<chrono::DateTime<chrono::Utc>>,
-        },
-        Error {
-            digest: String,
-        },
-    }
+        /// Public registries (GHCR, GCR, Docker Hub) deny or do not route `_catalog`, only
+        /// expected when `REGISTRY_NAMESPACE` or `KNOWN_REPOSITORIES` says the registry is one.
+        /// A 401 stays an error, wrong credentials must not pass for an unsupported catalog
+        fn catalog_unsupported_by(&self, status: reqwest::StatusCode) -> bool {
+            (self.namespace.is_some() || !self.known_repositories.is_empty())
+                && matches!(
+                    status,
+                    reqwest::StatusCode::FORBIDDEN
+                        | reqwest::StatusCode::NOT_FOUND
+                        | reqwest::StatusCode::METHOD_NOT_ALLOWED
+                )
+        }
 
-    impl TagManifest {
-        pub fn digest(&self) -> &str {
-            match self {
-                Self::Nominal { digest, .. }
-                | Self::MultiArch { digest, .. }
-                | Self::Error { digest } => digest,
+        /// Path of `image` in the registry, under `REGISTRY_NAMESPACE` when set
+        fn repository_name(&self, image: &str) -> String {
+            match self.namespace {
+                Some(namespace) => format!("{namespace}/{image}"),
+                None => image.to_owned(),
+            }
+        }
+
+        fn repository_url(&self, image: &str) -> String {
+            format!("{}/{}", self.base_url, self.repository_name(image))
+        }
+
+        /// Catalog names relative to `REGISTRY_NAMESPACE`, the others are left out
+        fn relative_names(&self, repositories: Vec<String>) -> Vec<String> {
+            match self.namespace {
+                Some(namespace) => repositories
+                    .into_iter()
+                    .filter_map(|repository| {
+                        Some(
+                            repository
+                                .strip_prefix(namespace)?
+                                .strip_prefix('/')?
+                                .to_owned(),
+                        )
+                    })
+                    .collect(),
+                None => repositories,
+            }
+        }
+
+        /// Asks the registry for `image` itself, the catalog may not list it
+        #[instrument(skip(self))]
+        pub async fn repository_exists(&self, image: &str) -> anyhow::Result<bool> {
+            let request = self
+                .inner
+                .get(format!("{}/tags/list", self.repository_url(image)))
+                .query(&[("n", 1)])
+                .basic_auth(self.username, Some(self.password));
+            let response = self.send(request).await?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(false);
+            }
+            response.error_for_status()?;
+            Ok(true)
+        }
+
+        /// Up to `count` repositories sorted after `last`, the registry announces a following page
+        /// with a `Link` header whose `last` parameter is the next cursor
+        #[instrument(skip(self))]
+        pub async fn catalog_page(
+            &self,
+            count: usize,
+            last: Option<&str>,
+        ) -> anyhow::Result<CatalogPage> {
+            let mut query = vec![("n", count.to_string())];
+            if let Some(last) = last {
+                query.push(("last", self.repository_name(last)));
+            }
+            let request = self
+                .inner
+                .get(format!("{}/_catalog", self.base_url))
+                .timeout_secs(common::service::env::registry_catalog_timeout_secs())
+                .query(&query)
+                .basic_auth(self.username, Some(self.password));
+            let response = self.send(request).await?;
+            let unsupported = self.catalog_unsupported_by(response.status());
+            self.catalog_unsupported
+                .store(unsupported, Ordering::Relaxed);
+            if unsupported {
+                let mut repositories = self
+                    .known_repositories
+                    .iter()
+                    .filter(|repository| last.is_none_or(|last| repository.as_str() > last))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let next = (repositories.len() > count)
+                    .then(|| repositories.get(count.checked_sub(1)?).cloned())
+                    .flatten();
+                repositories.truncate(count);
+                return Ok(CatalogPage { repositories, next });
+            }
+            let response = response.error_for_status()?;
+            let next = self.link_cursor(&response).map(|next| {
+                self.relative_names(vec![next.clone()])
+                    .pop()
+                    .unwrap_or(next)
+            });
+            let catalog = self.read_json::<CatalogResponse>(response).await?;
+            Ok(CatalogPage {
+                repositories: self.relative_names(catalog.repositories),
+                next,
+            })
+        }
+
+        #[instrument(skip(self))]
+        pub async fn count_tags(&self, image: &str) -> anyhow::Result<usize> {
+            let tags = self.tags(image).await?;
+            Ok(tags.tags.map_or(0, |tags| tags.len()))
+        }
+
+        /// `last` parameter of the page announced by the `Link` header
+        fn link_cursor(&self, response: &reqwest::Response) -> Option<String> {
+            response
+                .headers()
+                .get("link")
+                .and_then(|link| link.to_str().ok())
+                .and_then(|link| link.split_once('<')?.1.split_once('>'))
+                .and_then(|(target, _)| reqwest::Url::parse(&self.base_url).ok()?.join(target).ok())
+                .and_then(|url| {
+                    url.query_pairs()
+                        .find(|(key, _)| key == "last")
+                        .map(|(_, value)| value.into_owned())
+                })
+        }
+
+        /// Every tag of `image`, page after page. The next page is announced by a `Link` header,
+        /// or guessed from a full page for registries that only honor `n`. Following stops at
+        /// `MAX_TAGS`
+        #[instrument(skip(self))]
+        pub async fn tags(&self, image: &str) -> anyhow::Result<TagsResponse> {
+            let mut tags = Vec::new();
+            let mut last = None::<String>;
+            loop {
+                let mut query = vec![("n", TAGS_PAGE_SIZE.to_string())];
+                if let Some(last) = &last {
+                    query.push(("last", last.clone()));
+                }
+                let request = self
+                    .inner
+                    .get(format!("{}/tags/list", self.repository_url(image)))
+                    .query(&query)
+                    .basic_auth(self.username, Some(self.password));
+                let response = self.send(request).await?;
+                let link_cursor = self.link_cursor(&response);
+                let page = self
+                    .read_json::<TagsResponse>(response)
+                    .await?
+                    .tags
+                    .unwrap_or_default();
+                let next = link_cursor.or_else(|| {
+                    (page.len() >= TAGS_PAGE_SIZE)
+                        .then(|| page.last().cloned())
+                        .flatten()
+                });
+                // A registry ignoring `last` serves the same page again, and would forever
+                if last.is_some() && next == last {
+                    break;
+                }
+                tags.extend(page);
+                if tags.len() >= MAX_TAGS {
+                    tracing::warn!("{image} has more than {MAX_TAGS} tags, the others are ignored");
+                    tags.truncate(MAX_TAGS);
+                    break;
+                }
+                match next {
+                    Some(next) => last = Some(next),
+                    None => break,
+                }
+            }
+            Ok(TagsResponse { tags: Some(tags) })
+        }
+
+        #[instrument(skip(self))]
+        pub async fn manifest(&self, image: &str, tag: &str) -> anyhow::Result<TagManifest> {
+            let cache_key = format!("{image}:{tag}");
+            let cached = self
+                .manifest_cache
+                .read()
+                .map_err(|_| anyhow!("manifest cache poisoned"))?
+                .get(&cache_key)
+                .map(|cached| (cached.etag.clone(), cached.manifest.clone()));
+
+            let mut request = self
+                .inner
+                .get(format!("{}/manifests/{tag}", self.repository_url(image)))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", MANIFEST_ACCEPT);
+            if let Some((etag, _)) = &cached {
+                request = request.header("if-none-match", etag);
+            }
+            let response = self.send(request).await?;
+
+            if response.status() == reqwest::StatusCode::NOT_MODIFIED
+                && let Some((_, manifest)) = cached
+            {
+                return Ok(manifest);
+            }
+
+            let etag = response
+                .headers()
+                .get("etag")
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned);
+
+            let manifest = self.parse_manifest(image, response).await?;
+
+            if !matches!(manifest, TagManifest::Error { .. }) {
+                self.record_seen_digest(image, tag, manifest.digest())?;
+            }
+
+            if let Some(etag) = etag {
+                let mut manifest_cache = self
+                    .manifest_cache
+                    .write()
+                    .map_err(|_| anyhow!("manifest cache poisoned"))?;
+                if manifest_cache.len() >= MAX_CACHED_MANIFESTS
+                    && !manifest_cache.contains_key(&cache_key)
+                    && let Some(oldest) = manifest_cache
+                        .iter()
+                        .min_by_key(|(_, cached)| cached.cached_at)
+                        .map(|(key, _)| key.clone())
+                {
+                    manifest_cache.remove(&oldest);
+                }
+                manifest_cache.insert(
+                    cache_key,
+                    CachedManifest {
+                        etag,
+                        manifest: manifest.clone(),
+                        cached_at: Instant::now(),
+                    },
+                );
+            }
+
+            Ok(manifest)
+        }
+
+        /// Manifest of `reference` exactly as served, with its digest
+        #[instrument(skip(self))]
+        pub async fn raw_manifest(
+            &self,
+            image: &str,
+            reference: &str,
+        ) -> anyhow::Result<(String, Vec<u8>)> {
+            let request = self
+                .inner
+                .get(format!(
+                    "{}/manifests/{reference}",
+                    self.repository_url(image)
+                ))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", MANIFEST_ACCEPT);
+            let response = self.send(request).await?.error_for_status()?;
+            let header_digest = response
+                .headers()
+                .get("docker-content-digest")
+                .map(|header| header.to_str().map(ToOwned::to_owned))
+                .transpose()?;
+            let body = self.read_bytes(response).await?;
+            let digest = header_digest.unwrap_or_else(|| content_digest(&body));
+            Ok((digest, body))
+        }
+
+        /// Content of a blob, bounded by `REGISTRY_MAX_BODY_BYTES` like any other response
+        #[instrument(skip(self))]
+        pub async fn blob(&self, image: &str, digest: &str) -> anyhow::Result<Vec<u8>> {
+            let request = self
+                .inner
+                .get(format!("{}/blobs/{digest}", self.repository_url(image)))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password));
+            let response = self.send(request).await?.error_for_status()?;
+            self.read_bytes(response).await
+        }
+
+        /// Manifest of one platform of an index, by the digest listed in the index
+        ///
+        /// Not recorded in the seen digests, platform manifests are never tagged themselves and
+        /// must not be mistaken for dangling ones
+        #[instrument(skip(self))]
+        pub async fn platform_manifest(
+            &self,
+            image: &str,
+            digest: &str,
+        ) -> anyhow::Result<TagManifest> {
+            let request = self
+                .inner
+                .get(format!("{}/manifests/{digest}", self.repository_url(image)))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", IMAGE_MANIFEST_ACCEPT);
+            let response = self.send(request).await?;
+            self.parse_manifest(image, response).await
+        }
+
+        /// Digests of the tags of `image` resolved since startup, whether or not they are still
+        /// tagged
+        pub fn seen_digests(&self, image: &str) -> HashSet<String> {
+            self.seen_digests
+                .read()
+                .map(|seen| seen.get(image).cloned().unwrap_or_default())
+                .unwrap_or_default()
+        }
+
+        /// Records the digest a tag resolved to, a manifest fetched by digest may be an untagged
+        /// platform manifest or referrer and is skipped
+        fn record_seen_digest(
+            &self,
+            image: &str,
+            reference: &str,
+            digest: &str,
+        ) -> anyhow::Result<()> {
+            if reference.contains(':') {
+                return Ok(());
+            }
+            self.seen_digests
+                .write()
+                .map_err(|_| anyhow!("seen digests poisoned"))?
+                .entry(image.to_owned())
+                .or_default()
+                .insert(digest.to_owned());
+            Ok(())
+        }
+
+        /// Digest of `reference` from a `HEAD`, without fetching the manifest nor its config
+        #[instrument(skip(self))]
+        pub async fn manifest_digest(
+            &self,
+            image: &str,
+            reference: &str,
+        ) -> anyhow::Result<String> {
+            self.find_manifest_digest(image, reference)
+                .await?
+                .with_context(|| format!("{image}:{reference} does not exist"))
+        }
+
+        /// Digest of `reference`, `None` when the registry does not know it. Behind a proxy
+        /// stripping `Docker-Content-Digest`, the manifest is fetched and digested
+        #[instrument(skip(self))]
+        pub async fn find_manifest_digest(
+            &self,
+            image: &str,
+            reference: &str,
+        ) -> anyhow::Result<Option<String>> {
+            let request = self
+                .inner
+                .head(format!(
+                    "{}/manifests/{reference}",
+                    self.repository_url(image)
+                ))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", MANIFEST_ACCEPT);
+            let response = self.send(request).await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let response = response.error_for_status()?;
+            let header_digest = response
+                .headers()
+                .get("docker-content-digest")
+                .map(|header| header.to_str().map(ToOwned::to_owned))
+                .transpose()?;
+            let digest = match header_digest {
+                Some(digest) => digest,
+                None => self.raw_manifest(image, reference).await?.0,
+            };
+            self.record_seen_digest(image, reference, &digest)?;
+            Ok(Some(digest))
+        }
+
+        #[instrument(skip(self))]
+        pub async fn manifest_exists(&self, image: &str, reference: &str) -> anyhow::Result<bool> {
+            let request = self
+                .inner
+                .head(format!(
+                    "{}/manifests/{reference}",
+                    self.repository_url(image)
+                ))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", MANIFEST_ACCEPT);
+            let response = self.send(request).await?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(false);
+            }
+            response.error_for_status()?;
+            Ok(true)
+        }
+
+        async fn parse_manifest(
+            &self,
+            image: &str,
+            response: reqwest::Response,
+        ) -> anyhow::Result<TagManifest> {
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_owned();
+
+            let header_digest = response
+                .headers()
+                .get("docker-content-digest")
+                .ok_or_else(|| anyhow!("docker-content-digest is missing from response"))
+                .and_then(|header| header.to_str().map_err(|err| anyhow!(err)))
+                .map(ToOwned::to_owned);
+            let success = response.status().is_success();
+
+            let is_multi_arch =
+                content_type.contains("manifest.list") || content_type.contains("image.index");
+
+            let body = self.read_bytes(response).await?;
+            // Some proxies strip the header, the manifest served is still valid
+            let header_digest = header_digest.or_else(|err| {
+                if success {
+                    tracing::debug!("{image}: {err}, digest computed from the manifest");
+                    Ok(content_digest(&body))
+                } else {
+                    Err(err)
+                }
+            });
+            let digest_mismatch = header_digest
+                .as_ref()
+                .is_ok_and(|digest| self.digest_mismatch(digest, &body));
+            let media_type = MediaType::from_manifest(&content_type, &body);
+
+            if is_multi_arch {
+                self.handle_multi_arch_manifest(
+                    image,
+                    header_digest,
+                    &body,
+                    digest_mismatch,
+                    media_type,
+                )
+                .await
+            } else {
+                self.handle_single_manifest(
+                    image,
+                    header_digest,
+                    &body,
+                    digest_mismatch,
+                    media_type,
+                )
+                .await
+            }
+        }
+
+        async fn handle_single_manifest(
+            &self,
+            image: &str,
+            header_digest: Result<String, anyhow::Error>,
+            body: &[u8],
+            digest_mismatch: bool,
+            media_type: MediaType,
+        ) -> anyhow::Result<TagManifest> {
+            let json = serde_json::from_slice::<serde_json::Value>(body)?;
+            if let Ok(digest) = header_digest {
+                if media_type.is_schema1() {
+                    return Ok(schema1_manifest(digest, &json, digest_mismatch, media_type));
+                }
+                let config = json
+                    .get("config")
+                    .ok_or_else(|| anyhow!("config missing"))?;
+                let artifact_type = json
+                    .get("artifactType")
+                    .or_else(|| config.get("mediaType"))
+                    .and_then(serde_json::Value::as_str)
+                    .map_or(ArtifactType::Image, ArtifactType::from_media_type);
+                let layers = Layer::from_manifest(&json);
+                let annotations = json
+                    .get("annotations")
+                    .and_then(serde_json::Value::as_object)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_owned())))
+                    .collect();
+                if artifact_type != ArtifactType::Image {
+                    return Ok(TagManifest::Artifact {
+                        digest,
+                        artifact_type,
+                        layers,
+                        digest_mismatch,
+                        media_type,
+                    });
+                }
+                let config_digest = config
+                    .get("digest")
+                    .ok_or_else(|| anyhow!("digest missing"))?
+                    .as_str()
+                    .ok_or_else(|| anyhow!("not a string"))?
+                    .to_owned();
+                let blob_response = self
+                    .send(
+                        self.inner
+                            .get(format!(
+                                "{}/blobs/{config_digest}",
+                                self.repository_url(image)
+                            ))
+                            .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                            .basic_auth(self.username, Some(self.password)),
+                    )
+                    .await?;
+                let blob_bytes = self.read_bytes(blob_response).await?;
+                let config_mismatch = self.digest_mismatch(&config_digest, &blob_bytes);
+                let blob = serde_json::from_slice::<ManifestBlob>(&blob_bytes)?;
+                let created = blob.created.as_deref().and_then(|created| {
+                    chrono::DateTime::parse_from_rfc3339(created)
+                        .map(|created| created.to_utc())
+                        .ok()
+                });
+                let image_config = blob.config.unwrap_or_default();
+                Ok(TagManifest::Nominal {
+                    digest,
+                    created,
+                    architecture: blob.architecture,
+                    layers,
+                    history: blob
+                        .history
+                        .into_iter()
+                        .filter_map(|entry| entry.created_by)
+                        .collect(),
+                    labels: image_config.labels.unwrap_or_default(),
+                    env: image_config.env.unwrap_or_default(),
+                    annotations,
+                    digest_mismatch: digest_mismatch || config_mismatch,
+                    media_type,
+                })
+            } else {
+                Ok(TagManifest::Error {
+                    digest: error_detail(&json),
+                })
+            }
+        }
+
+        async fn handle_multi_arch_manifest(
+            &self,
+            image: &str,
+            header_digest: Result<String, anyhow::Error>,
+            body: &[u8],
+            digest_mismatch: bool,
+            media_type: MediaType,
+        ) -> anyhow::Result<TagManifest> {
+            let digest = header_digest?;
+            let manifest_list = serde_json::from_slice::<ManifestListResponse>(body)?;
+
+            if manifest_list.manifests.is_empty() {
+                return Ok(TagManifest::Error { digest });
+            }
+
+            // Attestations are listed as `unknown/unknown`, they are not platforms
+            let entry_platforms = manifest_list
+                .manifests
+                .iter()
+                .map(|entry| {
+                    entry.platform.as_ref().and_then(|p| {
+                        if p.os == "unknown" && p.architecture == "unknown" {
+                            return None;
+                        }
+                        let base = format!("{}/{}", p.os, p.architecture);
+                        Some(match &p.variant {
+                            Some(v) => format!("{base}/{v}"),
+                            None => base,
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+            let platform_digests: Vec<(String, String)> = entry_platforms
+                .iter()
+                .zip(&manifest_list.manifests)
+                .filter_map(|(platform, entry)| {
+                    platform
+                        .as_ref()
+                        .map(|platform| (platform.clone(), entry.digest.clone()))
+                })
+                .collect();
+            let architectures = platform_digests
+                .iter()
+                .map(|(platform, _)| platform.clone())
+                .collect();
+
+            // `PREFER_PLATFORM` may omit the variant, fall back to the first platform
+            let prefer_platform = common::service::env::prefer_platform();
+            let preferred_digest = platform_digests
+                .iter()
+                .find(|(platform, _)| {
+                    platform == prefer_platform
+                        || platform
+                            .strip_prefix(prefer_platform)
+                            .is_some_and(|variant| variant.starts_with('/'))
+                })
+                .or_else(|| platform_digests.first())
+                .map_or(&manifest_list.manifests[0].digest, |(_, digest)| digest);
+
+            let created = self.fetch_created_date(image, preferred_digest).await.ok();
+
+            // Each fetch waits for a `REGISTRY_MAX_CONCURRENT_REQUESTS` slot, and the whole
+            // resolution is cached with the index. A platform that cannot be fetched is only
+            // left without a size
+            let entry_layers = futures::future::join_all(
+                manifest_list
+                    .manifests
+                    .iter()
+                    .map(|entry| self.fetch_layers(image, &entry.digest)),
+            )
+            .await
+            .into_iter()
+            .zip(&manifest_list.manifests)
+            .map(|(layers, entry)| {
+                layers
+                    .inspect_err(|err| {
+                        tracing::warn!("Could not fetch {image}@{}: {err:#}", entry.digest);
+                    })
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+            let platform_sizes = entry_platforms
+                .into_iter()
+                .zip(&entry_layers)
+                .filter_map(|(platform, layers)| {
+                    platform.map(|platform| {
+                        let size = layers
+                            .as_ref()
+                            .map(|layers| layers.iter().map(|layer| layer.size).sum());
+                        (platform, size)
+                    })
+                })
+                .collect();
+            let layers = entry_layers.into_iter().flatten().flatten().collect();
+
+            Ok(TagManifest::MultiArch {
+                digest,
+                architectures,
+                platform_digests,
+                platform_sizes,
+                created,
+                layers,
+                digest_mismatch,
+                media_type,
+            })
+        }
+
+        async fn fetch_layers(
+            &self,
+            image: &str,
+            manifest_digest: &str,
+        ) -> anyhow::Result<Vec<Layer>> {
+            let request = self
+                .inner
+                .get(format!(
+                    "{}/manifests/{manifest_digest}",
+                    self.repository_url(image)
+                ))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", IMAGE_MANIFEST_ACCEPT);
+            let manifest_response = self.send(request).await?;
+            let json = self
+                .read_json::<serde_json::Value>(manifest_response)
+                .await?;
+            Ok(Layer::from_manifest(&json))
+        }
+
+        async fn fetch_created_date(
+            &self,
+            image: &str,
+            manifest_digest: &str,
+        ) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+            let request = self
+                .inner
+                .get(format!(
+                    "{}/manifests/{manifest_digest}",
+                    self.repository_url(image)
+                ))
+                .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                .basic_auth(self.username, Some(self.password))
+                .header("accept", IMAGE_MANIFEST_ACCEPT);
+            let manifest_response = self.send(request).await?;
+            let json = self
+                .read_json::<serde_json::Value>(manifest_response)
+                .await?;
+            let config_digest = json
+                .get("config")
+                .ok_or_else(|| anyhow!("config missing"))?
+                .get("digest")
+                .ok_or_else(|| anyhow!("digest missing"))?
+                .as_str()
+                .ok_or_else(|| anyhow!("not a string"))?;
+            let blob_response = self
+                .send(
+                    self.inner
+                        .get(format!(
+                            "{}/blobs/{config_digest}",
+                            self.repository_url(image)
+                        ))
+                        .timeout_secs(common::service::env::registry_manifest_timeout_secs())
+                        .basic_auth(self.username, Some(self.password)),
+                )
+                .await?;
+            let blob = self.read_json::<ManifestBlob>(blob_response).await?;
+            let created = blob.created.ok_or_else(|| anyhow!("created missing"))?;
+            let created = chrono::DateTime::parse_from_rfc3339(&created)?.to_utc();
+            Ok(created)
+        }
+
+        #[instrument(skip(self))]
+        pub async fn delete_tag(&self, image: &str, digest: &str) -> anyhow::Result<()> {
+            info!("Calling delete tag request");
+            let request = self
+                .inner
+                .delete(format!("{}/manifests/{digest}", self.repository_url(image)))
+                .basic_auth(self.username, Some(self.password));
+            self.send_scoped(
+                request,
+                Some(format!(
+                    "repository:{}:pull,push,delete",
+                    self.repository_name(image)
+                )),
+            )
+            .await?
+            .error_for_status()?;
+
+            Ok(())
+        }
+    }
+
+    /// Proxies of the `HTTP_PROXY` and `HTTPS_PROXY` variables, lowercase ones included, bypassed
+    /// for `no_proxy`
+    fn system_proxies(no_proxy: &str) -> anyhow::Result<Vec<reqwest::Proxy>> {
+        let variable = |names: [&str; 2]| {
+            names
+                .into_iter()
+                .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        };
+        let mut proxies = Vec::new();
+        if let Some(proxy_url) = variable(["HTTP_PROXY", "http_proxy"]) {
+            proxies.push(
+                reqwest::Proxy::http(&proxy_url)
+                    .with_context(|| format!("invalid HTTP_PROXY: {proxy_url}"))?
+                    .no_proxy(reqwest::NoProxy::from_string(no_proxy)),
+            );
+        }
+        if let Some(proxy_url) = variable(["HTTPS_PROXY", "https_proxy"]) {
+            proxies.push(
+                reqwest::Proxy::https(&proxy_url)
+                    .with_context(|| format!("invalid HTTPS_PROXY: {proxy_url}"))?
+                    .no_proxy(reqwest::NoProxy::from_string(no_proxy)),
+            );
+        }
+        Ok(proxies)
+    }
+
+    fn with_bearer(mut request: reqwest::Request, token: &str) -> anyhow::Result<reqwest::Request> {
+        let mut authorization = reqwest::header::HeaderValue::from_str(&format!("Bearer {token}"))?;
+        authorization.set_sensitive(true);
+        request
+            .headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, authorization);
+        Ok(request)
+    }
+
+    /// Error pages of proxies and web servers in front of a misconfigured registry
+    fn is_html(response: &reqwest::Response) -> bool {
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/html"))
+    }
+
+    /// Most useful part of an error body, tried in order: the revision of the distribution
+    /// registry, the OCI `message`, a Docker `detail` string, the top-level `message` of
+    /// Harbor, then the first error or the whole body as JSON
+    fn error_detail(json: &serde_json::Value) -> String {
+        let first_error = json
+            .get("errors")
+            .and_then(serde_json::Value::as_array)
+            .and_then(|errors| errors.first());
+        first_error
+            .and_then(|error| error.pointer("/detail/Revision"))
+            .or_else(|| first_error.and_then(|error| error.get("message")))
+            .or_else(|| first_error.and_then(|error| error.get("detail")))
+            .or_else(|| json.get("message"))
+            .and_then(serde_json::Value::as_str)
+            .filter(|detail| !detail.is_empty())
+            .map_or_else(
+                || first_error.unwrap_or(json).to_string(),
+                ToOwned::to_owned,
+            )
+    }
+
+    /// Schema 1 manifests have no config blob, the config of the top layer is embedded as a
+    /// string in the first `history` entry. Layer sizes are not listed at all
+    fn schema1_manifest(
+        digest: String,
+        json: &serde_json::Value,
+        digest_mismatch: bool,
+        media_type: MediaType,
+    ) -> TagManifest {
+        let config = json
+            .get("history")
+            .and_then(|history| history.get(0))
+            .and_then(|entry| entry.get("v1Compatibility"))
+            .and_then(serde_json::Value::as_str)
+            .and_then(|config| serde_json::from_str::<ManifestBlob>(config).ok());
+        let created = config
+            .as_ref()
+            .and_then(|config| config.created.as_deref())
+            .and_then(|created| chrono::DateTime::parse_from_rfc3339(created).ok())
+            .map(|created| created.to_utc());
+        let architecture = json
+            .get("architecture")
+            .and_then(serde_json::Value::as_str)
+            .map(ToOwned::to_owned)
+            .or_else(|| {
+                config
+                    .as_ref()
+                    .and_then(|config| config.architecture.clone())
+            });
+        let image_config = config.and_then(|config| config.config).unwrap_or_default();
+        TagManifest::Nominal {
+            digest,
+            created,
+            architecture,
+            layers: Vec::new(),
+            history: Vec::new(),
+            labels: image_config.labels.unwrap_or_default(),
+            env: image_config.env.unwrap_or_default(),
+            annotations: BTreeMap::new(),
+            digest_mismatch,
+            media_type,
+        }
+    }
+}
+
+pub mod dto {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize)]
+    pub struct CatalogResponse {
+        pub repositories: Vec<String>,
+    }
+
+    /// One page of the catalog walked with the registry cursor
+    pub struct CatalogPage {
+        pub repositories: Vec<String>,
+        /// `last` value of the following page, `None` on the last page
+        pub next: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct TagsResponse {
+        pub tags: Option<Vec<String>>,
+    }
+
+    /// `WWW-Authenticate: Bearer` challenge of a registry delegating authentication to a token
+    /// server
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct BearerChallenge {
+        pub realm: String,
+        pub service: Option<String>,
+        pub scope: Option<String>,
+    }
+
+    impl BearerChallenge {
+        /// `None` for any other scheme or without a realm
+        pub fn parse(header: &str) -> Option<Self> {
+            let (scheme, params) = header.trim().split_once(' ')?;
+            if !scheme.eq_ignore_ascii_case("bearer") {
+                return None;
+            }
+            let mut values = BTreeMap::new();
+            let mut rest = params.trim();
+            while let Some((name, after)) = rest.split_once('=') {
+                let name = name.trim().to_ascii_lowercase();
+                // Quoted values may contain commas, as in `repository:app:pull,push`
+                let (value, after) = match after.strip_prefix('"') {
+                    Some(quoted) => {
+                        let (value, after) = quoted.split_once('"')?;
+                        (value, after)
+                    }
+                    None => after
+                        .split_once(',')
+                        .map_or((after, ""), |(value, after)| (value, after)),
+                };
+                values.insert(name, value.trim().to_owned());
+                rest = after.trim_start().trim_start_matches(',');
+            }
+            Some(Self {
+                realm: values.remove("realm")?,
+                service: values.remove("service"),
+                scope: values.remove("scope"),
+            })
+        }
+    }
+
+    /// How the registry authenticates clients
+    pub enum AuthScheme {
+        /// The API base answers without credentials
+        Anonymous,
+        Basic,
+        /// Tokens delivered by the token server at `realm`
+        Bearer {
+            realm: String,
+        },
+        Other(String),
+    }
+
+    /// Entries held by the registry client
+    pub struct CacheStats {
+        /// Manifests kept for conditional requests
+        pub manifests: usize,
+        /// Digests resolved since startup, across images
+        pub seen_digests: usize,
+        /// Bearer tokens, one per scope
+        pub tokens: usize,
+    }
+
+    /// Docker token servers answer `token`, OAuth2 ones `access_token`
+    #[derive(Deserialize)]
+    pub struct TokenResponse {
+        pub token: Option<String>,
+        pub access_token: Option<String>,
+        /// Lifetime in seconds
+        pub expires_in: Option<u64>,
+    }
+
+    #[derive(Clone, Debug, Serialize)]
+    #[serde(tag = "kind", rename_all = "snake_case")]
+    pub enum TagManifest {
+        Nominal {
+            digest: String,
+            created: Option<chrono::DateTime<chrono::Utc>>,
+            architecture: Option<String>,
+            layers: Vec<Layer>,
+            /// `created_by` build commands of the config history, oldest first
+            history: Vec<String>,
+            /// `Labels` of the image config, set at build time
+            labels: BTreeMap<String, String>,
+            /// `Env` of the image config, `KEY=value` entries
+            env: Vec<String>,
+            /// `annotations` of the manifest itself, often provenance set when pushing
+            annotations: BTreeMap<String, String>,
+            /// The manifest or its config does not hash to its digest, see `VERIFY_DIGESTS`
+            digest_mismatch: bool,
+            media_type: MediaType,
+        },
+        MultiArch {
+            digest: String,
+            architectures: Vec<String>,
+            /// Platform (`os/architecture[/variant]`) and manifest digest of each index entry
+            platform_digests: Vec<(String, String)>,
+            /// Summed layer size of each platform, attestations excluded. `None` when the
+            /// manifest of the platform could not be fetched
+            platform_sizes: Vec<(String, Option<u64>)>,
+            created: Option<chrono::DateTime<chrono::Utc>>,
+            /// Layers of every platform
+            layers: Vec<Layer>,
+            /// The index does not hash to its digest
+            digest_mismatch: bool,
+            media_type: MediaType,
+        },
+        /// Non-image OCI artifact (Helm chart, WASM module, ...), its config is not an image config
+        Artifact {
+            digest: String,
+            artifact_type: ArtifactType,
+            layers: Vec<Layer>,
+            digest_mismatch: bool,
+            media_type: MediaType,
+        },
+        Error {
+            digest: String,
+        },
+    }
+
+    impl TagManifest {
+        pub fn digest(&self) -> &str {
+            match self {
+                Self::Nominal { digest, .. }
+                | Self::MultiArch { digest, .. }
+                | Self::Artifact { digest, .. }
+                | Self::Error { digest } => digest,
+            }
+        }
+
+        pub fn digest_mismatch(&self) -> bool {
+            match self {
+                Self::Nominal {
+                    digest_mismatch, ..
+                }
+                | Self::MultiArch {
+                    digest_mismatch, ..
+                }
+                | Self::Artifact {
+                    digest_mismatch, ..
+                } => *digest_mismatch,
+                Self::Error { .. } => false,
+            }
+        }
+
+        pub fn media_type(&self) -> Option<&MediaType> {
+            match self {
+                Self::Nominal { media_type, .. }
+                | Self::MultiArch { media_type, .. }
+                | Self::Artifact { media_type, .. } => Some(media_type),
+                Self::Error { .. } => None,
+            }
+        }
+
+        pub fn layers(&self) -> &[Layer] {
+            match self {
+                Self::Nominal { layers, .. }
+                | Self::MultiArch { layers, .. }
+                | Self::Artifact { layers, .. } => layers,
+                Self::Error { .. } => &[],
+            }
+        }
+    }
+
+    /// Format of a manifest as served by the registry
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+    pub struct MediaType {
+        /// `mediaType` of the manifest, its `Content-Type` when the manifest has none
+        pub media_type: String,
+        pub schema_version: Option<u64>,
+    }
+
+    impl MediaType {
+        pub fn from_manifest(content_type: &str, body: &[u8]) -> Self {
+            #[derive(Deserialize)]
+            #[serde(rename_all = "camelCase")]
+            struct Header {
+                media_type: Option<String>,
+                schema_version: Option<u64>,
+            }
+
+            let header = serde_json::from_slice::<Header>(body).ok();
+            let content_type = content_type.split(';').next().unwrap_or_default().trim();
+            Self {
+                media_type: header
+                    .as_ref()
+                    .and_then(|header| header.media_type.clone())
+                    .unwrap_or_else(|| content_type.to_owned()),
+                schema_version: header.and_then(|header| header.schema_version),
+            }
+        }
+
+        /// Docker image manifest v2 schema 1, deprecated and refused by recent Docker versions
+        pub fn is_schema1(&self) -> bool {
+            self.schema_version == Some(1)
+                || self
+                    .media_type
+                    .starts_with("application/vnd.docker.distribution.manifest.v1")
+        }
+
+        pub fn label(&self) -> &str {
+            match self.media_type.as_str() {
+                _ if self.is_schema1() => "Docker schema 1",
+                "application/vnd.docker.distribution.manifest.v2+json" => "Docker schema 2",
+                "application/vnd.docker.distribution.manifest.list.v2+json" => {
+                    "Docker manifest list"
+                }
+                "application/vnd.oci.image.manifest.v1+json" => "OCI manifest",
+                "application/vnd.oci.image.index.v1+json" => "OCI index",
+                "" => "?",
+                media_type => media_type,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Layer {
+        pub digest: String,
+        pub size: u64,
+    }
+
+    impl Layer {
+        /// `layers` of an image manifest, empty when absent or malformed
+        pub fn from_manifest(manifest: &serde_json::Value) -> Vec<Self> {
+            manifest
+                .get("layers")
+                .and_then(|layers| Vec::<Self>::deserialize(layers).ok())
+                .unwrap_or_default()
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ArtifactType {
+        Image,
+        Helm,
+        Wasm,
+        Unknown,
+    }
+
+    impl ArtifactType {
+        /// Classifies a manifest from its `artifactType` or `config.mediaType`
+        pub fn from_media_type(media_type: &str) -> Self {
+            match media_type {
+                "application/vnd.docker.container.image.v1+json"
+                | "application/vnd.oci.image.config.v1+json" => Self::Image,
+                media_type if media_type.contains("helm") => Self::Helm,
+                media_type if media_type.contains("wasm") => Self::Wasm,
+                _ => Self::Unknown,
+            }
+        }
+
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Image => "image",
+                Self::Helm => "helm",
+                Self::Wasm => "wasm",
+                Self::Unknown => "unknown",
             }
         }
     }
 
     #[derive(Deserialize)]
     pub struct ManifestBlob {
-        pub architecture: String,
-        pub created: String,
+        pub architecture: Option<String>,
+        pub created: Option<String>,
+        #[serde(default)]
+        pub history: Vec<HistoryEntry>,
+        pub config: Option<ImageConfig>,
+    }
+
+    #[derive(Default, Deserialize)]
+    pub struct ImageConfig {
+        #[serde(rename = "Labels")]
+        pub labels: Option<BTreeMap<String, String>>,
+        #[serde(rename = "Env")]
+        pub env: Option<Vec<String>>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct HistoryEntry {
+        pub created_by: Option<String>,
+    }
+
+    /// Registry implementation, selects the extension APIs queried on top of the distribution API
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum RegistryFlavor {
+        #[default]
+        Distribution,
+        Harbor,
+    }
+
+    impl std::str::FromStr for RegistryFlavor {
+        type Err = ();
+
+        fn from_str(value: &str) -> Result<Self, Self::Err> {
+            match value.to_lowercase().as_str() {
+                "distribution" => Ok(Self::Distribution),
+                "harbor" => Ok(Self::Harbor),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl RegistryFlavor {
+        pub const fn as_str(self) -> &'static str {
+            match self {
+                Self::Distribution => "distribution",
+                Self::Harbor => "harbor",
+            }
+        }
+
+        pub const fn has_pull_counts(self) -> bool {
+            matches!(self, Self::Harbor)
+        }
+
+        pub const fn has_storage_usage(self) -> bool {
+            matches!(self, Self::Harbor)
+        }
+
+        /// The distribution API has no repository deletion, empty repositories stay listed
+        pub const fn can_delete_repositories(self) -> bool {
+            matches!(self, Self::Harbor)
+        }
+    }
+
+    /// Entry of the Harbor `/api/v2.0/repositories` listing, `name` is `project/repository`
+    #[derive(Deserialize)]
+    pub struct HarborRepository {
+        pub name: String,
+        #[serde(default)]
+        pub pull_count: u64,
+        #[serde(default)]
+        pub artifact_count: u64,
+    }
+
+    /// Entry of the Harbor `/api/v2.0/quotas` listing, one per project
+    #[derive(Deserialize)]
+    pub struct HarborQuota {
+        pub hard: HarborStorage,
+        pub used: HarborStorage,
+    }
+
+    /// In bytes, `-1` for an unlimited quota
+    #[derive(Deserialize)]
+    pub struct HarborStorage {
+        #[serde(default)]
+        pub storage: i64,
+    }
+
+    /// Storage of the registry from the extension API of the registry flavor
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct StorageUsage {
+        pub used: u64,
+        /// `None` when unlimited
+        pub quota: Option<u64>,
     }
 
     #[derive(Deserialize)]
@@ -324,6 +2003,20 @@ pub mod dto {
         pub platform: Option<Platform>,
     }
 
+    /// Index of the manifests referring to a subject
+    #[derive(Deserialize)]
+    pub struct ReferrersResponse {
+        #[serde(default)]
+        pub manifests: Vec<Referrer>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Referrer {
+        pub digest: String,
+        pub artifact_type: Option<String>,
+    }
+
     #[derive(Deserialize)]
     pub struct Platform {
         pub architecture: String,
@@ -331,3 +2024,982 @@ pub mod dto {
         pub variant: Option<String>,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{
+            basic_auth, header, header_exists, method, path, query_param, query_param_is_missing,
+        },
+    };
+
+    use crate::registry::{
+        api::Client,
+        dto::{BearerChallenge, RegistryFlavor, StorageUsage, TagManifest},
+    };
+
+    const DIGEST: &str = "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+    const CONFIG_DIGEST: &str =
+        "sha256:2222222222222222222222222222222222222222222222222222222222222222";
+
+    async fn registry() -> (MockServer, Client) {
+        let server = MockServer::start().await;
+        let client = Client::for_tests(&server.uri());
+        (server, client)
+    }
+
+    #[test]
+    fn bearer_challenge_keeps_commas_of_quoted_scopes() {
+        let challenge = BearerChallenge::parse(
+            r#"Bearer realm="https://auth.test/token",service="registry.test",scope="repository:alpine:pull,push""#,
+        );
+
+        assert_eq!(
+            challenge,
+            Some(BearerChallenge {
+                realm: "https://auth.test/token".to_owned(),
+                service: Some("registry.test".to_owned()),
+                scope: Some("repository:alpine:pull,push".to_owned()),
+            })
+        );
+        assert_eq!(BearerChallenge::parse(r#"Basic realm="registry""#), None);
+    }
+
+    #[tokio::test]
+    async fn deletion_escalates_a_pull_scoped_token() {
+        let (server, client) = registry().await;
+        let challenge = |scope: &str| {
+            format!(
+                r#"Bearer realm="{}/token",service="registry.test",scope="{scope}""#,
+                server.uri()
+            )
+        };
+        for (scope, token) in [
+            ("repository:alpine:pull", "pull-token"),
+            ("repository:alpine:pull,push,delete", "delete-token"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path("/token"))
+                .and(query_param("service", "registry.test"))
+                .and(query_param("scope", scope))
+                .and(basic_auth("user", "pass"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "token": token })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(header("authorization", "Bearer pull-token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["3.20"] })),
+            )
+            .with_priority(1)
+            .expect(2)
+            .mount(&server)
+            .await;
+        // The cached token is sent up front, only the first listing is challenged
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .insert_header("www-authenticate", challenge("repository:alpine:pull")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v2/alpine/manifests/{DIGEST}")))
+            .and(header("authorization", "Bearer delete-token"))
+            .respond_with(ResponseTemplate::new(202))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v2/alpine/manifests/{DIGEST}")))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .insert_header("www-authenticate", challenge("repository:alpine:delete")),
+            )
+            .mount(&server)
+            .await;
+
+        let tags = client.tags("alpine").await.unwrap();
+        // The pull token is cached, the token server is asked once per scope
+        client.tags("alpine").await.unwrap();
+        client.delete_tag("alpine", DIGEST).await.unwrap();
+
+        assert_eq!(tags.tags.unwrap(), ["3.20"]);
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_fetched_again() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "token": "short-token", "expires_in": 0 })),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(header("authorization", "Bearer short-token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["3.20"] })),
+            )
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(ResponseTemplate::new(401).insert_header(
+                "www-authenticate",
+                format!(r#"Bearer realm="{}/token""#, server.uri()),
+            ))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        client.tags("alpine").await.unwrap();
+        client.tags("alpine").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn extra_headers_are_not_sent_to_the_token_server() {
+        let (server, client) = registry().await;
+        let client = client.with_extra_header("x-api-key", "secret");
+        let token_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/token"))
+            .and(header_exists("x-api-key"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(0)
+            .mount(&token_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "token": "token" })))
+            .expect(1)
+            .mount(&token_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(header("authorization", "Bearer token"))
+            .and(header("x-api-key", "secret"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["3.20"] })),
+            )
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(header("x-api-key", "secret"))
+            .respond_with(ResponseTemplate::new(401).insert_header(
+                "www-authenticate",
+                format!(r#"Bearer realm="{}/token""#, token_server.uri()),
+            ))
+            .mount(&server)
+            .await;
+
+        client.tags("alpine").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn catalog_lists_repositories() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .and(basic_auth("user", "pass"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "repositories": ["alpine", "nginx"] })),
+            )
+            .mount(&server)
+            .await;
+
+        let catalog = client.catalog().await.unwrap();
+
+        assert_eq!(catalog.repositories, ["alpine", "nginx"]);
+    }
+
+    #[tokio::test]
+    async fn refused_catalog_is_an_error_without_known_repositories() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let err = client.catalog().await.map(drop).unwrap_err();
+
+        assert!(!client.catalog_unsupported());
+        assert!(err.to_string().contains("404"));
+    }
+
+    #[tokio::test]
+    async fn unauthorized_catalog_is_an_error_even_with_known_repositories() {
+        let (server, client) = registry().await;
+        let client = client.with_namespace("acme", Vec::leak(vec!["api".to_owned()]));
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let err = client.catalog().await.map(drop).unwrap_err();
+
+        assert!(!client.catalog_unsupported());
+        assert!(err.to_string().contains("401"));
+    }
+
+    #[tokio::test]
+    async fn ping_rejects_an_html_page() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("<html>Welcome</html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+
+        let err = client.ping().await.unwrap_err();
+
+        assert!(err.to_string().contains("HTML page"));
+    }
+
+    #[tokio::test]
+    async fn namespaced_registry_falls_back_to_known_repositories() {
+        let (server, client) = registry().await;
+        let client = client.with_namespace("acme", Vec::leak(vec!["api".to_owned()]));
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/acme/api/tags/list"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "acme/api", "tags": ["1.0"] })),
+            )
+            .mount(&server)
+            .await;
+
+        let catalog = client.catalog().await.unwrap();
+        let tags = client.tags("api").await.unwrap();
+
+        assert_eq!(catalog.repositories, ["api"]);
+        assert!(client.catalog_unsupported());
+        assert_eq!(tags.tags.unwrap(), ["1.0"]);
+    }
+
+    #[tokio::test]
+    async fn catalog_stops_at_the_max_size() {
+        let (server, client) = registry().await;
+        let client = client.with_max_catalog_size(2);
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .and(query_param("n", "2"))
+            .respond_with(
+                // Registries ignoring `n` answer with the whole catalog
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "repositories": ["alpine", "nginx", "redis"] })),
+            )
+            .mount(&server)
+            .await;
+
+        let catalog = client.catalog().await.unwrap();
+
+        assert_eq!(catalog.repositories, ["alpine", "nginx"]);
+        assert_eq!(client.catalog_truncated(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn harbor_pull_counts() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v2.0/repositories"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "library/alpine", "pull_count": 42, "artifact_count": 3 },
+                { "name": "library/nginx" }
+            ])))
+            // The second call is served from the cache
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let plain = client.pull_counts().await.unwrap();
+        let client = client.with_flavor(RegistryFlavor::Harbor);
+        let harbor = client.pull_counts().await.unwrap();
+        let cached = client.pull_counts().await.unwrap();
+
+        assert!(plain.is_empty(), "distribution has no pull counts");
+        assert_eq!(harbor["library/alpine"], 42);
+        assert_eq!(harbor["library/nginx"], 0);
+        assert_eq!(cached, harbor);
+    }
+
+    #[tokio::test]
+    async fn tags_and_count_tags() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["3.19", "3.20", "latest"] })),
+            )
+            .mount(&server)
+            .await;
+
+        let tags = client.tags("alpine").await.unwrap();
+
+        assert_eq!(tags.tags.unwrap(), ["3.19", "3.20", "latest"]);
+        assert_eq!(client.count_tags("alpine").await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn tags_follow_the_link_header() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(query_param_is_missing("last"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(
+                        "link",
+                        r#"</v2/alpine/tags/list?n=2&last=3.20>; rel="next""#,
+                    )
+                    .set_body_json(json!({ "name": "alpine", "tags": ["3.19", "3.20"] })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(query_param("last", "3.20"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["latest"] })),
+            )
+            .mount(&server)
+            .await;
+
+        let tags = client.tags("alpine").await.unwrap();
+
+        assert_eq!(tags.tags.unwrap(), ["3.19", "3.20", "latest"]);
+    }
+
+    #[tokio::test]
+    async fn tags_continue_after_a_full_page_without_link() {
+        let (server, client) = registry().await;
+        let first_page = (0..1000).map(|i| format!("{i:04}")).collect::<Vec<_>>();
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(query_param("n", "1000"))
+            .and(query_param_is_missing("last"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": first_page })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .and(query_param("last", "0999"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["latest"] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tags = client.tags("alpine").await.unwrap().tags.unwrap();
+
+        assert_eq!(tags.len(), 1001);
+        assert_eq!(tags.last().unwrap(), "latest");
+    }
+
+    #[tokio::test]
+    async fn tags_of_a_registry_ignoring_last_are_not_repeated() {
+        let (server, client) = registry().await;
+        let page = (0..1000).map(|i| format!("{i:04}")).collect::<Vec<_>>();
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "name": "alpine", "tags": page })),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let tags = client.tags("alpine").await.unwrap().tags.unwrap();
+
+        assert_eq!(tags.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn count_tags_of_an_emptied_repository() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "name": "alpine", "tags": null })),
+            )
+            .mount(&server)
+            .await;
+
+        assert_eq!(client.count_tags("alpine").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn manifest_of_an_image() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/latest"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", DIGEST)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 2,
+                            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                            "config": {
+                                "mediaType": "application/vnd.oci.image.config.v1+json",
+                                "digest": CONFIG_DIGEST,
+                                "size": 100
+                            },
+                            "layers": [
+                                { "digest": "sha256:aaaa", "size": 1000 },
+                                { "digest": "sha256:bbbb", "size": 234 }
+                            ],
+                            "annotations": { "org.opencontainers.image.revision": "abc123" }
+                        })
+                        .to_string(),
+                        "application/vnd.oci.image.manifest.v1+json",
+                    ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/blobs/{CONFIG_DIGEST}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "architecture": "amd64",
+                "created": "2024-05-01T12:00:00Z",
+                "history": [{ "created_by": "/bin/sh -c #(nop)  CMD [\"/bin/sh\"]" }],
+                "config": { "Labels": { "org.opencontainers.image.version": "3.20" } }
+            })))
+            .mount(&server)
+            .await;
+
+        let manifest = client.manifest("alpine", "latest").await.unwrap();
+
+        let TagManifest::Nominal {
+            digest,
+            created,
+            architecture,
+            layers,
+            history,
+            labels,
+            annotations,
+            digest_mismatch,
+            media_type,
+            ..
+        } = manifest
+        else {
+            panic!("expected a nominal manifest, got {manifest:?}");
+        };
+        assert_eq!(digest, DIGEST);
+        assert_eq!(created.unwrap().to_rfc3339(), "2024-05-01T12:00:00+00:00");
+        assert_eq!(architecture.as_deref(), Some("amd64"));
+        assert_eq!(layers.iter().map(|layer| layer.size).sum::<u64>(), 1234);
+        assert_eq!(history, ["/bin/sh -c #(nop)  CMD [\"/bin/sh\"]"]);
+        assert_eq!(
+            labels.keys().collect::<Vec<_>>(),
+            ["org.opencontainers.image.version"]
+        );
+        assert_eq!(annotations["org.opencontainers.image.revision"], "abc123");
+        assert!(!digest_mismatch, "verification is disabled");
+        assert_eq!(media_type.label(), "OCI manifest");
+        assert_eq!(media_type.schema_version, Some(2));
+    }
+
+    #[tokio::test]
+    async fn schema1_manifest_is_read_without_config() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/legacy/manifests/latest"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", DIGEST)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 1,
+                            "name": "legacy",
+                            "tag": "latest",
+                            "architecture": "amd64",
+                            "fsLayers": [{ "blobSum": "sha256:aaaa" }],
+                            "history": [{
+                                "v1Compatibility": "{\"architecture\":\"amd64\",\"created\":\"2016-03-01T12:00:00Z\",\"config\":{\"Labels\":{\"maintainer\":\"ops\"}}}"
+                            }],
+                            "signatures": [{ "header": { "alg": "ES256" }, "signature": "c2ln", "protected": "cHJvdA" }]
+                        })
+                        .to_string(),
+                        "application/vnd.docker.distribution.manifest.v1+prettyjws",
+                    ),
+            )
+            .mount(&server)
+            .await;
+
+        let manifest = client.manifest("legacy", "latest").await.unwrap();
+
+        let TagManifest::Nominal {
+            created,
+            architecture,
+            labels,
+            media_type,
+            ..
+        } = manifest
+        else {
+            panic!("expected a nominal manifest, got {manifest:?}");
+        };
+        assert_eq!(created.unwrap().to_rfc3339(), "2016-03-01T12:00:00+00:00");
+        assert_eq!(architecture.as_deref(), Some("amd64"));
+        assert_eq!(labels["maintainer"], "ops");
+        assert!(media_type.is_schema1());
+        assert_eq!(media_type.label(), "Docker schema 1");
+    }
+
+    #[tokio::test]
+    async fn index_sizes_each_platform_without_attestations() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/multi/manifests/latest"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", DIGEST)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 2,
+                            "mediaType": "application/vnd.oci.image.index.v1+json",
+                            "manifests": [
+                                { "digest": "sha256:amd", "platform": { "os": "linux", "architecture": "amd64" } },
+                                { "digest": "sha256:arm", "platform": { "os": "linux", "architecture": "arm64", "variant": "v8" } },
+                                { "digest": "sha256:att", "platform": { "os": "unknown", "architecture": "unknown" } }
+                            ]
+                        })
+                        .to_string(),
+                        "application/vnd.oci.image.index.v1+json",
+                    ),
+            )
+            .mount(&server)
+            .await;
+        for (digest, sizes) in [
+            ("sha256:amd", [100, 20]),
+            ("sha256:arm", [90, 28]),
+            ("sha256:att", [1, 1]),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/v2/multi/manifests/{digest}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "schemaVersion": 2,
+                    "config": { "digest": CONFIG_DIGEST, "size": 2 },
+                    "layers": sizes.map(|size| json!({ "digest": "sha256:aaaa", "size": size })),
+                })))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/multi/blobs/{CONFIG_DIGEST}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "created": "2024-05-01T12:00:00Z" })),
+            )
+            .mount(&server)
+            .await;
+
+        let manifest = client.manifest("multi", "latest").await.unwrap();
+
+        let TagManifest::MultiArch {
+            platform_sizes,
+            layers,
+            ..
+        } = manifest
+        else {
+            panic!("expected an index, got {manifest:?}");
+        };
+        assert_eq!(
+            platform_sizes,
+            [
+                ("linux/amd64".to_owned(), Some(120)),
+                ("linux/arm64/v8".to_owned(), Some(118))
+            ]
+        );
+        assert_eq!(layers.iter().map(|layer| layer.size).sum::<u64>(), 240);
+    }
+
+    #[tokio::test]
+    async fn index_keeps_the_platforms_left_by_a_failed_one() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/multi/manifests/latest"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", DIGEST)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 2,
+                            "mediaType": "application/vnd.oci.image.index.v1+json",
+                            "manifests": [
+                                { "digest": "sha256:amd", "platform": { "os": "linux", "architecture": "amd64" } },
+                                { "digest": "sha256:arm", "platform": { "os": "linux", "architecture": "arm64" } }
+                            ]
+                        })
+                        .to_string(),
+                        "application/vnd.oci.image.index.v1+json",
+                    ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/multi/manifests/sha256:amd"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schemaVersion": 2,
+                "config": { "digest": CONFIG_DIGEST, "size": 2 },
+                "layers": [{ "digest": "sha256:aaaa", "size": 120 }],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/multi/manifests/sha256:arm"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/multi/blobs/{CONFIG_DIGEST}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "created": "2024-05-01T12:00:00Z" })),
+            )
+            .mount(&server)
+            .await;
+
+        let manifest = client.manifest("multi", "latest").await.unwrap();
+
+        let TagManifest::MultiArch {
+            architectures,
+            platform_sizes,
+            ..
+        } = manifest
+        else {
+            panic!("expected an index, got {manifest:?}");
+        };
+        assert_eq!(architectures, ["linux/amd64", "linux/arm64"]);
+        assert_eq!(
+            platform_sizes,
+            [
+                ("linux/amd64".to_owned(), Some(120)),
+                ("linux/arm64".to_owned(), None)
+            ]
+        );
+    }
+
+    /// Serves `manifest` under `tag` with `digest` as its announced digest, and `config` as its
+    /// config blob
+    async fn mount_image(
+        server: &MockServer,
+        tag: &str,
+        digest: &str,
+        manifest: &str,
+        config: &str,
+    ) {
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/manifests/{tag}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", digest)
+                    .set_body_raw(
+                        manifest.to_owned(),
+                        "application/vnd.oci.image.manifest.v1+json",
+                    ),
+            )
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/blobs/{}", sha256(config))))
+            .respond_with(ResponseTemplate::new(200).set_body_string(config))
+            .mount(server)
+            .await;
+    }
+
+    fn sha256(content: &str) -> String {
+        use sha2::{Digest, Sha256};
+
+        format!("sha256:{:x}", Sha256::digest(content.as_bytes()))
+    }
+
+    fn manifest_referencing(config_digest: &str) -> String {
+        json!({
+            "schemaVersion": 2,
+            "config": {
+                "mediaType": "application/vnd.oci.image.config.v1+json",
+                "digest": config_digest
+            },
+            "layers": []
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn verified_manifest_matching_its_digest() {
+        let (server, client) = registry().await;
+        let client = client.with_digest_verification();
+        let config = r#"{"architecture":"amd64"}"#;
+        let manifest = manifest_referencing(&sha256(config));
+        mount_image(&server, "latest", &sha256(&manifest), &manifest, config).await;
+
+        let manifest = client.manifest("alpine", "latest").await.unwrap();
+
+        assert!(!manifest.digest_mismatch());
+    }
+
+    #[tokio::test]
+    async fn verified_manifest_not_matching_its_digest() {
+        let (server, client) = registry().await;
+        let client = client.with_digest_verification();
+        let config = r#"{"architecture":"amd64"}"#;
+        let manifest = manifest_referencing(&sha256(config));
+        mount_image(&server, "latest", DIGEST, &manifest, config).await;
+
+        let manifest = client.manifest("alpine", "latest").await.unwrap();
+
+        assert_eq!(manifest.digest(), DIGEST);
+        assert!(manifest.digest_mismatch());
+    }
+
+    #[tokio::test]
+    async fn verified_config_not_matching_its_digest() {
+        let (server, client) = registry().await;
+        let client = client.with_digest_verification();
+        let config = r#"{"architecture":"amd64"}"#;
+        let manifest = manifest_referencing(&sha256(config));
+        mount_image(&server, "latest", &sha256(&manifest), &manifest, config).await;
+        // Takes precedence over the genuine config mounted above
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/blobs/{}", sha256(config))))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"architecture":"arm64"}"#))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let manifest = client.manifest("alpine", "latest").await.unwrap();
+
+        assert!(manifest.digest_mismatch());
+    }
+
+    #[tokio::test]
+    async fn manifest_without_a_digest_header_is_digested() {
+        let (server, client) = registry().await;
+        let config = r#"{"architecture":"amd64"}"#;
+        let manifest = manifest_referencing(&sha256(config));
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                manifest.clone(),
+                "application/vnd.oci.image.manifest.v1+json",
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/blobs/{}", sha256(config))))
+            .respond_with(ResponseTemplate::new(200).set_body_string(config))
+            .mount(&server)
+            .await;
+
+        let tag = client.manifest("alpine", "latest").await.unwrap();
+
+        assert!(matches!(tag, TagManifest::Nominal { .. }));
+        assert_eq!(tag.digest(), sha256(&manifest));
+    }
+
+    #[tokio::test]
+    async fn manifest_digest_without_a_digest_header_is_computed() {
+        let (server, client) = registry().await;
+        let manifest = manifest_referencing(CONFIG_DIGEST);
+        Mock::given(method("HEAD"))
+            .and(path("/v2/alpine/manifests/latest"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                manifest.clone(),
+                "application/vnd.oci.image.manifest.v1+json",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let digest = client.manifest_digest("alpine", "latest").await.unwrap();
+
+        assert_eq!(digest, sha256(&manifest));
+    }
+
+    #[tokio::test]
+    async fn manifest_error_without_a_revision_shows_its_message() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/oci"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "errors": [{ "code": "MANIFEST_UNKNOWN", "message": "manifest unknown" }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/odd"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "errors": [{ "code": "X" }] })),
+            )
+            .mount(&server)
+            .await;
+
+        let oci = client.manifest("alpine", "oci").await.unwrap();
+        let odd = client.manifest("alpine", "odd").await.unwrap();
+
+        assert!(matches!(oci, TagManifest::Error { digest } if digest == "manifest unknown"));
+        assert!(matches!(odd, TagManifest::Error { digest } if digest == r#"{"code":"X"}"#));
+    }
+
+    #[tokio::test]
+    async fn manifest_with_a_missing_revision() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/broken"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "errors": [{
+                    "code": "MANIFEST_UNKNOWN",
+                    "message": "manifest unknown",
+                    "detail": { "Name": "alpine", "Revision": DIGEST }
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let manifest = client.manifest("alpine", "broken").await.unwrap();
+
+        assert!(matches!(manifest, TagManifest::Error { digest } if digest == DIGEST));
+    }
+
+    #[tokio::test]
+    async fn delete_tag_deletes_the_manifest() {
+        let (server, client) = registry().await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v2/alpine/manifests/{DIGEST}")))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        client.delete_tag("alpine", DIGEST).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_tag_reports_registry_errors() {
+        let (server, client) = registry().await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v2/alpine/manifests/{DIGEST}")))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&server)
+            .await;
+
+        assert!(client.delete_tag("alpine", DIGEST).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn harbor_artifact_count() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/api/v2.0/projects/library/repositories/tools%252Fcurl",
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "library/tools/curl", "artifact_count": 2 })),
+            )
+            .mount(&server)
+            .await;
+
+        let artifact_count = client
+            .with_flavor(RegistryFlavor::Harbor)
+            .artifact_count("library/tools/curl")
+            .await
+            .unwrap();
+
+        assert_eq!(artifact_count, 2);
+    }
+
+    #[tokio::test]
+    async fn harbor_repository_deletion() {
+        let (server, client) = registry().await;
+        Mock::given(method("DELETE"))
+            .and(path(
+                "/api/v2.0/projects/library/repositories/tools%252Fcurl",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let plain = client.delete_repository("library/tools/curl").await;
+        client
+            .with_flavor(RegistryFlavor::Harbor)
+            .delete_repository("library/tools/curl")
+            .await
+            .unwrap();
+
+        assert!(plain.is_err(), "distribution cannot delete repositories");
+    }
+
+    #[tokio::test]
+    async fn harbor_storage_usage() {
+        let (server, client) = registry().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v2.0/quotas"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "hard": { "storage": 1000 }, "used": { "storage": 300 } },
+                { "hard": { "storage": -1 }, "used": { "storage": 200 } }
+            ])))
+            .mount(&server)
+            .await;
+
+        let plain = client.storage_usage().await.unwrap();
+        let harbor = client
+            .with_flavor(RegistryFlavor::Harbor)
+            .storage_usage()
+            .await
+            .unwrap();
+
+        assert!(plain.is_none(), "distribution has no storage usage");
+        assert_eq!(
+            harbor,
+            Some(StorageUsage {
+                used: 500,
+                quota: None
+            })
+        );
+    }
+}
@@ -1,32 +1,1564 @@
-use std::env;
-
 use axum::{
     Router,
-    response::Redirect,
-    routing::{get, post},
+    http::{HeaderName, HeaderValue},
+    middleware,
+    routing::{delete, get, post},
+};
+use tower_http::{
+    catch_panic::CatchPanicLayer, compression::CompressionLayer, services::ServeDir,
+    set_header::SetResponseHeaderLayer,
 };
-use tower_http::services::ServeDir;
-
-use crate::{AppState, auth, common, home, image};
 
-pub fn create_router() -> Router<AppState> {
-    let static_dir = env::var("STATIC_DIR").expect("STATIC_DIR");
+use crate::{
+    AppState, api, audit, auth, common, diagnostics, home, i18n, image,
+    metrics::{self, dto::Page},
+    registries::{self, service::RegistryRouters},
+};
 
+/// Serves the router of the registry selected by the `registry` cookie, see `create_router`
+pub fn create_multi_registry_router(routers: RegistryRouters) -> Router {
     Router::new()
-        .route("/", get(home::handler::index))
-        .route("/{image}", get(image::handler::index))
+        .route("/registry", post(registries::handler::select))
+        .fallback(registries::handler::dispatch)
+        .with_state(routers)
+}
+
+pub fn create_router(app_state: AppState) -> Router {
+    let mut router = Router::new()
+        .route(
+            "/",
+            get(home::handler::index).layer(middleware::from_fn_with_state(
+                Page::Home,
+                metrics::handler::track,
+            )),
+        )
+        .route("/catalog/refresh", post(home::handler::refresh_catalog))
+        .route("/events", get(home::handler::events))
+        .route("/density", post(common::handler::set_table_density))
+        .route(
+            "/{image}",
+            get(image::handler::index).layer(middleware::from_fn_with_state(
+                Page::Image,
+                metrics::handler::track,
+            )),
+        )
         .route(
-            "/{image}/delete",
-            post(home::handler::delete_all_image_tags),
+            "/{image}/tag/{tag}",
+            get(image::handler::tag_detail).layer(middleware::from_fn_with_state(
+                Page::Tag,
+                metrics::handler::track,
+            )),
         )
-        .route("/{image}/delete/{digest}", post(image::handler::delete_tag))
+        .route("/{image}/tag/{tag}/bundle.tar", get(image::handler::bundle))
         .route(
-            "/favicon.ico",
-            get(|| async { Redirect::permanent("/static/favicon.ico") }),
+            "/{image}/tag/{tag}/row-details",
+            get(image::handler::row_details),
         )
+        .route("/{image}/dangling", get(image::handler::dangling_manifests))
+        .route("/{image}/retention", get(image::handler::retention))
+        .route("/{image}/compare", get(image::handler::compare))
+        .route("/{image}/tags.txt", get(image::handler::tag_names))
+        .route("/{image}/digests.txt", get(image::handler::tag_digests))
+        .route("/{image}/columns", post(image::handler::set_tag_columns));
+
+    if !common::service::env::read_only() {
+        router = router
+            .route(
+                "/{image}/delete",
+                get(home::handler::confirm_delete_all_image_tags)
+                    .post(home::handler::delete_all_image_tags),
+            )
+            .route(
+                "/{image}/deletions/{job}",
+                get(home::handler::image_deletion),
+            )
+            .route("/{image}/delete/{digest}", post(image::handler::delete_tag))
+            .route(
+                "/{image}/repository/delete",
+                get(home::handler::confirm_delete_repository)
+                    .post(home::handler::delete_repository),
+            )
+            .route(
+                "/api/v1/repositories/{image}/tags/{tag}",
+                delete(api::handler::delete_tag),
+            )
+            .route(
+                "/{image}/dangling/delete",
+                post(image::handler::delete_dangling_manifests),
+            )
+            .route(
+                "/{image}/retention/delete",
+                post(image::handler::delete_old_tags),
+            );
+    }
+
+    // Only the pages needing the registry are replaced by the maintenance page
+    let router = router
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            common::handler::maintenance,
+        ))
+        .route(
+            "/maintenance/retry",
+            post(common::handler::retry_registry_probe),
+        )
+        .route("/favicon.ico", get(common::handler::favicon))
+        .route("/lang", post(i18n::handler::select))
         .route("/auth/login", get(auth::handler::login_index))
         .route("/auth/authenticate", post(auth::handler::authenticate))
         .route("/auth/logout", post(auth::handler::logout))
+        .route("/audit", get(audit::handler::index))
+        .route("/diagnostics", get(diagnostics::handler::index))
+        .route("/api/v1/repositories", get(api::handler::repositories))
         .route("/health", get(common::handler::health))
-        .nest_service("/static", ServeDir::new(static_dir))
+        .route("/metrics", get(metrics::handler::index))
+        .route_layer(middleware::from_fn(auth::middleware::slide_idle_timeout))
+        .fallback(common::handler::not_found)
+        // Static assets are served as is, only the rendered pages are compressed. Measured on a
+        // 100-tag image page: 136,762 bytes uncompressed, 5,394 with gzip, 3,781 with brotli
+        .layer(CompressionLayer::new().gzip(true).br(true));
+    let router = match common::service::env::static_dir() {
+        Some(static_dir) => router.nest_service("/static", ServeDir::new(static_dir)),
+        #[cfg(feature = "embed-static")]
+        None => router.route("/static/{*path}", get(common::handler::embedded_static)),
+        #[cfg(not(feature = "embed-static"))]
+        None => unreachable!("STATIC_DIR is required without the embed-static feature"),
+    };
+    let router = router.layer(CatchPanicLayer::custom(common::handler::panic_page));
+
+    let router = if common::service::env::allow_indexing() {
+        router
+    } else {
+        router
+            .route("/robots.txt", get(common::handler::robots_txt))
+            .layer(SetResponseHeaderLayer::overriding(
+                HeaderName::from_static("x-robots-tag"),
+                HeaderValue::from_static("noindex, nofollow"),
+            ))
+    };
+    let router = router.layer(middleware::from_fn(i18n::handler::localize));
+    let router = if common::service::env::access_log() {
+        router.layer(middleware::from_fn(common::handler::access_log))
+    } else {
+        router
+    };
+    router.with_state(app_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header},
+        response::Response,
+    };
+    use serde_json::json;
+    use tower::ServiceExt;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, path_regex},
+    };
+
+    use crate::{
+        AppState,
+        common::{self, service::RegistryProbe},
+        registries::dto::RegistryConfig,
+        router::{create_multi_registry_router, create_router},
+        test_support::{self, admin_cookie, app},
+    };
+
+    const DIGEST: &str = "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+
+    async fn get(registry: &MockServer, uri: &str, cookie: Option<&str>) -> Response {
+        let mut request = Request::get(uri);
+        if let Some(cookie) = cookie {
+            request = request.header(header::COOKIE, cookie);
+        }
+        app(&registry.uri())
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    async fn mount_tags(registry: &MockServer, image: &str, tags: &[&str]) {
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/{image}/tags/list")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "name": image, "tags": tags })),
+            )
+            .mount(registry)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn anonymous_users_are_sent_to_the_login_page() {
+        let registry = MockServer::start().await;
+
+        let response = get(&registry, "/alpine?page=1", None).await;
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/auth/login?from=%2Falpine%3Fpage%3D1"
+        );
+    }
+
+    #[tokio::test]
+    async fn login_only_redirects_to_explorer_paths() {
+        let registry = MockServer::start().await;
+        let login = |from: &str| {
+            let query = serde_urlencoded::to_string([("from", from)]).unwrap();
+            app(&registry.uri()).oneshot(
+                Request::post(format!("/auth/authenticate?{query}"))
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(format!(
+                        "username={}&password={}",
+                        test_support::USERNAME,
+                        test_support::PASSWORD
+                    )))
+                    .unwrap(),
+            )
+        };
+
+        for from in ["//evil.test", "/\\evil.test", "/\tevil.test"] {
+            let response = login(from).await.unwrap();
+            assert_eq!(response.headers()[header::LOCATION], "/", "{from:?}");
+        }
+        let response = login("/alpine?page=2").await.unwrap();
+        assert_eq!(response.headers()[header::LOCATION], "/alpine?page=2");
+    }
+
+    #[tokio::test]
+    async fn failed_login_keeps_the_username_out_of_the_url() {
+        let registry = MockServer::start().await;
+        let app = app(&registry.uri());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/auth/authenticate")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from("username=alice&password=wrong"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        assert_eq!(location, "/auth/login?error=invalid_credentials");
+        let cookie = response.headers()[header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+        let login = app
+            .oneshot(
+                Request::get(location)
+                    .header(header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(body(login).await.contains(r#"value="alice""#));
+    }
+
+    #[tokio::test]
+    async fn logout_confirms_on_the_login_page() {
+        let registry = MockServer::start().await;
+
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::post("/auth/logout")
+                    .header(header::COOKIE, admin_cookie())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        assert_eq!(location, "/auth/login?logged_out=1");
+        let login_page = body(get(&registry, location, None).await).await;
+        assert!(login_page.contains("You have been logged out"));
+    }
+
+    #[tokio::test]
+    async fn unreachable_registry_shows_the_maintenance_page_until_a_retry_succeeds() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &[]).await;
+        let app_state = AppState::for_tests(&registry.uri());
+        *app_state.registry_probe.write().unwrap() = RegistryProbe {
+            reachable: false,
+            checked_at: chrono::Utc::now(),
+        };
+        let app = create_router(app_state);
+        let page = |cookie: Option<String>| {
+            let mut request = Request::get("/alpine");
+            if let Some(cookie) = cookie {
+                request = request.header(header::COOKIE, cookie);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let anonymous = page(None).await.unwrap();
+        let maintenance = page(Some(admin_cookie())).await.unwrap();
+        let retry = app
+            .clone()
+            .oneshot(
+                Request::post("/maintenance/retry")
+                    .header(header::COOKIE, admin_cookie())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from("from=%2Falpine"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let after_retry = page(Some(admin_cookie())).await.unwrap();
+
+        assert_eq!(anonymous.status(), StatusCode::SEE_OTHER);
+        assert_eq!(maintenance.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body(maintenance).await.contains("Registry unavailable"));
+        assert_eq!(retry.headers()[header::LOCATION], "/alpine");
+        assert_eq!(after_retry.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn search_engines_are_kept_out_by_default() {
+        let registry = MockServer::start().await;
+
+        let response = get(&registry, "/robots.txt", None).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-robots-tag"], "noindex, nofollow");
+        assert!(body(response).await.contains("Disallow: /"));
+    }
+
+    #[tokio::test]
+    async fn tag_bundle_is_a_tar_of_manifest_and_config() {
+        let registry = MockServer::start().await;
+        let manifest = json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "config": { "digest": "sha256:c0ffee", "size": 2 },
+            "layers": [{ "digest": "sha256:1a7e4", "size": 10 }],
+        });
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/1.0"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", "sha256:0123456789abcdef")
+                    .set_body_json(&manifest),
+            )
+            .mount(&registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/blobs/sha256:c0ffee"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&registry)
+            .await;
+
+        let response = get(
+            &registry,
+            "/alpine/tag/1.0/bundle.tar",
+            Some(&admin_cookie()),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"alpine_1.0_0123456789ab.tar\""
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut archive = tar::Archive::new(bytes.as_ref());
+        let entries = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut content = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut content).unwrap();
+                (entry.path().unwrap().display().to_string(), content)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                ("manifest.json".to_owned(), manifest.to_string()),
+                ("config.json".to_owned(), "{}".to_owned()),
+                ("layers.txt".to_owned(), "sha256:1a7e4\n".to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn api_deletes_a_tag_by_name_with_the_tags_sharing_its_manifest() {
+        const DIGEST: &str = "sha256:5a1e";
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["latest", "3.20"]).await;
+        Mock::given(method("HEAD"))
+            .and(path("/v2/alpine/manifests/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&registry)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path_regex("^/v2/alpine/manifests/"))
+            .respond_with(ResponseTemplate::new(200).insert_header("docker-content-digest", DIGEST))
+            .mount(&registry)
+            .await;
+        // The tags sharing the manifest are resolved with `HEAD`s only
+        Mock::given(method("GET"))
+            .and(path_regex("^/v2/alpine/manifests/"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&registry)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v2/alpine/manifests/{DIGEST}")))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&registry)
+            .await;
+        let delete = |uri: &'static str| {
+            app(&registry.uri()).oneshot(
+                Request::delete(uri)
+                    .header(header::COOKIE, admin_cookie())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let missing = delete("/api/v1/repositories/alpine/tags/missing")
+            .await
+            .unwrap();
+        let response = delete("/api/v1/repositories/alpine/tags/latest")
+            .await
+            .unwrap();
+
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.status(), StatusCode::OK);
+        let deletion = serde_json::from_str::<serde_json::Value>(&body(response).await).unwrap();
+        assert_eq!(
+            deletion,
+            json!({ "digest": DIGEST, "deleted_tags": ["latest", "3.20"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn home_page_lists_repositories_with_their_tag_count() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "repositories": ["alpine", "nginx", "redis"] })),
+            )
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &["3.19", "3.20"]).await;
+        mount_tags(&registry, "nginx", &["latest"]).await;
+        mount_tags(&registry, "redis", &["7"]).await;
+
+        let response = get(&registry, "/?page=0&size=2", Some(&admin_cookie())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        assert!(html.contains(r#"href="alpine""#));
+        assert!(html.contains(r#"href="nginx""#));
+        assert!(
+            !html.contains(r#"href="redis""#),
+            "redis is on the second page"
+        );
+        assert!(html.contains("Next"));
+    }
+
+    #[tokio::test]
+    async fn api_refuses_an_empty_page_size_and_a_page_past_the_end() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "repositories": ["alpine"] })),
+            )
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &["latest"]).await;
+
+        let empty_size = get(
+            &registry,
+            "/api/v1/repositories?size=0",
+            Some(&admin_cookie()),
+        )
+        .await;
+        let past_the_end = get(
+            &registry,
+            "/api/v1/repositories?page=3&size=2",
+            Some(&admin_cookie()),
+        )
+        .await;
+        let first_page = get(
+            &registry,
+            "/api/v1/repositories?page=0&size=2",
+            Some(&admin_cookie()),
+        )
+        .await;
+
+        assert_eq!(empty_size.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(past_the_end.status(), StatusCode::BAD_REQUEST);
+        assert!(body(past_the_end).await.contains("past the last page"));
+        assert_eq!(first_page.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn empty_catalog_is_told_apart_from_an_unreachable_registry() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "repositories": [] })))
+            .up_to_n_times(1)
+            .mount(&registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&registry)
+            .await;
+
+        let empty = body(get(&registry, "/", Some(&admin_cookie())).await).await;
+        let unreachable = body(get(&registry, "/", Some(&admin_cookie())).await).await;
+        let second_page = body(
+            get(
+                &registry,
+                "/?page=1&size=2&refresh=1",
+                Some(&admin_cookie()),
+            )
+            .await,
+        )
+        .await;
+
+        assert!(empty.contains("No repositories yet"));
+        assert!(empty.contains("<title>Repositories \u{2013} "));
+        assert!(!empty.contains("alert-danger"));
+        assert!(unreachable.contains("Could not reach the registry"));
+        assert!(unreachable.contains("503"));
+        assert!(unreachable.contains("href=\"/?refresh=1\""));
+        assert!(second_page.contains("href=\"/?page=1&amp;size=2&amp;refresh=1\""));
+    }
+
+    #[tokio::test]
+    async fn hidden_repositories_are_revealed_on_demand() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "repositories": ["alpine", "cache/build"] })),
+            )
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &["3.20"]).await;
+        mount_tags(&registry, "cache/build", &["buildcache"]).await;
+
+        let hidden = body(get(&registry, "/", Some(&admin_cookie())).await).await;
+        let shown = body(get(&registry, "/?show_hidden=true", Some(&admin_cookie())).await).await;
+
+        assert!(hidden.contains("alpine"));
+        assert!(!hidden.contains("cache/build"));
+        assert!(hidden.contains("Show hidden repositories"));
+        assert!(shown.contains("cache/build"));
+        assert!(shown.contains("Hide hidden repositories"));
+    }
+
+    #[tokio::test]
+    async fn registry_calls_are_attributed_to_the_rendered_page() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "repositories": ["alpine"] })),
+            )
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &["3.20"]).await;
+
+        get(&registry, "/", Some(&admin_cookie())).await;
+        let metrics = body(get(&registry, "/metrics", None).await).await;
+
+        // Other tests render pages concurrently, only lower bounds hold
+        let value = |name: &str| {
+            metrics
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .unwrap()
+        };
+        assert!(value("explorer_page_renders_total{page=\"home\"}") >= 1);
+        assert!(value("explorer_registry_calls_total{page=\"home\"}") >= 2);
+    }
+
+    #[tokio::test]
+    async fn missing_favicon_falls_back_to_the_embedded_one() {
+        let registry = MockServer::start().await;
+
+        let response = get(&registry, "/favicon.ico", None).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/svg+xml");
+        assert!(body(response).await.contains("<svg"));
+    }
+
+    #[tokio::test]
+    async fn compact_density_is_remembered() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "repositories": ["alpine"] })),
+            )
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &["3.20"]).await;
+
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::post("/density")
+                    .header(header::COOKIE, admin_cookie())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from("density=compact&from=%2F%3Fsort%3Dpulls"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()[header::LOCATION], "/?sort=pulls");
+        let density_cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(density_cookie.starts_with("table_density=compact"));
+        let cookies = format!("{}; table_density=compact", admin_cookie());
+        let html = body(get(&registry, "/", Some(&cookies)).await).await;
+        assert!(html.contains("table-sm"));
+        assert!(html.contains("Comfortable rows"));
+    }
+
+    #[tokio::test]
+    async fn page_past_the_end_shows_the_last_page() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "repositories": ["alpine", "nginx", "redis"] })),
+            )
+            .mount(&registry)
+            .await;
+        for image in ["alpine", "nginx", "redis"] {
+            mount_tags(&registry, image, &["7"]).await;
+        }
+
+        let response = get(&registry, "/?goto=42&size=2", Some(&admin_cookie())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        assert!(html.contains(r#"href="redis""#));
+        assert!(html.contains("2 / 2"));
+    }
+
+    #[tokio::test]
+    async fn empty_repositories_are_left_out_before_paginating() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "repositories": ["alpine", "emptied", "redis"] })),
+            )
+            .mount(&registry)
+            .await;
+        mount_tags(&registry, "alpine", &["3.20"]).await;
+        mount_tags(&registry, "emptied", &[]).await;
+        mount_tags(&registry, "redis", &["7"]).await;
+
+        let html = body(get(&registry, "/?size=2", Some(&admin_cookie())).await).await;
+
+        assert!(html.contains(r#"href="alpine""#));
+        assert!(html.contains(r#"href="redis""#));
+        assert!(!html.contains(r#"href="emptied""#));
+    }
+
+    #[tokio::test]
+    async fn image_page_lists_tags() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["latest"]).await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/latest"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", DIGEST)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 2,
+                            "config": {
+                                "mediaType": "application/vnd.oci.image.config.v1+json",
+                                "digest": "sha256:config"
+                            },
+                            "layers": [{ "digest": "sha256:layer", "size": 2048 }]
+                        })
+                        .to_string(),
+                        "application/vnd.oci.image.manifest.v1+json",
+                    ),
+            )
+            .mount(&registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/blobs/sha256:config"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                json!({ "architecture": "arm64", "created": "2024-05-01T12:00:00Z" }),
+            ))
+            .mount(&registry)
+            .await;
+
+        let response = get(&registry, "/alpine", Some(&admin_cookie())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        assert!(html.contains("/alpine/tag/latest"));
+        assert!(html.contains(DIGEST));
+        assert!(html.contains("arm64"));
+    }
+
+    /// `tag` of `alpine` pointing to `digest`, created at `created` when given
+    async fn mount_dated_tag(
+        registry: &MockServer,
+        tag: &str,
+        digest: &str,
+        created: Option<&str>,
+    ) {
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/manifests/{tag}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", digest)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 2,
+                            "config": {
+                                "mediaType": "application/vnd.oci.image.config.v1+json",
+                                "digest": format!("sha256:config-{tag}")
+                            },
+                            "layers": []
+                        })
+                        .to_string(),
+                        "application/vnd.oci.image.manifest.v1+json",
+                    ),
+            )
+            .mount(registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/alpine/blobs/sha256:config-{tag}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                created.map_or_else(|| json!({}), |created| json!({ "created": created })),
+            ))
+            .mount(registry)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn recent_filter_leaves_out_old_and_undated_tags() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["fresh", "old", "undated"]).await;
+        let an_hour_ago = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+        mount_dated_tag(&registry, "fresh", "sha256:fresh", Some(&an_hour_ago)).await;
+        mount_dated_tag(&registry, "old", "sha256:old", Some("2024-05-01T12:00:00Z")).await;
+        mount_dated_tag(&registry, "undated", "sha256:undated", None).await;
+
+        let recent = body(
+            get(
+                &registry,
+                "/alpine?created_within=24h",
+                Some(&admin_cookie()),
+            )
+            .await,
+        )
+        .await;
+        let all = body(get(&registry, "/alpine", Some(&admin_cookie())).await).await;
+
+        assert!(recent.contains(">fresh<"));
+        assert!(!recent.contains(">old<"));
+        assert!(!recent.contains(">undated<"));
+        assert!(all.contains(">old<"));
+        assert!(all.contains(">undated<"));
+    }
+
+    #[tokio::test]
+    async fn name_sort_only_resolves_the_manifests_of_the_page() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["a", "b", "c"]).await;
+        mount_dated_tag(&registry, "a", "sha256:a", None).await;
+        mount_dated_tag(&registry, "b", "sha256:b", None).await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/c"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(0)
+            .mount(&registry)
+            .await;
+
+        let html =
+            body(get(&registry, "/alpine?sort=name&size=2", Some(&admin_cookie())).await).await;
+
+        assert!(html.contains(">a<"));
+        assert!(html.contains(">b<"));
+        assert!(!html.contains("Unique size"));
+    }
+
+    #[tokio::test]
+    async fn old_tags_are_deleted_unless_a_newer_tag_shares_their_manifest() {
+        let registry = MockServer::start().await;
+        mount_tags(
+            &registry,
+            "alpine",
+            &["1.0", "1.0.0", "1.1", "latest", "dev"],
+        )
+        .await;
+        let an_hour_ago = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+        let old = Some("2024-05-01T12:00:00Z");
+        mount_dated_tag(&registry, "1.0", "sha256:old", old).await;
+        mount_dated_tag(&registry, "1.0.0", "sha256:old", old).await;
+        mount_dated_tag(&registry, "1.1", "sha256:shared", old).await;
+        mount_dated_tag(&registry, "latest", "sha256:shared", Some(&an_hour_ago)).await;
+        mount_dated_tag(&registry, "dev", "sha256:undated", None).await;
+        Mock::given(method("HEAD"))
+            .and(path("/v2/alpine/manifests/sha256:old"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&registry)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v2/alpine/manifests/sha256:old"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&registry)
+            .await;
+
+        let plan = body(
+            get(
+                &registry,
+                "/alpine/retention?days=30",
+                Some(&admin_cookie()),
+            )
+            .await,
+        )
+        .await;
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::post("/alpine/retention/delete")
+                    .header(header::COOKIE, admin_cookie())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from("days=30&digests=sha256:old"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(plan.contains("1.0, 1.0.0"));
+        assert!(plan.contains("Delete 2 tag(s), 1 manifest(s)"));
+        assert!(plan.contains("shares their manifest: 1.1"));
+        assert!(plan.contains("name=\"digests\" value=\"sha256:old\""));
+        assert!(!plan.contains("sha256:undated"));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body(response).await.contains("sha256:old"));
+    }
+
+    #[tokio::test]
+    async fn retention_dry_run_deletes_nothing() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["1.0"]).await;
+        mount_dated_tag(&registry, "1.0", "sha256:old", Some("2024-05-01T12:00:00Z")).await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&registry)
+            .await;
+
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::post("/alpine/retention/delete?dry_run=1")
+                    .header(header::COOKIE, admin_cookie())
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from("days=30&digests=sha256:old"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        assert!(html.contains("Would delete 1 tag(s) and 1 manifest(s)"));
+        assert!(html.contains("sha256:old"));
+        let no_days = get(&registry, "/alpine/retention?days=0", Some(&admin_cookie())).await;
+        assert_eq!(no_days.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn version_sort_puts_releases_first_and_flags_the_latest() {
+        let registry = MockServer::start().await;
+        mount_tags(
+            &registry,
+            "alpine",
+            &["latest", "1.2.0", "v1.10", "2.0.0-rc.1"],
+        )
+        .await;
+        Mock::given(method("GET"))
+            .and(path_regex("^/v2/alpine/manifests/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", DIGEST)
+                    .set_body_raw(
+                        json!({
+                            "schemaVersion": 2,
+                            "config": {
+                                "mediaType": "application/vnd.oci.image.config.v1+json",
+                                "digest": "sha256:config"
+                            },
+                            "layers": []
+                        })
+                        .to_string(),
+                        "application/vnd.oci.image.manifest.v1+json",
+                    ),
+            )
+            .mount(&registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/blobs/sha256:config"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&registry)
+            .await;
+
+        let response = get(&registry, "/alpine?sort=semver", Some(&admin_cookie())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        let position = |text: &str| html.find(text).unwrap();
+        assert!(position(">v1.10<") < position(">1.2.0<"));
+        assert!(position(">1.2.0<") < position("Pre-releases"));
+        assert!(position("Pre-releases") < position(">2.0.0-rc.1<"));
+        assert!(position(">2.0.0-rc.1<") < position("Other tags"));
+        assert!(position("Other tags") < position(">latest<"));
+        assert_eq!(html.matches("latest release").count(), 1);
+        assert!(position(">v1.10<") < position("latest release"));
+        assert!(position("latest release") < position(">1.2.0<"));
+    }
+
+    #[tokio::test]
+    async fn repository_search_asks_the_registry() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "unlisted", &["latest"]).await;
+        Mock::given(method("GET"))
+            .and(path("/v2/failing/tags/list"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&registry)
+            .await;
+
+        let found = get(&registry, "/?repository=unlisted", Some(&admin_cookie())).await;
+        let missing = get(&registry, "/?repository=missing", Some(&admin_cookie())).await;
+        let failing = get(&registry, "/?repository=failing", Some(&admin_cookie())).await;
+
+        assert_eq!(found.status(), StatusCode::SEE_OTHER);
+        assert_eq!(found.headers()[header::LOCATION], "/unlisted");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert!(body(missing).await.contains("No repository named missing"));
+        assert_eq!(failing.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn compared_tags_show_their_layer_and_environment_differences() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["1.0", "1.1"]).await;
+        for (tag, layers, env) in [
+            ("1.0", ["sha256:base", "sha256:app-1.0"], "VERSION=1.0"),
+            ("1.1", ["sha256:base", "sha256:app-1.1"], "VERSION=1.1"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/v2/alpine/manifests/{tag}")))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("docker-content-digest", format!("sha256:{tag}"))
+                        .set_body_raw(
+                            json!({
+                                "schemaVersion": 2,
+                                "config": {
+                                    "mediaType": "application/vnd.oci.image.config.v1+json",
+                                    "digest": format!("sha256:config-{tag}")
+                                },
+                                "layers": layers.map(|digest| json!({ "digest": digest, "size": 100 }))
+                            })
+                            .to_string(),
+                            "application/vnd.oci.image.manifest.v1+json",
+                        ),
+                )
+                .mount(&registry)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(format!("/v2/alpine/blobs/sha256:config-{tag}")))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(json!({ "config": { "Env": [env] } })),
+                )
+                .mount(&registry)
+                .await;
+        }
+
+        let response = get(
+            &registry,
+            "/alpine/compare?a=1.0&b=1.1",
+            Some(&admin_cookie()),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        assert!(html.contains(r#"<td class="text-muted" title="sha256:base">"#));
+        assert!(html.contains(r#"<td class="table-danger" title="sha256:app-1.0">"#));
+        assert!(html.contains(r#"<td class="table-success" title="sha256:app-1.1">"#));
+        assert!(html.contains("1 shared (100 B)"));
+        assert!(html.contains(r#"<tr class="table-warning"><th class="fw-normal"><code class="text-reset">VERSION</code>"#));
+    }
+
+    #[tokio::test]
+    async fn diagnostics_probe_the_registry_capabilities() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .insert_header("docker-distribution-api-version", "registry/2.0")
+                    .insert_header("www-authenticate", r#"Basic realm="registry""#),
+            )
+            .mount(&registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/_catalog"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "repositories": ["alpine"] })),
+            )
+            .mount(&registry)
+            .await;
+
+        let response = get(&registry, "/diagnostics", Some(&admin_cookie())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body(response).await;
+        assert!(html.contains(r#"<td class="text-success">registry/2.0</td>"#));
+        assert!(html.contains("<td>Basic</td>"));
+        assert!(html.contains(r#"<td class="text-success">Listed</td>"#));
+        assert!(html.contains("<td>Not supported</td>"));
+    }
+
+    #[tokio::test]
+    async fn selected_registry_serves_the_pages() {
+        let registries = [MockServer::start().await, MockServer::start().await];
+        for (registry, repository) in registries.iter().zip(["alpine", "nginx"]) {
+            Mock::given(method("GET"))
+                .and(path("/v2/_catalog"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({ "repositories": [repository] })),
+                )
+                .mount(registry)
+                .await;
+            mount_tags(registry, repository, &["latest"]).await;
+        }
+        let routers = registries
+            .iter()
+            .zip(["first", "second"])
+            .map(|(registry, name)| {
+                let config: &'static RegistryConfig = Box::leak(Box::new(RegistryConfig {
+                    name: name.to_owned(),
+                    url: registry.uri(),
+                    username: String::new(),
+                    password: String::new(),
+                }));
+                (config, create_router(AppState::for_tests(&registry.uri())))
+            })
+            .collect();
+        let app = create_multi_registry_router(Arc::new(routers));
+        let home = async |registry: &str| {
+            let cookie = format!("{}; registry={registry}", admin_cookie());
+            let request = Request::get("/").header(header::COOKIE, cookie);
+            body(
+                app.clone()
+                    .oneshot(request.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap(),
+            )
+            .await
+        };
+
+        let second = home("second").await;
+        let unknown = home("removed").await;
+
+        assert!(second.contains(r#"href="nginx""#));
+        assert!(!second.contains(r#"href="alpine""#));
+        assert!(
+            unknown.contains(r#"href="alpine""#),
+            "the first registry is the default"
+        );
+    }
+
+    #[tokio::test]
+    async fn dangling_manifests_leave_out_the_referrers_of_tags() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["latest", "signature"]).await;
+        mount_dated_tag(&registry, "latest", "sha256:old", None).await;
+        mount_dated_tag(&registry, "signature", "sha256:signature", None).await;
+        let app = app(&registry.uri());
+        let page = |uri: &'static str| {
+            app.clone().oneshot(
+                Request::get(uri)
+                    .header(header::COOKIE, admin_cookie())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        page("/alpine").await.unwrap();
+        registry.reset().await;
+        mount_tags(&registry, "alpine", &["latest"]).await;
+        mount_dated_tag(&registry, "latest", "sha256:new", None).await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/referrers/sha256:new"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schemaVersion": 2,
+                "manifests": [{ "digest": "sha256:signature" }]
+            })))
+            .mount(&registry)
+            .await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&registry)
+            .await;
+
+        let html = body(page("/alpine/dangling").await.unwrap()).await;
+
+        assert!(html.contains("sha256:old"));
+        assert!(!html.contains("sha256:signature"));
+    }
+
+    #[tokio::test]
+    async fn signed_column_shows_the_cosign_signer() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["signed", "unsigned"]).await;
+        mount_dated_tag(&registry, "signed", "sha256:signed", None).await;
+        mount_dated_tag(&registry, "unsigned", "sha256:unsigned", None).await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/referrers/sha256:signed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "schemaVersion": 2,
+                "manifests": [{
+                    "digest": "sha256:signature",
+                    "artifactType": "application/vnd.dev.cosign.artifact.sig.v1+json"
+                }]
+            })))
+            .mount(&registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/referrers/sha256:unsigned"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "schemaVersion": 2, "manifests": [] })),
+            )
+            .mount(&registry)
+            .await;
+        // Self-signed, with an email alternative name and the Fulcio issuer extension
+        let pem = "-----BEGIN CERTIFICATE-----\n\
+MIIBmjCCAUGgAwIBAgIUCUq5pmPNRaxiwBGOJdMvi4OME4IwCgYIKoZIzj0EAwIw\n\
+FzEVMBMGA1UECgwMc2lnc3RvcmUuZGV2MB4XDTI2MTAxNjExMzIzN1oXDTI2MTAx\n\
+NzExMzIzN1owFzEVMBMGA1UECgwMc2lnc3RvcmUuZGV2MFkwEwYHKoZIzj0CAQYI\n\
+KoZIzj0DAQcDQgAELle+ynK7RixNRP9CTJaRe2r0/Jg1iBiQwcN5cAnFdSVT0EZH\n\
++7smsZmeM3P6I0hWemUK3N+Js5earMgbWYsioaNrMGkwGgYDVR0RBBMwEYEPZGV2\n\
+QGV4YW1wbGUuY29tMCwGCisGAQQBg78wAQgEHgwcaHR0cHM6Ly9hY2NvdW50cy5l\n\
+eGFtcGxlLmNvbTAdBgNVHQ4EFgQU8SMJqaEkBAXEpMfx8G/MLDNnadQwCgYIKoZI\n\
+zj0EAwIDRwAwRAIgJQHRcQkc7++1dDoki7SlFR4zJWZ/f1H4Hyki06jBV3ECIDKB\n\
+LGLcuVGLi5akFi2SqgBLReNwPPLliUHe/fiCtuTv\n\
+-----END CERTIFICATE-----\n";
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/manifests/sha256:signature"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("docker-content-digest", "sha256:signature")
+                    .set_body_json(json!({
+                        "schemaVersion": 2,
+                        "layers": [{
+                            "digest": "sha256:payload",
+                            "annotations": { "dev.sigstore.cosign/certificate": pem }
+                        }]
+                    })),
+            )
+            .mount(&registry)
+            .await;
+
+        let cookie = format!("{}; tag_columns=tag.signed", admin_cookie());
+        let html = body(get(&registry, "/alpine", Some(&cookie)).await).await;
+
+        assert!(html.contains(
+            r#"title="Signed by dev@example.com, issued by https://accounts.example.com""#
+        ));
+        assert!(html.contains(r#"title="No cosign signature""#));
+    }
+
+    #[tokio::test]
+    async fn all_tag_digests_are_listed_as_plain_text() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "name": "alpine", "tags": ["1.0", "1.1", "latest"] })),
+            )
+            .mount(&registry)
+            .await;
+        for (tag, digest) in [
+            ("1.0", "sha256:aa"),
+            ("1.1", "sha256:bb"),
+            ("latest", "sha256:bb"),
+        ] {
+            Mock::given(method("HEAD"))
+                .and(path(format!("/v2/alpine/manifests/{tag}")))
+                .respond_with(
+                    ResponseTemplate::new(200).insert_header("docker-content-digest", digest),
+                )
+                .mount(&registry)
+                .await;
+        }
+
+        let tags = get(&registry, "/alpine/tags.txt", Some(&admin_cookie())).await;
+        let digests = get(&registry, "/alpine/digests.txt", Some(&admin_cookie())).await;
+
+        assert_eq!(
+            tags.headers()[header::CONTENT_TYPE],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(body(tags).await, "1.0\n1.1\nlatest\n");
+        assert_eq!(body(digests).await, "sha256:aa\nsha256:bb\n");
+    }
+
+    #[tokio::test]
+    async fn unresolved_tags_are_left_out_of_the_digests() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["1.0", "broken"]).await;
+        Mock::given(method("HEAD"))
+            .and(path("/v2/alpine/manifests/1.0"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("docker-content-digest", "sha256:aa"),
+            )
+            .mount(&registry)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/v2/alpine/manifests/broken"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&registry)
+            .await;
+
+        let digests = get(&registry, "/alpine/digests.txt", Some(&admin_cookie())).await;
+
+        assert_eq!(digests.status(), StatusCode::OK);
+        assert_eq!(digests.headers()["x-unresolved-tags"], "broken");
+        assert_eq!(body(digests).await, "sha256:aa\n");
+    }
+
+    #[tokio::test]
+    async fn pages_follow_the_lang_cookie_then_accept_language() {
+        let registry = MockServer::start().await;
+        let login = |cookie: Option<&'static str>| {
+            let mut request = Request::get("/auth/login")
+                .header(header::ACCEPT_LANGUAGE, "de-DE, fr-CA;q=0.8, en;q=0.5");
+            if let Some(cookie) = cookie {
+                request = request.header(header::COOKIE, cookie);
+            }
+            app(&registry.uri()).oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let negotiated = body(login(None).await.unwrap()).await;
+        let chosen = body(login(Some("lang=en")).await.unwrap()).await;
+
+        assert!(negotiated.contains("<html lang=\"fr\">"));
+        assert!(negotiated.contains("Connexion"));
+        assert!(chosen.contains("<html lang=\"en\">"));
+        assert!(chosen.contains("Login"));
+    }
+
+    #[tokio::test]
+    async fn delete_all_redirects_to_its_results() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["1.0", "1.0.0", "1.1"]).await;
+        mount_dated_tag(&registry, "1.0", "sha256:one", None).await;
+        mount_dated_tag(&registry, "1.0.0", "sha256:one", None).await;
+        mount_dated_tag(&registry, "1.1", "sha256:two", None).await;
+        Mock::given(method("HEAD"))
+            .and(path_regex("^/v2/alpine/manifests/sha256:"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&registry)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v2/alpine/manifests/sha256:one"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&registry)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v2/alpine/manifests/sha256:two"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&registry)
+            .await;
+        let app = app(&registry.uri());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/alpine/delete")
+                    .header(header::COOKIE, admin_cookie())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let mut results = String::new();
+        for _ in 0..50 {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::get(&location)
+                        .header(header::COOKIE, admin_cookie())
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            results = body(response).await;
+            if !results.contains("http-equiv=\"refresh\"") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        assert!(location.starts_with("/alpine/deletions/"));
+        assert!(results.contains("Deleted 2 tag(s) of alpine (1 manifest(s))"));
+        assert!(results.contains("could not delete: 1.1"));
+        assert!(results.contains(">Retry<"));
+    }
+
+    #[tokio::test]
+    async fn registry_failures_render_the_error_page() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/alpine/tags/list"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&registry)
+            .await;
+
+        let index = get(&registry, "/alpine", Some(&admin_cookie())).await;
+
+        assert_eq!(index.status(), StatusCode::BAD_GATEWAY);
+        let page = body(index).await;
+        assert!(page.contains("502 Bad Gateway"));
+        assert!(page.contains("Could not load the tags of alpine."));
+    }
+
+    #[tokio::test]
+    async fn health_serves_the_last_probe_without_calling_the_registry() {
+        let registry = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&registry)
+            .await;
+
+        let health: serde_json::Value =
+            serde_json::from_str(&body(get(&registry, "/health", None).await).await).unwrap();
+
+        assert_eq!(health["registry_reachable"], true);
+        assert!(health["registry_checked_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn unresolved_protected_tag_refuses_the_deletion_and_says_so() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["latest", "release-1"]).await;
+        mount_dated_tag(&registry, "latest", DIGEST, None).await;
+        Mock::given(method("HEAD"))
+            .and(path("/v2/alpine/manifests/release-1"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&registry)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&registry)
+            .await;
+
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::post(format!("/alpine/delete/{DIGEST}"))
+                    .header(header::COOKIE, admin_cookie())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let flash = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(flash.contains("Nothing+was+deleted"));
+        assert!(flash.contains("release-1"));
+    }
+
+    #[test]
+    fn redacted_query_hides_credential_values_only() {
+        assert_eq!(
+            common::service::redacted_query("page=2&Password=hunter2&token=abc&sort=name&flag"),
+            "page=2&Password=REDACTED&token=REDACTED&sort=name&flag"
+        );
+    }
+
+    #[tokio::test]
+    async fn cross_site_form_posts_are_refused_despite_the_session_cookie() {
+        let registry = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&registry)
+            .await;
+
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::post(format!("/alpine/delete/{DIGEST}"))
+                    .header(header::COOKIE, admin_cookie())
+                    .header("sec-fetch-site", "cross-site")
+                    .header(header::ORIGIN, "https://evil.test")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    async fn post_delete_tag(registry: &MockServer) -> Response {
+        Mock::given(method("HEAD"))
+            .and(path_regex("^/v2/alpine/manifests/"))
+            .respond_with(ResponseTemplate::new(200).insert_header("docker-content-digest", DIGEST))
+            .mount(registry)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("^/v2/alpine/manifests/"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(registry)
+            .await;
+        app(&registry.uri())
+            .oneshot(
+                Request::post(format!("/alpine/delete/{DIGEST}"))
+                    .header(header::COOKIE, admin_cookie())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deleting_a_tag_resolves_the_tags_sharing_it_without_fetching_manifests() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["latest", "3.20"]).await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/v2/alpine/manifests/{DIGEST}")))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&registry)
+            .await;
+
+        let response = post_delete_tag(&registry).await;
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+    }
+
+    #[tokio::test]
+    async fn protected_tag_deletion_is_refused_before_resolving_the_tags_sharing_it() {
+        let registry = MockServer::start().await;
+        mount_tags(&registry, "alpine", &["latest", "release-1"]).await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&registry)
+            .await;
+
+        let response = post_delete_tag(&registry).await;
+
+        let flash = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(flash.contains("protected"));
+        let tag_lists = registry
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.url.path() == "/v2/alpine/tags/list")
+            .count();
+        assert_eq!(tag_lists, 1);
+    }
+
+    #[tokio::test]
+    async fn logout_is_only_offered_to_authenticated_users() {
+        let registry = MockServer::start().await;
+
+        let not_found = get(&registry, "/no/such/page", None).await;
+        let login = get(&registry, "/auth/login", None).await;
+        let audit = get(&registry, "/audit", Some(&admin_cookie())).await;
+
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+        assert!(!body(not_found).await.contains("/auth/logout"));
+        assert!(!body(login).await.contains("/auth/logout"));
+        assert!(body(audit).await.contains("/auth/logout"));
+    }
+
+    #[tokio::test]
+    async fn error_pages_are_translated() {
+        let registry = MockServer::start().await;
+
+        let response = app(&registry.uri())
+            .oneshot(
+                Request::get("/no/such/page")
+                    .header(header::COOKIE, "lang=fr")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(body(response).await.contains("Retour aux images"));
+    }
 }
@@ -1,8 +1,18 @@
 "use strict";
 
 function copyToClipboard(element) {
-  const image = element.dataset.image;
+  const text = element.dataset.clipboard;
   const clipboard = navigator.clipboard;
-  clipboard.writeText(image);
+  clipboard.writeText(text);
+  element.textContent = "\u{2713}";
+}
+
+async function copyFromUrl(element) {
+  const response = await fetch(element.dataset.clipboardUrl);
+  if (!response.ok) {
+    element.textContent = "\u{2717}";
+    return;
+  }
+  await navigator.clipboard.writeText(await response.text());
   element.textContent = "\u{2713}";
 }
@@ -0,0 +1,16 @@
+"use strict";
+
+// The background refresh (HOME_REFRESH_SECS) announces catalog changes on /events
+const events = new EventSource("/events");
+
+events.addEventListener("catalog", () => {
+  // A page in the background reloads once it is shown again
+  if (document.visibilityState === "visible") {
+    location.reload();
+  } else {
+    document.addEventListener("visibilitychange", () => location.reload(), { once: true });
+  }
+});
+
+// Closed by the server on shutdown, the browser reconnects on its own afterwards
+window.addEventListener("pagehide", () => events.close());
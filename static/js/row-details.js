@@ -0,0 +1,29 @@
+"use strict";
+
+// Rows rendered without their details (MAX_ENRICHED_TAGS) fetch them once the page is shown
+const ROW_DETAILS_CONCURRENCY = 4;
+
+async function loadRowDetails(row) {
+  const response = await fetch(row.dataset.rowDetails);
+  if (!response.ok) {
+    return;
+  }
+  const template = document.createElement("template");
+  template.innerHTML = `<table><tbody><tr>${await response.text()}</tr></tbody></table>`;
+  for (const cell of template.content.querySelectorAll("td[data-column]")) {
+    const placeholder = row.querySelector(`td[data-column="${cell.dataset.column}"]`);
+    if (placeholder) {
+      placeholder.replaceWith(cell);
+    }
+  }
+}
+
+document.addEventListener("DOMContentLoaded", async () => {
+  const rows = [...document.querySelectorAll("tr[data-row-details]")];
+  const workers = Array.from({ length: ROW_DETAILS_CONCURRENCY }, async () => {
+    while (rows.length > 0) {
+      await loadRowDetails(rows.shift()).catch(() => {});
+    }
+  });
+  await Promise.all(workers);
+});